@@ -19,7 +19,8 @@ impl<'a> TryFrom<&'a SerializedEvtxRecord<Value>> for ActivityId<'a> {
 
 impl<'a> Display for ActivityId<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0.as_str().unwrap())
+        // records without correlation data have no activity id
+        write!(f, "{}", self.0.as_str().unwrap_or_default())
     }
 }
 
@@ -0,0 +1,45 @@
+use std::fmt::Display;
+
+use anyhow::anyhow;
+use evtx::SerializedEvtxRecord;
+use serde_json::Value;
+
+use super::{truncate, EvtxFieldView};
+
+/// the name of the channel the event was logged to, e.g. `Security`
+#[derive(PartialEq, Eq, Clone)]
+pub struct Channel<'a>(&'a str);
+
+impl<'a> TryFrom<&'a SerializedEvtxRecord<Value>> for Channel<'a> {
+    type Error = anyhow::Error;
+
+    fn try_from(record: &'a SerializedEvtxRecord<Value>) -> Result<Self, Self::Error> {
+        record.data["Event"]["System"]["Channel"]
+            .as_str()
+            .map(Self)
+            .ok_or_else(|| anyhow!("missing channel name"))
+    }
+}
+
+impl<'a> Display for Channel<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+pub const CHANNEL_MAX_LENGTH: usize = 30;
+impl<'a> EvtxFieldView for Channel<'a> {
+    fn maximum_display_length(&self) -> usize {
+        CHANNEL_MAX_LENGTH
+    }
+
+    fn value_with_padding(&self) -> String {
+        format!("{:30}", truncate(self.0, CHANNEL_MAX_LENGTH))
+    }
+}
+
+impl<'a> Channel<'a> {
+    pub fn value(&self) -> &str {
+        self.0
+    }
+}
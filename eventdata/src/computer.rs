@@ -0,0 +1,45 @@
+use std::fmt::Display;
+
+use anyhow::anyhow;
+use evtx::SerializedEvtxRecord;
+use serde_json::Value;
+
+use super::{truncate, EvtxFieldView};
+
+/// the name of the computer on which the event occurred
+#[derive(PartialEq, Eq, Clone)]
+pub struct Computer<'a>(&'a str);
+
+impl<'a> TryFrom<&'a SerializedEvtxRecord<Value>> for Computer<'a> {
+    type Error = anyhow::Error;
+
+    fn try_from(record: &'a SerializedEvtxRecord<Value>) -> Result<Self, Self::Error> {
+        record.data["Event"]["System"]["Computer"]
+            .as_str()
+            .map(Self)
+            .ok_or_else(|| anyhow!("missing computer name"))
+    }
+}
+
+impl<'a> Display for Computer<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+pub const COMPUTER_MAX_LENGTH: usize = 30;
+impl<'a> EvtxFieldView for Computer<'a> {
+    fn maximum_display_length(&self) -> usize {
+        COMPUTER_MAX_LENGTH
+    }
+
+    fn value_with_padding(&self) -> String {
+        format!("{:30}", truncate(self.0, COMPUTER_MAX_LENGTH))
+    }
+}
+
+impl<'a> Computer<'a> {
+    pub fn value(&self) -> &str {
+        self.0
+    }
+}
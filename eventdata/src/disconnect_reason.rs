@@ -0,0 +1,83 @@
+use std::fmt::Display;
+
+use anyhow::bail;
+use evtx::SerializedEvtxRecord;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{EventId, ProviderName};
+
+const LOCAL_SESSION_MANAGER: &str = "Microsoft-Windows-TerminalServices-LocalSessionManager";
+
+/// the reason code of a disconnected Remote Desktop session, as found in
+/// event 40 of the `LocalSessionManager`
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct DisconnectReason(pub u32);
+
+impl TryFrom<&SerializedEvtxRecord<Value>> for DisconnectReason {
+    type Error = anyhow::Error;
+
+    fn try_from(record: &SerializedEvtxRecord<Value>) -> Result<Self, Self::Error> {
+        let provider = ProviderName::try_from(record)?;
+        let event_id = EventId::try_from(record)?;
+        if event_id.value() != 40 || !provider.value().eq_ignore_ascii_case(LOCAL_SESSION_MANAGER) {
+            bail!("this event does not contain a disconnect reason");
+        }
+
+        let reason = &record.data["Event"]["UserData"]["EventXML"]["Reason"];
+        let reason = match reason {
+            Value::Number(n) => n.as_u64(),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        };
+        match reason.and_then(|r| u32::try_from(r).ok()) {
+            Some(reason) => Ok(Self(reason)),
+            None => bail!("missing or invalid disconnect reason"),
+        }
+    }
+}
+
+impl DisconnectReason {
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+
+    /// returns the meaning of this reason code, if it is known
+    pub fn description(&self) -> Option<&'static str> {
+        let description = match self.0 {
+            0 => "no additional information is available",
+            1 => "an application initiated the disconnection",
+            2 => "an application logged off the client",
+            3 => "the client has been idle for longer than the time-out period",
+            4 => "the client has exceeded the maximum connection time",
+            5 => "the client's connection was replaced by another connection",
+            6 => "no memory is available",
+            7 => "the server denied the connection",
+            8 => "the server denied the connection for security reasons",
+            9 => "the server denied the connection because of licensing",
+            10 => "the client's credentials have expired",
+            11 => "the user disconnected the session or was disconnected by an administrator",
+            12 => "the user logged off the session",
+            _ => return None,
+        };
+        Some(description)
+    }
+}
+
+impl Display for DisconnectReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.description() {
+            Some(description) => write!(f, "{} ({description})", self.0),
+            None => self.0.fmt(f),
+        }
+    }
+}
+
+impl Serialize for DisconnectReason {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
@@ -5,6 +5,8 @@ use evtx::SerializedEvtxRecord;
 use serde::Serialize;
 use serde_json::Value;
 
+use crate::Keywords;
+
 pub enum EventLevel {
     LogAlways,
     Critical,
@@ -47,6 +49,41 @@ impl TryFrom<u64> for EventLevel {
     }
 }
 
+impl EventLevel {
+    /// the level of a record, where audit events are classified by their
+    /// keywords. Records without a known level are informational
+    pub fn of_record(record: &SerializedEvtxRecord<Value>) -> Self {
+        match Keywords::try_from(record) {
+            Ok(keywords) if keywords.is_audit_failure() => Self::AuditFailure,
+            Ok(keywords) if keywords.is_audit_success() => Self::AuditSuccess,
+            _ => Self::try_from(record).unwrap_or(Self::Information),
+        }
+    }
+
+    /// the severity as used by CEF and LEEF, ranging from 0 (lowest) to 10
+    pub fn cef_severity(&self) -> u8 {
+        match self {
+            EventLevel::Critical => 10,
+            EventLevel::Error => 7,
+            EventLevel::AuditFailure => 6,
+            EventLevel::Warning => 5,
+            EventLevel::LogAlways | EventLevel::Information | EventLevel::AuditSuccess => 3,
+        }
+    }
+
+    /// the severity as defined by RFC 5424, ranging from 0 (emergency) to
+    /// 7 (debug)
+    pub fn syslog_severity(&self) -> u8 {
+        match self {
+            EventLevel::Critical => 2,
+            EventLevel::Error => 3,
+            EventLevel::Warning | EventLevel::AuditFailure => 4,
+            EventLevel::AuditSuccess => 5,
+            EventLevel::LogAlways | EventLevel::Information => 6,
+        }
+    }
+}
+
 impl Display for EventLevel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
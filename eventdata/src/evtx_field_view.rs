@@ -1,6 +1,19 @@
-use std::fmt::Display;
+use std::{borrow::Cow, fmt::Display};
 
 pub trait EvtxFieldView: Display {
     fn maximum_display_length(&self) -> usize { 0 }
     fn value_with_padding(&self) -> String { "".to_owned() } 
+}
+
+/// cuts `value` at `width` characters, where the last character is replaced
+/// by an ellipsis, so that it fits into a column of fixed width
+pub fn truncate(value: &str, width: usize) -> Cow<'_, str> {
+    if value.chars().count() <= width {
+        return Cow::Borrowed(value);
+    }
+    let mut truncated: String = value.chars().take(width.saturating_sub(1)).collect();
+    if width > 0 {
+        truncated.push('…');
+    }
+    Cow::Owned(truncated)
 }
\ No newline at end of file
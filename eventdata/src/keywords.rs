@@ -0,0 +1,64 @@
+use std::fmt::Display;
+
+use anyhow::anyhow;
+use evtx::SerializedEvtxRecord;
+use serde_json::Value;
+
+use super::{truncate, EvtxFieldView};
+
+/// the keyword which marks successful audits
+const AUDIT_SUCCESS: u64 = 0x0020_0000_0000_0000;
+
+/// the keyword which marks failed audits
+const AUDIT_FAILURE: u64 = 0x0010_0000_0000_0000;
+
+/// the keywords bitmask of the event, as hexadecimal string (e.g. `0x8020000000000000`)
+#[derive(PartialEq, Eq, Clone)]
+pub struct Keywords<'a>(&'a str);
+
+impl<'a> TryFrom<&'a SerializedEvtxRecord<Value>> for Keywords<'a> {
+    type Error = anyhow::Error;
+
+    fn try_from(record: &'a SerializedEvtxRecord<Value>) -> Result<Self, Self::Error> {
+        record.data["Event"]["System"]["Keywords"]
+            .as_str()
+            .map(Self)
+            .ok_or_else(|| anyhow!("missing keywords"))
+    }
+}
+
+impl<'a> Display for Keywords<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+pub const KEYWORDS_MAX_LENGTH: usize = "0x8020000000000000".len();
+impl<'a> EvtxFieldView for Keywords<'a> {
+    fn maximum_display_length(&self) -> usize {
+        KEYWORDS_MAX_LENGTH
+    }
+
+    fn value_with_padding(&self) -> String {
+        format!("{:18}", truncate(self.0, KEYWORDS_MAX_LENGTH))
+    }
+}
+
+impl<'a> Keywords<'a> {
+    pub fn value(&self) -> &str {
+        self.0
+    }
+
+    /// the keywords as number, or `None` if they are no hexadecimal number
+    pub fn mask(&self) -> Option<u64> {
+        u64::from_str_radix(self.0.trim_start_matches("0x"), 16).ok()
+    }
+
+    pub fn is_audit_success(&self) -> bool {
+        self.mask().is_some_and(|mask| mask & AUDIT_SUCCESS != 0)
+    }
+
+    pub fn is_audit_failure(&self) -> bool {
+        self.mask().is_some_and(|mask| mask & AUDIT_FAILURE != 0)
+    }
+}
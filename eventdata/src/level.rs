@@ -0,0 +1,67 @@
+use std::fmt::Display;
+
+use anyhow::bail;
+use evtx::SerializedEvtxRecord;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::EvtxFieldView;
+
+/// the numeric level of the event. See [`crate::EventLevel`] for its meaning
+#[derive(PartialEq, Eq, Clone, Debug, Serialize)]
+pub struct Level(pub u8);
+
+impl TryFrom<&SerializedEvtxRecord<Value>> for Level {
+    type Error = anyhow::Error;
+
+    fn try_from(record: &SerializedEvtxRecord<Value>) -> Result<Self, Self::Error> {
+        let level = &record.data["Event"]["System"]["Level"];
+
+        let level = match level.get("#text") {
+            Some(v) => v,
+            None => level,
+        };
+
+        if let Some(level) = level.as_u64() {
+            let value: u8 = level.try_into()?;
+            Ok(Self(value))
+        } else {
+            bail!("level cannot be converted to u8: {level}")
+        }
+    }
+}
+
+impl Level {
+    pub fn value(&self) -> u8 {
+        self.0
+    }
+}
+
+pub const LEVEL_MAX_LENGTH: usize = 3;
+impl EvtxFieldView for Level {
+    fn maximum_display_length(&self) -> usize {
+        LEVEL_MAX_LENGTH
+    }
+
+    fn value_with_padding(&self) -> String {
+        format!("{:3}", self.0)
+    }
+}
+
+impl From<Level> for u8 {
+    fn from(me: Level) -> Self {
+        me.0
+    }
+}
+
+impl From<u8> for Level {
+    fn from(value: u8) -> Self {
+        Self(value)
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
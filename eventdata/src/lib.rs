@@ -26,4 +26,46 @@ mod event_level;
 pub use event_level::*;
 
 mod process_id;
-pub use process_id::*;
\ No newline at end of file
+pub use process_id::*;
+
+mod provider_name;
+pub use provider_name::*;
+
+mod channel;
+pub use channel::*;
+
+mod computer;
+pub use computer::*;
+
+mod level;
+pub use level::*;
+
+mod task;
+pub use task::*;
+
+mod opcode;
+pub use opcode::*;
+
+mod keywords;
+pub use keywords::*;
+
+mod thread_id;
+pub use thread_id::*;
+
+mod version;
+pub use version::*;
+
+mod user_id;
+pub use user_id::*;
+
+mod parameter_message;
+pub use parameter_message::*;
+
+mod logon_type;
+pub use logon_type::*;
+
+mod nt_status;
+pub use nt_status::*;
+
+mod disconnect_reason;
+pub use disconnect_reason::*;
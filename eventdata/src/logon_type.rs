@@ -0,0 +1,128 @@
+use std::fmt::Display;
+
+use anyhow::{anyhow, bail};
+use evtx::SerializedEvtxRecord;
+use serde::Serialize;
+use serde_json::Value;
+
+/// the kind of a logon, as found in the `LogonType` field of events like
+/// 4624 and 4625
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum LogonType {
+    System,
+    Interactive,
+    Network,
+    Batch,
+    Service,
+    Proxy,
+    Unlock,
+    NetworkCleartext,
+    NewCredentials,
+    RemoteInteractive,
+    CachedInteractive,
+    CachedRemoteInteractive,
+    CachedUnlock,
+}
+
+impl TryFrom<&SerializedEvtxRecord<Value>> for LogonType {
+    type Error = anyhow::Error;
+
+    fn try_from(record: &SerializedEvtxRecord<Value>) -> Result<Self, Self::Error> {
+        Self::try_from(&record.data["Event"]["EventData"]["LogonType"])
+    }
+}
+
+impl TryFrom<&Value> for LogonType {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Number(n) => match n.as_u64() {
+                Some(n) => Self::try_from(n),
+                None => bail!("invalid logon type: {value}"),
+            },
+            Value::String(s) => Self::try_from(
+                s.trim()
+                    .parse::<u64>()
+                    .map_err(|_| anyhow!("invalid logon type: {s}"))?,
+            ),
+            _ => bail!("missing logon type"),
+        }
+    }
+}
+
+impl TryFrom<u64> for LogonType {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::System,
+            2 => Self::Interactive,
+            3 => Self::Network,
+            4 => Self::Batch,
+            5 => Self::Service,
+            6 => Self::Proxy,
+            7 => Self::Unlock,
+            8 => Self::NetworkCleartext,
+            9 => Self::NewCredentials,
+            10 => Self::RemoteInteractive,
+            11 => Self::CachedInteractive,
+            12 => Self::CachedRemoteInteractive,
+            13 => Self::CachedUnlock,
+            _ => bail!("unknown logon type: {value}"),
+        })
+    }
+}
+
+impl LogonType {
+    pub fn value(&self) -> u8 {
+        match self {
+            Self::System => 0,
+            Self::Interactive => 2,
+            Self::Network => 3,
+            Self::Batch => 4,
+            Self::Service => 5,
+            Self::Proxy => 6,
+            Self::Unlock => 7,
+            Self::NetworkCleartext => 8,
+            Self::NewCredentials => 9,
+            Self::RemoteInteractive => 10,
+            Self::CachedInteractive => 11,
+            Self::CachedRemoteInteractive => 12,
+            Self::CachedUnlock => 13,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::System => "System",
+            Self::Interactive => "Interactive",
+            Self::Network => "Network",
+            Self::Batch => "Batch",
+            Self::Service => "Service",
+            Self::Proxy => "Proxy",
+            Self::Unlock => "Unlock",
+            Self::NetworkCleartext => "NetworkCleartext",
+            Self::NewCredentials => "NewCredentials",
+            Self::RemoteInteractive => "RemoteInteractive",
+            Self::CachedInteractive => "CachedInteractive",
+            Self::CachedRemoteInteractive => "CachedRemoteInteractive",
+            Self::CachedUnlock => "CachedUnlock",
+        }
+    }
+}
+
+impl Display for LogonType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.value(), self.name())
+    }
+}
+
+impl Serialize for LogonType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
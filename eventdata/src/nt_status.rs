@@ -0,0 +1,114 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, bail};
+use evtx::SerializedEvtxRecord;
+use serde::Serialize;
+use serde_json::Value;
+
+/// an `NTSTATUS` code, such as the `Status` and `SubStatus` fields of
+/// failed logons (4625) or the `Status` of NTLM authentications (4776).
+/// The result codes of Kerberos events (e.g. 4768 and 4771) are no
+/// `NTSTATUS` codes
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct NtStatus(pub u32);
+
+impl NtStatus {
+    pub const SUCCESS: Self = Self(0);
+
+    /// reads the status code from the field `name` of `EventData`
+    pub fn from_event_data(record: &SerializedEvtxRecord<Value>, name: &str) -> anyhow::Result<Self> {
+        Self::try_from(&record.data["Event"]["EventData"][name])
+    }
+
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+
+    pub fn is_success(&self) -> bool {
+        *self == Self::SUCCESS
+    }
+
+    /// returns the meaning of this status code, if it is known
+    pub fn description(&self) -> Option<&'static str> {
+        let description = match self.0 {
+            0x0000_0000 => "success",
+            0xC000_005E => "no logon servers available",
+            0xC000_0064 => "user name does not exist",
+            0xC000_006A => "user name is correct but the password is wrong",
+            0xC000_006C => "password policy not met",
+            0xC000_006D => "bad user name or authentication information",
+            0xC000_006E => "account restriction",
+            0xC000_006F => "user logon outside authorized hours",
+            0xC000_0070 => "workstation restriction",
+            0xC000_0071 => "password has expired",
+            0xC000_0072 => "account is disabled",
+            0xC000_00DC => "SAM server was in the wrong state",
+            0xC000_0133 => "clocks between DC and other computer too far out of sync",
+            0xC000_015B => "user has not been granted the requested logon type",
+            0xC000_018C => "trust relationship between domain and trusted domain failed",
+            0xC000_0192 => "NetLogon service was not started",
+            0xC000_0193 => "account has expired",
+            0xC000_0224 => "user is required to change password at next logon",
+            0xC000_0225 => "object not found (STATUS_NOT_FOUND)",
+            0xC000_0234 => "account is locked out",
+            0xC000_0371 => "local account store does not contain secret material for the specified account",
+            0xC000_0413 => "authentication firewall: machine is not allowed to authenticate",
+            _ => return None,
+        };
+        Some(description)
+    }
+}
+
+impl TryFrom<&Value> for NtStatus {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Number(n) => match n.as_u64().and_then(|n| u32::try_from(n).ok()) {
+                Some(n) => Ok(Self(n)),
+                None => bail!("invalid status code: {value}"),
+            },
+            Value::String(s) => s.parse(),
+            _ => bail!("missing status code"),
+        }
+    }
+}
+
+impl FromStr for NtStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let value = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex) => u32::from_str_radix(hex, 16),
+            None => s.parse(),
+        };
+        value
+            .map(Self)
+            .map_err(|_| anyhow!("invalid status code: {s}"))
+    }
+}
+
+impl From<u32> for NtStatus {
+    fn from(value: u32) -> Self {
+        Self(value)
+    }
+}
+
+impl Display for NtStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.description() {
+            Some(description) => write!(f, "0x{:08X} ({description})", self.0),
+            None => write!(f, "0x{:08X}", self.0),
+        }
+    }
+}
+
+impl Serialize for NtStatus {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
@@ -0,0 +1,67 @@
+use std::fmt::Display;
+
+use anyhow::bail;
+use evtx::SerializedEvtxRecord;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::EvtxFieldView;
+
+/// the opcode of the event, which is specific to the provider
+#[derive(PartialEq, Eq, Clone, Debug, Serialize)]
+pub struct Opcode(pub u8);
+
+impl TryFrom<&SerializedEvtxRecord<Value>> for Opcode {
+    type Error = anyhow::Error;
+
+    fn try_from(record: &SerializedEvtxRecord<Value>) -> Result<Self, Self::Error> {
+        let opcode = &record.data["Event"]["System"]["Opcode"];
+
+        let opcode = match opcode.get("#text") {
+            Some(v) => v,
+            None => opcode,
+        };
+
+        if let Some(opcode) = opcode.as_u64() {
+            let value: u8 = opcode.try_into()?;
+            Ok(Self(value))
+        } else {
+            bail!("opcode cannot be converted to u8: {opcode}")
+        }
+    }
+}
+
+impl Opcode {
+    pub fn value(&self) -> u8 {
+        self.0
+    }
+}
+
+pub const OPCODE_MAX_LENGTH: usize = 3;
+impl EvtxFieldView for Opcode {
+    fn maximum_display_length(&self) -> usize {
+        OPCODE_MAX_LENGTH
+    }
+
+    fn value_with_padding(&self) -> String {
+        format!("{:3}", self.0)
+    }
+}
+
+impl From<Opcode> for u8 {
+    fn from(me: Opcode) -> Self {
+        me.0
+    }
+}
+
+impl From<u8> for Opcode {
+    fn from(value: u8) -> Self {
+        Self(value)
+    }
+}
+
+impl Display for Opcode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
@@ -0,0 +1,120 @@
+use std::borrow::Cow;
+
+use serde_json::Value;
+
+/// returns the English text of a parameter message, which is referenced in
+/// event data as `%%<id>` (e.g. `%%1936`). These messages are defined in
+/// `msobjs.dll` and are mostly used by the Security log.
+pub fn parameter_message(id: u32) -> Option<&'static str> {
+    let message = match id {
+        // access rights
+        1537 => "DELETE",
+        1538 => "READ_CONTROL",
+        1539 => "WRITE_DAC",
+        1540 => "WRITE_OWNER",
+        1541 => "SYNCHRONIZE",
+        1542 => "ACCESS_SYS_SEC",
+        4416 => "ReadData (or ListDirectory)",
+        4417 => "WriteData (or AddFile)",
+        4418 => "AppendData (or AddSubdirectory or CreatePipeInstance)",
+        4419 => "ReadEA",
+        4420 => "WriteEA",
+        4421 => "Execute/Traverse",
+        4422 => "DeleteChild",
+        4423 => "ReadAttributes",
+        4424 => "WriteAttributes",
+        4432 => "Query key value",
+        4433 => "Set key value",
+        4434 => "Create sub-key",
+        4435 => "Enumerate sub-keys",
+        4436 => "Notify about changes to keys",
+        4437 => "Create Link",
+
+        // impersonation levels
+        1832 => "Identification",
+        1833 => "Impersonation",
+        1840 => "Delegation",
+
+        // boolean values
+        1842 => "Yes",
+        1843 => "No",
+
+        // registry operations
+        1904 => "New registry value created",
+        1905 => "Existing registry value modified",
+        1906 => "Registry value deleted",
+
+        // token elevation types
+        1936 => "Type 1 is a full token with no privileges removed or groups disabled.",
+        1937 => "Type 2 is an elevated token with no privileges removed or groups disabled.",
+        1938 => "Type 3 is a limited token with administrative privileges removed and administrative groups disabled.",
+
+        // logon failure reasons
+        2304 => "An Error occured during Logon.",
+        2305 => "The specified user account has expired.",
+        2306 => "The NetLogon component is not active.",
+        2307 => "Account locked out.",
+        2308 => "The user has not been granted the requested logon type at this machine.",
+        2309 => "The specified account's password has expired.",
+        2310 => "Account currently disabled.",
+        2311 => "Account logon time restriction violation.",
+        2312 => "User not allowed to logon at this computer.",
+        2313 => "Unknown user name or bad password.",
+
+        // audit policy changes
+        8448 => "Success removed",
+        8449 => "Success added",
+        8450 => "Failure removed",
+        8451 => "Failure added",
+
+        // Windows Filtering Platform
+        14592 => "Inbound",
+        14593 => "Outbound",
+        14608 => "Resource Assignment",
+        14609 => "Listen",
+        14610 => "Receive/Accept",
+        14611 => "Connect",
+        _ => return None,
+    };
+    Some(message)
+}
+
+/// replaces all known parameter message references (`%%<id>`) in `s` by their
+/// text. Unknown references are left unchanged.
+pub fn resolve_parameter_messages(s: &str) -> Cow<'_, str> {
+    if !s.contains("%%") {
+        return Cow::Borrowed(s);
+    }
+
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(pos) = rest.find("%%") {
+        result.push_str(&rest[..pos]);
+        let reference = &rest[pos + 2..];
+        let digits = reference
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(reference.len());
+
+        match reference[..digits].parse().ok().and_then(parameter_message) {
+            Some(message) => result.push_str(message),
+            None => result.push_str(&rest[pos..pos + 2 + digits]),
+        }
+        rest = &reference[digits..];
+    }
+    result.push_str(rest);
+    Cow::Owned(result)
+}
+
+/// replaces parameter message references in all strings of `value`
+pub fn resolve_parameter_messages_in(value: &mut Value) {
+    match value {
+        Value::String(s) => {
+            if let Cow::Owned(resolved) = resolve_parameter_messages(s) {
+                *s = resolved;
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(resolve_parameter_messages_in),
+        Value::Object(o) => o.values_mut().for_each(resolve_parameter_messages_in),
+        _ => (),
+    }
+}
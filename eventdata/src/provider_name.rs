@@ -0,0 +1,47 @@
+use std::fmt::Display;
+
+use anyhow::anyhow;
+use evtx::SerializedEvtxRecord;
+use serde_json::Value;
+
+use super::{truncate, EvtxFieldView};
+
+/// the name of the provider which logged the event, as found in
+/// `Event/System/Provider/@Name`. See [`crate::EventProvider`] for the
+/// providers which are known to this crate.
+#[derive(PartialEq, Eq, Clone)]
+pub struct ProviderName<'a>(&'a str);
+
+impl<'a> TryFrom<&'a SerializedEvtxRecord<Value>> for ProviderName<'a> {
+    type Error = anyhow::Error;
+
+    fn try_from(record: &'a SerializedEvtxRecord<Value>) -> Result<Self, Self::Error> {
+        record.data["Event"]["System"]["Provider"]["#attributes"]["Name"]
+            .as_str()
+            .map(Self)
+            .ok_or_else(|| anyhow!("missing provider name"))
+    }
+}
+
+impl<'a> Display for ProviderName<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+pub const PROVIDER_NAME_MAX_LENGTH: usize = 40;
+impl<'a> EvtxFieldView for ProviderName<'a> {
+    fn maximum_display_length(&self) -> usize {
+        PROVIDER_NAME_MAX_LENGTH
+    }
+
+    fn value_with_padding(&self) -> String {
+        format!("{:40}", truncate(self.0, PROVIDER_NAME_MAX_LENGTH))
+    }
+}
+
+impl<'a> ProviderName<'a> {
+    pub fn value(&self) -> &str {
+        self.0
+    }
+}
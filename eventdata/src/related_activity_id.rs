@@ -23,10 +23,7 @@ impl<'a> TryFrom<&'a SerializedEvtxRecord<Value>> for RelatedActivityId<'a> {
 
 impl<'a> Display for RelatedActivityId<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.0.as_ref() {
-            Some(v) => v.fmt(f),
-            None => "None".fmt(f),
-        }
+        self.0.and_then(Value::as_str).unwrap_or_default().fmt(f)
     }
 }
 
@@ -0,0 +1,67 @@
+use std::fmt::Display;
+
+use anyhow::bail;
+use evtx::SerializedEvtxRecord;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::EvtxFieldView;
+
+/// the task (or category) of the event, which is specific to the provider
+#[derive(PartialEq, Eq, Clone, Debug, Serialize)]
+pub struct Task(pub u16);
+
+impl TryFrom<&SerializedEvtxRecord<Value>> for Task {
+    type Error = anyhow::Error;
+
+    fn try_from(record: &SerializedEvtxRecord<Value>) -> Result<Self, Self::Error> {
+        let task = &record.data["Event"]["System"]["Task"];
+
+        let task = match task.get("#text") {
+            Some(v) => v,
+            None => task,
+        };
+
+        if let Some(task) = task.as_u64() {
+            let value: u16 = task.try_into()?;
+            Ok(Self(value))
+        } else {
+            bail!("task cannot be converted to u16: {task}")
+        }
+    }
+}
+
+impl Task {
+    pub fn value(&self) -> u16 {
+        self.0
+    }
+}
+
+pub const TASK_MAX_LENGTH: usize = 5;
+impl EvtxFieldView for Task {
+    fn maximum_display_length(&self) -> usize {
+        TASK_MAX_LENGTH
+    }
+
+    fn value_with_padding(&self) -> String {
+        format!("{:5}", self.0)
+    }
+}
+
+impl From<Task> for u16 {
+    fn from(me: Task) -> Self {
+        me.0
+    }
+}
+
+impl From<u16> for Task {
+    fn from(value: u16) -> Self {
+        Self(value)
+    }
+}
+
+impl Display for Task {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
@@ -0,0 +1,66 @@
+use std::fmt::Display;
+
+use anyhow::bail;
+use evtx::SerializedEvtxRecord;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::EvtxFieldView;
+
+/// the ID of the thread that created the event
+#[derive(PartialEq, Eq, Clone, Debug, Serialize)]
+pub struct ThreadId(pub u64);
+
+impl TryFrom<&SerializedEvtxRecord<Value>> for ThreadId {
+    type Error = anyhow::Error;
+
+    fn try_from(record: &SerializedEvtxRecord<Value>) -> Result<Self, Self::Error> {
+        let thread_id = &record.data["Event"]["System"]["Execution"]["#attributes"]["ThreadID"];
+
+        let thread_id = match thread_id.get("#text") {
+            Some(v) => v,
+            None => thread_id,
+        };
+
+        if let Some(thread_id) = thread_id.as_u64() {
+            Ok(Self(thread_id))
+        } else {
+            bail!("thread id cannot be converted to u64: {thread_id}")
+        }
+    }
+}
+
+impl ThreadId {
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+pub const THREAD_ID_MAX_LENGTH: usize = 10;
+impl EvtxFieldView for ThreadId {
+    fn maximum_display_length(&self) -> usize {
+        THREAD_ID_MAX_LENGTH
+    }
+
+    fn value_with_padding(&self) -> String {
+        format!("{:10}", self.0)
+    }
+}
+
+impl From<ThreadId> for u64 {
+    fn from(me: ThreadId) -> Self {
+        me.0
+    }
+}
+
+impl From<u64> for ThreadId {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl Display for ThreadId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
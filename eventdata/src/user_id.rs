@@ -0,0 +1,46 @@
+use std::fmt::Display;
+
+use anyhow::anyhow;
+use evtx::SerializedEvtxRecord;
+use serde_json::Value;
+
+use super::{truncate, EvtxFieldView};
+
+/// the security identifier of the user in whose context the event was
+/// logged, as found in `Event/System/Security/@UserID`
+#[derive(PartialEq, Eq, Clone)]
+pub struct UserId<'a>(&'a str);
+
+impl<'a> TryFrom<&'a SerializedEvtxRecord<Value>> for UserId<'a> {
+    type Error = anyhow::Error;
+
+    fn try_from(record: &'a SerializedEvtxRecord<Value>) -> Result<Self, Self::Error> {
+        record.data["Event"]["System"]["Security"]["#attributes"]["UserID"]
+            .as_str()
+            .map(Self)
+            .ok_or_else(|| anyhow!("missing user id"))
+    }
+}
+
+impl<'a> Display for UserId<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+pub const USER_ID_MAX_LENGTH: usize = "S-1-5-21-4294967295-4294967295-4294967295-4294967295".len();
+impl<'a> EvtxFieldView for UserId<'a> {
+    fn maximum_display_length(&self) -> usize {
+        USER_ID_MAX_LENGTH
+    }
+
+    fn value_with_padding(&self) -> String {
+        format!("{:width$}", truncate(self.0, USER_ID_MAX_LENGTH), width = USER_ID_MAX_LENGTH)
+    }
+}
+
+impl<'a> UserId<'a> {
+    pub fn value(&self) -> &str {
+        self.0
+    }
+}
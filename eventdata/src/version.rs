@@ -0,0 +1,67 @@
+use std::fmt::Display;
+
+use anyhow::bail;
+use evtx::SerializedEvtxRecord;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::EvtxFieldView;
+
+/// the version of the event definition
+#[derive(PartialEq, Eq, Clone, Debug, Serialize)]
+pub struct Version(pub u8);
+
+impl TryFrom<&SerializedEvtxRecord<Value>> for Version {
+    type Error = anyhow::Error;
+
+    fn try_from(record: &SerializedEvtxRecord<Value>) -> Result<Self, Self::Error> {
+        let version = &record.data["Event"]["System"]["Version"];
+
+        let version = match version.get("#text") {
+            Some(v) => v,
+            None => version,
+        };
+
+        if let Some(version) = version.as_u64() {
+            let value: u8 = version.try_into()?;
+            Ok(Self(value))
+        } else {
+            bail!("version cannot be converted to u8: {version}")
+        }
+    }
+}
+
+impl Version {
+    pub fn value(&self) -> u8 {
+        self.0
+    }
+}
+
+pub const VERSION_MAX_LENGTH: usize = 3;
+impl EvtxFieldView for Version {
+    fn maximum_display_length(&self) -> usize {
+        VERSION_MAX_LENGTH
+    }
+
+    fn value_with_padding(&self) -> String {
+        format!("{:3}", self.0)
+    }
+}
+
+impl From<Version> for u8 {
+    fn from(me: Version) -> Self {
+        me.0
+    }
+}
+
+impl From<u8> for Version {
+    fn from(value: u8) -> Self {
+        Self(value)
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
@@ -1,8 +1,25 @@
-use std::{io::stdout, path::PathBuf};
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap},
+    io::stdout,
+    path::PathBuf,
+};
 
+use chrono::SecondsFormat;
 use clap::{Parser, Subcommand, ValueEnum};
+use anyhow::bail;
+use eventdata::resolve_parameter_messages_in;
+use libevtx::{
+    InputArgs, PartitionTemplate, PseudonymArgs, Pseudonymizer, RecordScript, TimeArgs,
+    TimesketchEvent, TimestampFormat,
+};
+use serde_json::json;
 
-use super::sessions::SessionStore;
+use super::{
+    ioc::{find_ioc_matches, IndicatorKind, IndicatorList},
+    record_output::{CsvOutput, RecordColumns, TimesketchOutput},
+    sessions::{SessionAsCsv, SessionStore},
+    sigma::{find_sigma_matches, SigmaRule},
+};
 
 #[derive(ValueEnum, Clone)]
 pub enum Format {
@@ -14,6 +31,9 @@ pub enum Format {
     LaTeX,
 
     Dot,
+
+    /// JSON lines which can be imported into Timesketch
+    Timesketch,
 }
 
 #[derive(Subcommand)]
@@ -25,34 +45,134 @@ pub enum Command {
         #[clap(short('U'), long("username"))]
         username: Option<String>,
 
-        /// Name of the evtx file to parse
+        /// display only processes of this account, which can be specified as
+        /// SID, 'DOMAIN\name', 'name@domain' or as plain name
+        #[clap(short('I'), long("identity"))]
+        identity: Option<String>,
+
+        /// Name of the evtx file to parse. If this is a directory or a ZIP
+        /// archive, all evtx files contained in it are parsed
         evtx_file: PathBuf,
 
         /// output format
         #[clap(short('F'), long("format"), value_enum, default_value_t=Format::Csv)]
         format: Format,
+
+        /// write the processes into separate files instead of displaying
+        /// them. The names of the files are given by this template, e.g.
+        /// '{computer}/{date}.jsonl'. Only 'timesketch' output can be
+        /// partitioned
+        #[clap(long("partition"))]
+        partition: Option<PartitionTemplate>,
     },
 
     /// display sessions
     #[clap(name = "sessions")]
     Sessions {
-        /// Names of the evtx files to parse
+        /// directory or ZIP archive which contains the evtx files to parse.
+        /// It is searched recursively
         evtx_files_dir: PathBuf,
 
         /// include anonymous sessions
         #[clap(long("include-anonymous"))]
         include_anonymous: bool,
+
+        /// display only sessions of this account, which can be specified as
+        /// SID, 'DOMAIN\name', 'name@domain' or as plain name
+        #[clap(short('I'), long("identity"))]
+        identity: Option<String>,
+
+        /// output format. Only 'csv' and 'timesketch' are supported
+        #[clap(short('F'), long("format"), value_enum, default_value_t=Format::Csv)]
+        format: Format,
+
+        /// write the sessions into separate files instead of displaying them.
+        /// The names of the files are given by this template, e.g.
+        /// '{computer}/{date}.csv'. Placeholders are replaced by the values of
+        /// the first record of a session; Timesketch events are written into
+        /// the files of their records
+        #[clap(long("partition"))]
+        partition: Option<PartitionTemplate>,
     },
 
     /// display one single session
     #[clap(name = "session")]
     Session {
-        /// Names of the evtx files to parse
+        /// directory or ZIP archive which contains the evtx files to parse.
+        /// It is searched recursively
         evtx_files_dir: PathBuf,
 
         /// Session ID
         session_id: String,
     },
+
+    /// match records against Sigma rules
+    #[clap(name = "sigma")]
+    Sigma {
+        /// directory which contains the Sigma rules. It is searched
+        /// recursively for `*.yml` and `*.yaml` files
+        #[clap(short('r'), long("rules"))]
+        rules: PathBuf,
+
+        /// evtx files to scan. If this is a directory or a ZIP archive, all
+        /// evtx files contained in it are scanned
+        #[clap(required = true)]
+        evtx_files: Vec<PathBuf>,
+
+        /// output format. Only 'csv', 'json', 'markdown' and 'timesketch' are
+        /// supported
+        #[clap(short('F'), long("format"), value_enum, default_value_t=Format::Csv)]
+        format: Format,
+
+        /// write the matches into separate files instead of displaying them.
+        /// The names of the files are given by this template, e.g.
+        /// '{computer}/{channel}/{date}.csv'. Only 'csv' and 'timesketch'
+        /// output can be partitioned
+        #[clap(long("partition"))]
+        partition: Option<PartitionTemplate>,
+    },
+
+    /// search indicators of compromise in all records
+    #[clap(name = "ioc")]
+    Ioc {
+        /// file which contains the indicators: a CSV file (`*.csv`) with the
+        /// columns `value`, `type` and `description`, a STIX 2 bundle
+        /// (`*.json`), or a text file with one indicator per line
+        #[clap(short('i'), long("iocs"))]
+        indicators: PathBuf,
+
+        /// evtx files to scan. If this is a directory or a ZIP archive, all
+        /// evtx files contained in it are scanned
+        #[clap(required = true)]
+        evtx_files: Vec<PathBuf>,
+
+        /// output format. Only 'csv', 'json', 'markdown' and 'timesketch' are
+        /// supported
+        #[clap(short('F'), long("format"), value_enum, default_value_t=Format::Csv)]
+        format: Format,
+
+        /// write the matches into separate files instead of displaying them.
+        /// The names of the files are given by this template, e.g.
+        /// '{computer}/{channel}/{date}.csv'. Only 'csv' and 'timesketch'
+        /// output can be partitioned
+        #[clap(long("partition"))]
+        partition: Option<PartitionTemplate>,
+    },
+
+    /// run a Rhai script for all records. The script can define the
+    /// functions `filter(record)`, `map(record)` and `finish()`, whose
+    /// results are displayed
+    #[clap(name = "script")]
+    Script {
+        /// the Rhai script
+        #[clap(short('s'), long("script"))]
+        script: PathBuf,
+
+        /// evtx files to process. If this is a directory or a ZIP archive, all
+        /// evtx files contained in it are processed
+        #[clap(required = true)]
+        evtx_files: Vec<PathBuf>,
+    },
 }
 
 #[derive(Parser)]
@@ -62,6 +182,19 @@ pub(crate) struct Cli {
 
     #[command(flatten)]
     pub(crate) verbose: clap_verbosity_flag::Verbosity,
+
+    #[command(flatten)]
+    pub(crate) input: InputArgs,
+
+    #[command(flatten)]
+    pub(crate) time: TimeArgs,
+
+    #[command(flatten)]
+    pub(crate) pseudonym: PseudonymArgs,
+
+    /// replace parameter message references (such as `%%1936`) by their text
+    #[clap(short('P'), long("resolve-parameters"), global = true)]
+    pub(crate) resolve_parameters: bool,
 }
 
 impl Cli {
@@ -71,18 +204,30 @@ impl Cli {
                 evtx_files_dir,
                 session_id,
             } => {
-                let sessions = SessionStore::import(evtx_files_dir, true)?;
+                let sources = self.input.sources([evtx_files_dir])?;
+                let mut pseudonymizer = self.pseudonym.pseudonymizer()?;
+                let sessions = SessionStore::import(
+                    &sources,
+                    true,
+                    self.resolve_parameters,
+                    pseudonymizer.as_mut(),
+                )?;
                 match sessions.find_session(session_id) {
                     None => log::error!("no value found for session id {session_id}"),
                     Some(session) => {
+                        let formatter = self
+                            .time
+                            .formatter(TimestampFormat::Rfc3339(SecondsFormat::AutoSi));
                         let mut csv_writer = csv::Writer::from_writer(stdout());
                         for event in session.iter_events() {
-                            event.to_csv(&mut csv_writer)?;
+                            event.to_csv(&mut csv_writer, &formatter)?;
                         }
                         csv_writer.flush()?;
                     }
                 }
-                Ok(())
+                pseudonymizer
+                    .as_ref()
+                    .map_or(Ok(()), Pseudonymizer::save_mapping)
             }
 
             _ => unreachable!(),
@@ -93,14 +238,431 @@ impl Cli {
             Command::Sessions {
                 evtx_files_dir,
                 include_anonymous,
+                identity,
+                format,
+                partition,
+            } => {
+                let sources = self.input.sources([evtx_files_dir])?;
+                let mut pseudonymizer = self.pseudonym.pseudonymizer()?;
+                let mut sessions = SessionStore::import(
+                    &sources,
+                    *include_anonymous,
+                    self.resolve_parameters,
+                    pseudonymizer.as_mut(),
+                )?;
+                if let Some(identity) = identity {
+                    // records have been pseudonymized, so the identity must be too
+                    match pseudonymizer.as_mut() {
+                        Some(p) => sessions.retain_identity(&p.pseudonymize_account(identity)?),
+                        None => sessions.retain_identity(identity),
+                    }
+                }
+                match format {
+                    Format::Csv => {
+                        let formatter = self
+                            .time
+                            .formatter(TimestampFormat::Custom("%Y-%m-%dT%H:%M:%S".to_owned()));
+
+                        let mut output = CsvOutput::new(
+                            &SessionAsCsv::HEADER,
+                            partition.as_ref(),
+                            &self.time.timezone,
+                        )?;
+                        for session in sessions {
+                            let first = session.first_event();
+                            output.serialize(
+                                first.source(),
+                                first.record(),
+                                session.to_session_as_csv(&formatter),
+                            )?;
+                        }
+                        output.flush()?;
+                    }
+                    Format::Timesketch => {
+                        let formatter = self
+                            .time
+                            .formatter(TimestampFormat::Rfc3339(SecondsFormat::AutoSi));
+                        let mut output =
+                            TimesketchOutput::new(partition.as_ref(), &self.time.timezone);
+                        for session in sessions {
+                            for (session_event, event) in session.to_timesketch(&formatter) {
+                                output.write(
+                                    session_event.source(),
+                                    session_event.record(),
+                                    event,
+                                )?;
+                            }
+                        }
+                        output.flush()?;
+                    }
+                    _ => bail!("sessions can only be displayed as csv or timesketch"),
+                }
+                pseudonymizer
+                    .as_ref()
+                    .map_or(Ok(()), Pseudonymizer::save_mapping)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn display_sigma_matches(&self) -> anyhow::Result<()> {
+        match &self.command {
+            Command::Sigma {
+                rules,
+                evtx_files,
+                format,
+                partition,
+            } => {
+                if partition.is_some() && !matches!(format, Format::Csv | Format::Timesketch) {
+                    bail!("only csv and timesketch output can be partitioned");
+                }
+                let rules = SigmaRule::load_all(rules)?;
+                if rules.is_empty() {
+                    bail!("no usable Sigma rules found");
+                }
+                log::info!("loaded {} Sigma rules", rules.len());
+
+                let sources = self.input.sources(evtx_files)?;
+                let mut matches = find_sigma_matches(&rules, &sources, self.resolve_parameters)?;
+
+                // rules must be evaluated using the original values
+                let mut pseudonymizer = self.pseudonym.pseudonymizer()?;
+                if let Some(pseudonymizer) = pseudonymizer.as_mut() {
+                    for m in matches.iter_mut() {
+                        pseudonymizer.pseudonymize_record(&mut m.record)?;
+                    }
+                    pseudonymizer.save_mapping()?;
+                }
+                let formatter = self
+                    .time
+                    .formatter(TimestampFormat::Rfc3339(SecondsFormat::AutoSi));
+
+                match format {
+                    Format::Csv => {
+                        let mut output = CsvOutput::new(
+                            &[
+                                &["timestamp", "level", "title", "rule_id", "tags"][..],
+                                &RecordColumns::HEADER,
+                            ]
+                            .concat(),
+                            partition.as_ref(),
+                            &self.time.timezone,
+                        )?;
+                        for m in matches.iter() {
+                            let record = &m.record;
+                            let columns = RecordColumns::new(&sources[m.source], record).to_csv();
+                            for rule in m.rules.iter().map(|r| &rules[*r]) {
+                                output.write(
+                                    &sources[m.source],
+                                    record,
+                                    [
+                                        formatter.format(&record.timestamp),
+                                        rule.level().unwrap_or_default().to_owned(),
+                                        rule.title().to_owned(),
+                                        rule.id().unwrap_or_default().to_owned(),
+                                        rule.tags().join(","),
+                                    ]
+                                    .into_iter()
+                                    .chain(columns.iter().cloned()),
+                                )?;
+                            }
+                        }
+                        output.flush()?;
+                    }
+                    Format::Json => {
+                        let mut matches_by_rule: BTreeMap<usize, Vec<_>> = BTreeMap::new();
+                        for m in matches.iter() {
+                            for rule in m.rules.iter() {
+                                let mut entry = json!({
+                                    "timestamp": formatter.format(&m.record.timestamp),
+                                });
+                                RecordColumns::new(&sources[m.source], &m.record)
+                                    .add_to(&mut entry);
+                                matches_by_rule.entry(*rule).or_default().push(entry);
+                            }
+                        }
+                        let result: Vec<_> = matches_by_rule
+                            .into_iter()
+                            .map(|(rule, matches)| {
+                                let rule = &rules[rule];
+                                json!({
+                                    "title": rule.title(),
+                                    "id": rule.id(),
+                                    "level": rule.level(),
+                                    "tags": rule.tags(),
+                                    "path": rule.path(),
+                                    "matches": matches,
+                                })
+                            })
+                            .collect();
+                        serde_json::to_writer_pretty(stdout(), &result)?;
+                        println!();
+                    }
+                    Format::Markdown => {
+                        let mut matches_by_rule: BTreeMap<usize, Vec<u64>> = BTreeMap::new();
+                        for m in matches.iter() {
+                            for rule in m.rules.iter() {
+                                matches_by_rule
+                                    .entry(*rule)
+                                    .or_default()
+                                    .push(m.record.event_record_id);
+                            }
+                        }
+                        println!("| Level | Title | Tags | Matches | Record IDs |");
+                        println!("|-------|-------|------|---------|------------|");
+                        for (rule, record_ids) in matches_by_rule {
+                            let rule = &rules[rule];
+                            println!(
+                                "| {} | {} | {} | {} | {} |",
+                                rule.level().unwrap_or_default(),
+                                rule.title().replace('|', "\\|"),
+                                rule.tags().join(", "),
+                                record_ids.len(),
+                                record_ids
+                                    .iter()
+                                    .map(|id| id.to_string())
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                            );
+                        }
+                    }
+                    Format::Timesketch => {
+                        let mut output =
+                            TimesketchOutput::new(partition.as_ref(), &self.time.timezone);
+                        for m in matches.iter() {
+                            for rule in m.rules.iter().map(|r| &rules[*r]) {
+                                let event = TimesketchEvent::from_record(
+                                    &m.record,
+                                    Some(&sources[m.source]),
+                                    Some(format!("Sigma: {}", rule.title())),
+                                )
+                                .with_field("sigma_title", rule.title())
+                                .with_field("sigma_id", rule.id())
+                                .with_field("sigma_level", rule.level())
+                                .with_field("tag", rule.tags());
+                                output.write(&sources[m.source], &m.record, event)?;
+                            }
+                        }
+                        output.flush()?;
+                    }
+                    _ => bail!(
+                        "Sigma matches can only be displayed as csv, json, markdown or timesketch"
+                    ),
+                }
+                Ok(())
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn run_script(&self) -> anyhow::Result<()> {
+        match &self.command {
+            Command::Script { script, evtx_files } => {
+                let mut script = RecordScript::load(script)?;
+                let mut pseudonymizer = self.pseudonym.pseudonymizer()?;
+                for source in self.input.sources(evtx_files)? {
+                    log::info!("processing {source}");
+                    for record in source.open()?.records_json_value() {
+                        let mut record = match record {
+                            Ok(record) => record,
+                            Err(why) => {
+                                log::warn!("error while reading {source}: {why}");
+                                continue;
+                            }
+                        };
+                        if self.resolve_parameters {
+                            resolve_parameter_messages_in(&mut record.data);
+                        }
+                        if let Some(pseudonymizer) = pseudonymizer.as_mut() {
+                            pseudonymizer.pseudonymize_record(&mut record)?;
+                        }
+                        if !script.filter(&source, &record)? {
+                            continue;
+                        }
+                        if let Some(output) = script.map(&source, &record)? {
+                            println!("{output}");
+                        }
+                    }
+                }
+                if let Some(output) = script.finish()? {
+                    println!("{output}");
+                }
+                pseudonymizer
+                    .as_ref()
+                    .map_or(Ok(()), Pseudonymizer::save_mapping)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn display_ioc_matches(&self) -> anyhow::Result<()> {
+        match &self.command {
+            Command::Ioc {
+                indicators,
+                evtx_files,
+                format,
+                partition,
             } => {
-                let sessions = SessionStore::import(evtx_files_dir, *include_anonymous)?;
+                if partition.is_some() && !matches!(format, Format::Csv | Format::Timesketch) {
+                    bail!("only csv and timesketch output can be partitioned");
+                }
+                let indicators = IndicatorList::load(indicators)?;
+                log::info!("loaded {} indicators", indicators.len());
+
+                let sources = self.input.sources(evtx_files)?;
+                let mut matches = find_ioc_matches(&indicators, &sources, self.resolve_parameters)?;
 
-                let mut csv_writer = csv::Writer::from_writer(stdout());
-                for session in sessions {
-                    session.into_csv(&mut csv_writer)?;
+                // indicators must be searched in the original values, and are
+                // displayed like the values they have been found in
+                let mut pseudonymizer = self.pseudonym.pseudonymizer()?;
+                let mut ioc_values = HashMap::new();
+                if let Some(pseudonymizer) = pseudonymizer.as_mut() {
+                    for m in matches.iter_mut() {
+                        pseudonymizer.pseudonymize_record(&mut m.record)?;
+                        for hit in m.hits.iter_mut() {
+                            hit.value = pseudonymizer.pseudonymize_field(&hit.field, &hit.value)?;
+                            if let Entry::Vacant(entry) = ioc_values.entry(hit.indicator) {
+                                let indicator = indicators.get(hit.indicator);
+                                entry.insert(match indicator.kind() {
+                                    IndicatorKind::UserName => {
+                                        pseudonymizer.pseudonymize_account(indicator.value())?
+                                    }
+                                    _ => pseudonymizer.pseudonymize_field("", indicator.value())?,
+                                });
+                            }
+                        }
+                    }
+                    pseudonymizer.save_mapping()?;
+                }
+                let ioc_value = |index: usize| match ioc_values.get(&index) {
+                    Some(value) => value.as_str(),
+                    None => indicators.get(index).value(),
+                };
+                let formatter = self
+                    .time
+                    .formatter(TimestampFormat::Rfc3339(SecondsFormat::AutoSi));
+
+                match format {
+                    Format::Csv => {
+                        let mut output = CsvOutput::new(
+                            &[
+                                &[
+                                    "timestamp",
+                                    "ioc",
+                                    "ioc_type",
+                                    "description",
+                                    "field",
+                                    "value",
+                                ][..],
+                                &RecordColumns::HEADER,
+                            ]
+                            .concat(),
+                            partition.as_ref(),
+                            &self.time.timezone,
+                        )?;
+                        for m in matches.iter() {
+                            let record = &m.record;
+                            let columns = RecordColumns::new(&sources[m.source], record).to_csv();
+                            for hit in m.hits.iter() {
+                                let indicator = indicators.get(hit.indicator);
+                                output.write(
+                                    &sources[m.source],
+                                    record,
+                                    [
+                                        formatter.format(&record.timestamp),
+                                        ioc_value(hit.indicator).to_owned(),
+                                        indicator.kind().to_string(),
+                                        indicator.description().unwrap_or_default().to_owned(),
+                                        hit.field.clone(),
+                                        hit.value.clone(),
+                                    ]
+                                    .into_iter()
+                                    .chain(columns.iter().cloned()),
+                                )?;
+                            }
+                        }
+                        output.flush()?;
+                    }
+                    Format::Json => {
+                        let hits: Vec<_> = matches
+                            .iter()
+                            .flat_map(|m| {
+                                m.hits.iter().map(|hit| {
+                                    let indicator = indicators.get(hit.indicator);
+                                    let mut hit = json!({
+                                        "timestamp": formatter.format(&m.record.timestamp),
+                                        "ioc": ioc_value(hit.indicator),
+                                        "ioc_type": indicator.kind().to_string(),
+                                        "description": indicator.description(),
+                                        "field": hit.field,
+                                        "value": hit.value,
+                                    });
+                                    RecordColumns::new(&sources[m.source], &m.record)
+                                        .add_to(&mut hit);
+                                    hit
+                                })
+                            })
+                            .collect();
+                        serde_json::to_writer_pretty(stdout(), &hits)?;
+                        println!();
+                    }
+                    Format::Markdown => {
+                        let mut hits_by_indicator = BTreeMap::new();
+                        for m in matches.iter() {
+                            for hit in m.hits.iter() {
+                                let (count, first, last) = hits_by_indicator
+                                    .entry(hit.indicator)
+                                    .or_insert((0, m.record.timestamp, m.record.timestamp));
+                                *count += 1;
+                                *first = (*first).min(m.record.timestamp);
+                                *last = (*last).max(m.record.timestamp);
+                            }
+                        }
+                        println!("| IOC | Type | Description | Hits | First seen | Last seen |");
+                        println!("|-----|------|-------------|------|------------|-----------|");
+                        for (index, (count, first, last)) in hits_by_indicator {
+                            let indicator = indicators.get(index);
+                            println!(
+                                "| `{}` | {} | {} | {count} | {} | {} |",
+                                ioc_value(index).replace('`', "'"),
+                                indicator.kind(),
+                                indicator
+                                    .description()
+                                    .unwrap_or_default()
+                                    .replace('|', "\\|"),
+                                formatter.format(&first),
+                                formatter.format(&last),
+                            );
+                        }
+                    }
+                    Format::Timesketch => {
+                        let mut output =
+                            TimesketchOutput::new(partition.as_ref(), &self.time.timezone);
+                        for m in matches.iter() {
+                            for hit in m.hits.iter() {
+                                let indicator = indicators.get(hit.indicator);
+                                let event = TimesketchEvent::from_record(
+                                    &m.record,
+                                    Some(&sources[m.source]),
+                                    Some(format!(
+                                        "IOC: {} found in {}",
+                                        ioc_value(hit.indicator),
+                                        hit.field
+                                    )),
+                                )
+                                .with_field("ioc", ioc_value(hit.indicator))
+                                .with_field("ioc_type", indicator.kind().to_string())
+                                .with_field("ioc_description", indicator.description())
+                                .with_field("ioc_field", hit.field.as_str());
+                                output.write(&sources[m.source], &m.record, event)?;
+                            }
+                        }
+                        output.flush()?;
+                    }
+                    _ => bail!(
+                        "IOC matches can only be displayed as csv, json, markdown or timesketch"
+                    ),
                 }
-                csv_writer.flush()?;
                 Ok(())
             }
             _ => unreachable!(),
@@ -0,0 +1,180 @@
+use std::{fmt::Display, net::IpAddr, str::FromStr};
+
+use anyhow::bail;
+
+/// the kind of an indicator of compromise, which determines how it is
+/// searched in the values of records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndicatorKind {
+    IpAddress,
+    Domain,
+    Hash,
+    FileName,
+    UserName,
+
+    /// any substring, e.g. of a command line
+    Substring,
+}
+
+impl FromStr for IndicatorKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim().to_lowercase().as_str() {
+            "ip" | "ipv4" | "ipv6" | "ip-addr" | "ipv4-addr" | "ipv6-addr" | "address" => {
+                Self::IpAddress
+            }
+            "domain" | "domain-name" | "hostname" | "fqdn" => Self::Domain,
+            "hash" | "md5" | "sha1" | "sha-1" | "sha256" | "sha-256" | "imphash" => Self::Hash,
+            "file" | "filename" | "file-name" | "file_name" => Self::FileName,
+            "user" | "username" | "user-name" | "user_name" | "account" | "user-account" => {
+                Self::UserName
+            }
+            "commandline" | "command-line" | "command_line" | "cmdline" | "cmd" | "substring"
+            | "string" | "url" => Self::Substring,
+            _ => bail!("unknown indicator type '{s}'"),
+        })
+    }
+}
+
+impl Display for IndicatorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IpAddress => "ip",
+            Self::Domain => "domain",
+            Self::Hash => "hash",
+            Self::FileName => "filename",
+            Self::UserName => "username",
+            Self::Substring => "substring",
+        }
+        .fmt(f)
+    }
+}
+
+impl IndicatorKind {
+    /// guesses the kind of an indicator whose kind is not given explicitly
+    pub fn infer(value: &str) -> Self {
+        if value.parse::<IpAddr>().is_ok() {
+            Self::IpAddress
+        } else if matches!(value.len(), 32 | 40 | 64)
+            && value.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            Self::Hash
+        } else if value.contains(char::is_whitespace) || value.contains(['\\', '/']) {
+            Self::Substring
+        } else if is_file_name(value) {
+            Self::FileName
+        } else if is_domain(value) {
+            Self::Domain
+        } else {
+            Self::Substring
+        }
+    }
+}
+
+/// file extensions which are common in indicators of compromise. Without
+/// them, file names could not be distinguished from domain names
+const FILE_EXTENSIONS: &[&str] = &[
+    "exe", "dll", "sys", "scr", "bat", "cmd", "ps1", "psm1", "vbs", "vbe", "js", "jse",
+    "wsf", "hta", "msi", "lnk", "jar", "py", "zip", "rar", "7z", "iso", "img", "doc", "docm",
+    "xls", "xlsm", "ppt", "pptm", "rtf", "pdf", "tmp", "dat", "bin",
+];
+
+fn is_file_name(value: &str) -> bool {
+    match value.rsplit_once('.') {
+        Some((name, extension)) => {
+            !name.is_empty() && FILE_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+        }
+        None => false,
+    }
+}
+
+fn is_domain(value: &str) -> bool {
+    let labels: Vec<_> = value.trim_end_matches('.').split('.').collect();
+    labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+        && labels
+            .last()
+            .is_some_and(|tld| tld.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+/// an indicator of compromise
+#[derive(Debug, Clone)]
+pub struct Indicator {
+    kind: IndicatorKind,
+    value: String,
+    description: Option<String>,
+}
+
+impl Indicator {
+    /// creates an indicator, whose value is normalized depending on its kind
+    pub fn new(kind: IndicatorKind, value: &str, description: Option<String>) -> Self {
+        let value = value.trim();
+        let value = match kind {
+            IndicatorKind::IpAddress => value
+                .parse::<IpAddr>()
+                .map(|ip| ip.to_string())
+                .unwrap_or_else(|_| value.to_owned()),
+            IndicatorKind::Domain => value.trim_end_matches('.').to_owned(),
+            _ => value.to_owned(),
+        };
+        Self {
+            kind,
+            value,
+            description: description.filter(|d| !d.trim().is_empty()),
+        }
+    }
+
+    pub fn kind(&self) -> IndicatorKind {
+        self.kind
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// checks if the indicator occurs at `start..end` of `text`, by checking
+    /// the characters around the occurrence. This prevents that e.g.
+    /// `10.0.0.1` matches `10.0.0.10`
+    pub fn is_delimited_in(&self, text: &str, start: usize, end: usize) -> bool {
+        let before = text[..start].chars().next_back();
+        let after = text[end..].chars().next();
+        match self.kind {
+            IndicatorKind::IpAddress if self.value.contains(':') => {
+                let is_part = |c: char| c.is_ascii_hexdigit() || c == ':';
+                !before.is_some_and(is_part) && !after.is_some_and(is_part)
+            }
+            IndicatorKind::IpAddress | IndicatorKind::Hash => {
+                let is_part = |c: char| c.is_ascii_alphanumeric() || c == '.';
+                !before.is_some_and(is_part) && !after.is_some_and(is_part)
+            }
+
+            // subdomains are matched too
+            IndicatorKind::Domain => {
+                let is_part = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+                !before.is_some_and(is_part)
+                    && !after.is_some_and(|c| is_part(c) || c == '.')
+            }
+
+            // file names are matched if they are the last component of a path
+            IndicatorKind::FileName => {
+                let is_separator =
+                    |c: char| c.is_whitespace() || matches!(c, '\\' | '/' | '"' | '\'' | '=' | ',');
+                before.is_none_or(is_separator) && after.is_none_or(is_separator)
+            }
+
+            // user names are matched if they are the complete value, or the
+            // name part of `DOMAIN\name` or `name@domain`
+            IndicatorKind::UserName => {
+                before.is_none_or(|c| c == '\\') && after.is_none_or(|c| c == '@')
+            }
+            IndicatorKind::Substring => true,
+        }
+    }
+}
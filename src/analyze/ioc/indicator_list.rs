@@ -0,0 +1,215 @@
+use std::{fs::File, path::Path};
+
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
+use anyhow::{anyhow, bail};
+use lazy_regex::regex;
+use serde_json::Value;
+
+use super::{Indicator, IndicatorKind};
+
+/// a list of indicators of compromise, which can be searched in texts
+pub struct IndicatorList {
+    indicators: Vec<Indicator>,
+    automaton: AhoCorasick,
+}
+
+impl IndicatorList {
+    pub fn new(mut indicators: Vec<Indicator>) -> anyhow::Result<Self> {
+        indicators.retain(|i| !i.value().is_empty());
+        let automaton = AhoCorasickBuilder::new()
+            .ascii_case_insensitive(true)
+            .match_kind(MatchKind::Standard)
+            .build(indicators.iter().map(|i| i.value()))?;
+        Ok(Self {
+            indicators,
+            automaton,
+        })
+    }
+
+    /// loads indicators from a file, whose format is determined by its
+    /// extension:
+    ///
+    ///  - `*.csv`: a CSV file with the columns `value` (or `indicator`),
+    ///    `type` and `description` (optional)
+    ///  - `*.json`: a STIX 2 bundle, or a list of STIX objects
+    ///  - otherwise: one indicator per line, optionally prefixed by its type
+    ///    (e.g. `filename:evil.exe`). Empty lines and lines starting with
+    ///    `#` are ignored
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let indicators = match extension.as_str() {
+            "csv" => read_csv(path)?,
+            "json" => read_stix(path)?,
+            _ => read_text(path)?,
+        };
+        if indicators.is_empty() {
+            bail!("{} contains no indicators", path.display());
+        }
+        Self::new(indicators)
+    }
+
+    pub fn len(&self) -> usize {
+        self.indicators.len()
+    }
+
+    pub fn get(&self, index: usize) -> &Indicator {
+        &self.indicators[index]
+    }
+
+    /// returns the indices of all indicators which occur in `text`
+    pub fn find_in(&self, text: &str) -> Vec<usize> {
+        let mut result: Vec<usize> = self
+            .automaton
+            .find_overlapping_iter(text)
+            .filter(|m| {
+                self.indicators[m.pattern().as_usize()].is_delimited_in(text, m.start(), m.end())
+            })
+            .map(|m| m.pattern().as_usize())
+            .collect();
+        result.sort_unstable();
+        result.dedup();
+        result
+    }
+}
+
+fn read_text(path: &Path) -> anyhow::Result<Vec<Indicator>> {
+    let content = std::fs::read_to_string(path)?;
+    let mut indicators = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        // the type prefix must be checked, because values might contain
+        // colons, e.g. IPv6 addresses or paths like `C:\Windows`
+        let typed = line
+            .split_once(':')
+            .and_then(|(kind, value)| kind.parse::<IndicatorKind>().ok().map(|k| (k, value)));
+        indicators.push(match typed {
+            Some((kind, value)) => Indicator::new(kind, value, None),
+            None => Indicator::new(IndicatorKind::infer(line), line, None),
+        });
+    }
+    Ok(indicators)
+}
+
+fn read_csv(path: &Path) -> anyhow::Result<Vec<Indicator>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(File::open(path)?);
+    let headers: Vec<String> = reader
+        .headers()?
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let column = |names: &[&str]| headers.iter().position(|h| names.contains(&h.as_str()));
+
+    let value_column = column(&["value", "indicator", "ioc"])
+        .ok_or_else(|| anyhow!("{} has no column 'value' or 'indicator'", path.display()))?;
+    let kind_column = column(&["type", "kind"]);
+    let description_column = column(&["description", "comment", "name"]);
+
+    let mut indicators = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let value = match record.get(value_column) {
+            Some(value) if !value.trim().is_empty() => value.trim(),
+            _ => continue,
+        };
+        let kind = match kind_column.and_then(|c| record.get(c)).filter(|k| !k.is_empty()) {
+            Some(kind) => kind.parse()?,
+            None => IndicatorKind::infer(value),
+        };
+        let description = description_column
+            .and_then(|c| record.get(c))
+            .map(str::to_owned);
+        indicators.push(Indicator::new(kind, value, description));
+    }
+    Ok(indicators)
+}
+
+/// reads indicators from STIX objects. Only simple comparisons in patterns
+/// (e.g. `[file:name = 'evil.exe' OR ipv4-addr:value = '10.0.0.1']`) and
+/// observable objects are supported
+fn read_stix(path: &Path) -> anyhow::Result<Vec<Indicator>> {
+    let content: Value = serde_json::from_reader(File::open(path)?)?;
+    let objects = match &content {
+        Value::Array(objects) => objects.as_slice(),
+        Value::Object(bundle) => match bundle.get("objects") {
+            Some(Value::Array(objects)) => objects.as_slice(),
+            _ => std::slice::from_ref(&content),
+        },
+        _ => bail!("{} contains no STIX objects", path.display()),
+    };
+
+    let mut indicators = Vec::new();
+    for object in objects {
+        let description = object
+            .get("name")
+            .or_else(|| object.get("description"))
+            .and_then(Value::as_str)
+            .map(str::to_owned);
+        let mut add = |property: &str, value: &str| match stix_kind(property) {
+            Some(kind) => indicators.push(Indicator::new(kind, value, description.clone())),
+            None => log::warn!("ignoring unsupported STIX property '{property}'"),
+        };
+
+        match object.get("type").and_then(Value::as_str) {
+            Some("indicator") => {
+                let pattern = object.get("pattern").and_then(Value::as_str).unwrap_or_default();
+                for comparison in
+                    regex!(r"([\w-]+:[\w.'-]+)\s*=\s*'((?:[^'\\]|\\.)*)'").captures_iter(pattern)
+                {
+                    add(&comparison[1], &comparison[2].replace("\\'", "'").replace("\\\\", "\\"));
+                }
+            }
+            Some("file") => {
+                if let Some(name) = object.get("name").and_then(Value::as_str) {
+                    add("file:name", name);
+                }
+                if let Some(Value::Object(hashes)) = object.get("hashes") {
+                    for hash in hashes.values().filter_map(Value::as_str) {
+                        add("file:hashes", hash);
+                    }
+                }
+            }
+            Some("user-account") => {
+                for property in ["user_id", "account_login"] {
+                    if let Some(name) = object.get(property).and_then(Value::as_str) {
+                        add(&format!("user-account:{property}"), name);
+                    }
+                }
+            }
+            Some("process") => {
+                if let Some(command_line) = object.get("command_line").and_then(Value::as_str) {
+                    add("process:command_line", command_line);
+                }
+            }
+            Some(kind) => {
+                if let Some(value) = object.get("value").and_then(Value::as_str) {
+                    add(&format!("{kind}:value"), value);
+                }
+            }
+            None => (),
+        }
+    }
+    Ok(indicators)
+}
+
+/// maps a STIX object path (e.g. `file:hashes.'SHA-256'`) to the kind of
+/// indicator
+fn stix_kind(property: &str) -> Option<IndicatorKind> {
+    let (object, path) = property.split_once(':')?;
+    Some(match (object, path.split('.').next()?) {
+        ("ipv4-addr" | "ipv6-addr", _) => IndicatorKind::IpAddress,
+        ("domain-name", _) => IndicatorKind::Domain,
+        ("file", "hashes") => IndicatorKind::Hash,
+        ("file", "name") => IndicatorKind::FileName,
+        ("user-account", "user_id" | "account_login") => IndicatorKind::UserName,
+        ("process", "command_line") | ("url", _) => IndicatorKind::Substring,
+        _ => return None,
+    })
+}
@@ -0,0 +1,74 @@
+use eventdata::resolve_parameter_messages_in;
+use evtx::SerializedEvtxRecord;
+use libevtx::{flatten_custom_data, EvtxSource};
+use serde_json::Value;
+
+use super::IndicatorList;
+
+/// an indicator which has been found in a field of a record
+pub struct IocHit {
+    /// the index of the indicator
+    pub indicator: usize,
+    pub field: String,
+    pub value: String,
+}
+
+/// a record which contains at least one indicator
+pub struct IocMatch {
+    pub hits: Vec<IocHit>,
+
+    /// the index of the evtx file which contains the record
+    pub source: usize,
+
+    pub record: SerializedEvtxRecord<Value>,
+}
+
+/// searches the indicators in all values of `EventData` and `UserData` of
+/// all records of `sources`. The matches are sorted by their timestamp
+pub fn find_ioc_matches(
+    indicators: &IndicatorList,
+    sources: &[EvtxSource],
+    resolve_parameters: bool,
+) -> anyhow::Result<Vec<IocMatch>> {
+    let mut matches = Vec::new();
+    for (source_index, source) in sources.iter().enumerate() {
+        log::info!("scanning {source}");
+        for record in source.open()?.records_json_value() {
+            let mut record = match record {
+                Ok(record) => record,
+                Err(why) => {
+                    log::warn!("error while reading {source}: {why}");
+                    continue;
+                }
+            };
+            if resolve_parameters {
+                resolve_parameter_messages_in(&mut record.data);
+            }
+
+            let mut hits = Vec::new();
+            for (field, value) in flatten_custom_data(&record) {
+                let value = match value {
+                    Value::Null => continue,
+                    Value::String(s) => s,
+                    v => v.to_string(),
+                };
+                for indicator in indicators.find_in(&value) {
+                    hits.push(IocHit {
+                        indicator,
+                        field: field.clone(),
+                        value: value.clone(),
+                    });
+                }
+            }
+            if !hits.is_empty() {
+                matches.push(IocMatch {
+                    hits,
+                    source: source_index,
+                    record,
+                });
+            }
+        }
+    }
+    matches.sort_by_key(|m| m.record.timestamp);
+    Ok(matches)
+}
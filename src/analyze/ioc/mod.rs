@@ -0,0 +1,6 @@
+mod indicator;
+mod indicator_list;
+mod ioc_match;
+pub use indicator::*;
+pub use indicator_list::*;
+pub use ioc_match::*;
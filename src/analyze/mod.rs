@@ -1,5 +1,8 @@
 mod cli;
+pub (crate) mod ioc;
 pub (crate) mod pstree;
+mod record_output;
 pub (crate) mod sessions;
+pub (crate) mod sigma;
 
 pub (crate) use cli::*;
\ No newline at end of file
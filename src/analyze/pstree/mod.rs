@@ -6,13 +6,19 @@ use std::{
     rc::{Rc, Weak},
 };
 
-use chrono::{DateTime, Utc};
-use evtx::EvtxParser;
+use anyhow::bail;
+use chrono::{DateTime, SecondsFormat, Utc};
+use eventdata::resolve_parameter_messages_in;
+use evtx::SerializedEvtxRecord;
+use libevtx::{
+    AccountReference, EvtxSource, IdentityStore, Pseudonymizer, TimestampFormat,
+    TimestampFormatter,
+};
 pub(crate) use process::*;
 use regex::Regex;
 use serde_json::{json, Value};
 
-use crate::analyze::{pstree::unique_pid::UniquePid, Format};
+use crate::analyze::{pstree::unique_pid::UniquePid, record_output::TimesketchOutput, Format};
 
 use super::Cli;
 
@@ -20,29 +26,90 @@ pub(crate) fn display_pstree(cli: &Cli) -> anyhow::Result<()> {
     match &cli.command {
         crate::analyze::Command::PsTree {
             username,
+            identity,
             evtx_file,
-            format
+            format,
+            partition,
         } => {
+            if partition.is_some() && !matches!(format, Format::Timesketch) {
+                bail!("only timesketch output can be partitioned");
+            }
+
             let username_regex = username
                 .as_ref()
                 .map(|s| Regex::new(&format!("(?i){}", s)).expect("invalid username regex"));
 
-            let has_username = |p: &Process| match username_regex.as_ref() {
+            // usernames are given in clear text, so they must be matched
+            // before the record is pseudonymized
+            let has_username = |record: &SerializedEvtxRecord<Value>| match &username_regex {
                 None => true,
-                Some(username) => {
-                    username.is_match(&p.subject_user_name)
-                        || username.is_match(&p.target_user_name)
+                Some(username) => ["SubjectUserName", "TargetUserName"].iter().any(|field| {
+                    record.data["Event"]["EventData"][field]
+                        .as_str()
+                        .is_some_and(|name| username.is_match(name))
+                }),
+            };
+
+            let sources = cli.input.sources([evtx_file])?;
+            let mut pseudonymizer = cli.pseudonym.pseudonymizer()?;
+
+            // users are identified using all events, not only process
+            // creations. The files are read twice, so that only process
+            // creations have to be kept in memory
+            let mut identities = IdentityStore::default();
+            for_each_record(&sources, cli.resolve_parameters, |_, mut record| {
+                if let Some(pseudonymizer) = pseudonymizer.as_mut() {
+                    pseudonymizer.pseudonymize_record(&mut record)?;
+                }
+                identities.learn_record(&record);
+                Ok(())
+            })?;
+
+            let mut processes = Vec::new();
+            for_each_record(&sources, cli.resolve_parameters, |source, mut record| {
+                if !has_username(&record) {
+                    return Ok(());
                 }
+                if let Some(pseudonymizer) = pseudonymizer.as_mut() {
+                    pseudonymizer.pseudonymize_record(&mut record)?;
+                }
+                let event_record_id = record.event_record_id;
+                match Process::try_from(source, record) {
+                    Ok(Some(process)) => processes.push(process),
+                    Ok(None) => (),
+                    Err(why) => log::warn!("ignoring invalid record {event_record_id}: {why}"),
+                }
+                Ok(())
+            })?;
+
+            // records have been pseudonymized, so the identity must be too
+            let identity = identity
+                .as_ref()
+                .map(|i| match pseudonymizer.as_mut() {
+                    Some(pseudonymizer) => pseudonymizer.pseudonymize_account(i),
+                    None => Ok(i.clone()),
+                })
+                .transpose()?;
+            let identity_pattern = identity
+                .as_ref()
+                .and_then(|i| identities.resolve(&AccountReference::from(&i[..])));
+            let has_identity = |p: &Process| match identity_pattern.as_ref() {
+                None => true,
+                Some(pattern) => p
+                    .subject_identity
+                    .iter()
+                    .chain(p.target_identity.iter())
+                    .any(|i| identities.matches(pattern, i)),
             };
 
-            let mut parser = EvtxParser::from_path(evtx_file)?;
             let mut unique_pids = HashMap::new();
-            let events: HashMap<_, _> = parser
-                .records_json_value()
-                .map(|r| r.expect("error reading event"))
-                .map(Process::try_from)
-                .filter_map(|r| r.expect("invalid event"))
-                .filter(|p| has_username(p))
+            let events: HashMap<_, _> = processes
+                .into_iter()
+                .map(|mut p| {
+                    p.resolve_identities(&identities);
+                    p
+                })
+                .filter(|p| has_identity(p))
                 .map(|e| {
                     let pid = UniquePid::from(&e);
                     unique_pids
@@ -82,26 +149,21 @@ pub(crate) fn display_pstree(cli: &Cli) -> anyhow::Result<()> {
                 }
             }
 
-            let root_processes: BTreeMap<_, _> = events
-                .values()
-                .filter(|e| e.borrow().is_root)
-                .map(|e| {
-                    let timestamp = e.borrow().timestamp;
-                    let value = Value::from(&*e.borrow());
-                    (timestamp, value)
-                })
-                .collect();
+            let root_processes = events.values().filter(|e| e.borrow().is_root).count();
 
-            log::warn!("{} processes have no parent", root_processes.len());
+            log::warn!("{} processes have no parent", root_processes);
 
             match format {
                 Format::Json => {
+                    let formatter = cli
+                        .time
+                        .formatter(TimestampFormat::Rfc3339(SecondsFormat::AutoSi));
                     let root_processes: BTreeMap<_, _> = events
                         .values()
                         .filter(|e| e.borrow().is_root)
                         .map(|e| {
                             let timestamp = e.borrow().timestamp;
-                            let value = Value::from(&*e.borrow());
+                            let value = e.borrow().to_json(&formatter);
                             (timestamp, value)
                         })
                         .collect();
@@ -112,6 +174,18 @@ pub(crate) fn display_pstree(cli: &Cli) -> anyhow::Result<()> {
 
                 Format::Csv => unimplemented!(),
 
+                Format::Timesketch => {
+                    let mut processes: Vec<_> = events.values().collect();
+                    processes.sort_by_key(|p| p.borrow().timestamp);
+
+                    let mut output = TimesketchOutput::new(partition.as_ref(), &cli.time.timezone);
+                    for process in processes {
+                        let process = process.borrow();
+                        output.write(&process.source, &process.system, process.to_timesketch())?;
+                    }
+                    output.flush()?;
+                }
+
                 Format::Markdown => {
                     let root_processes: BTreeMap<_, _> = events
                         .values()
@@ -122,7 +196,10 @@ pub(crate) fn display_pstree(cli: &Cli) -> anyhow::Result<()> {
                             (timestamp, proc)
                         })
                         .collect();
-                    display_markdown(&root_processes, 0);
+                    let formatter = cli
+                        .time
+                        .formatter(TimestampFormat::Rfc3339(SecondsFormat::Secs));
+                    display_markdown(&root_processes, 0, &formatter);
                 }
 
                 Format::LaTeX => {
@@ -135,7 +212,10 @@ pub(crate) fn display_pstree(cli: &Cli) -> anyhow::Result<()> {
                             (timestamp, proc)
                         })
                         .collect();
-                    display_latex(&root_processes);
+                    let formatter = cli
+                        .time
+                        .formatter(TimestampFormat::Custom("%FT%T".to_owned()));
+                    display_latex(&root_processes, &formatter);
                 }
 
                 Format::Dot => {
@@ -148,29 +228,66 @@ pub(crate) fn display_pstree(cli: &Cli) -> anyhow::Result<()> {
                             (timestamp, proc)
                         })
                         .collect();
+                    let formatter = cli
+                        .time
+                        .formatter(TimestampFormat::Custom("%FT%T".to_owned()));
                     println!("digraph {{");
                     println!("rankdir=\"LR\";");
-                    display_dot(&root_processes);
+                    display_dot(&root_processes, &formatter);
                     println!("}}");
                 }
             }
 
-            Ok(())
+            pseudonymizer
+                .as_ref()
+                .map_or(Ok(()), Pseudonymizer::save_mapping)
         }
         _ => unreachable!(),
     }
 }
 
-fn display_markdown(procs: &BTreeMap<DateTime<Utc>, Weak<RefCell<Process>>>, indent: usize) {
+/// calls `f` for every record which can be read from `sources`, together
+/// with the source of the record
+fn for_each_record(
+    sources: &[EvtxSource],
+    resolve_parameters: bool,
+    mut f: impl FnMut(&EvtxSource, SerializedEvtxRecord<Value>) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    for source in sources {
+        for record in source.open()?.records_json_value() {
+            let mut record = match record {
+                Ok(record) => record,
+                Err(why) => {
+                    log::warn!("error while reading {source}: {why}");
+                    continue;
+                }
+            };
+            if resolve_parameters {
+                resolve_parameter_messages_in(&mut record.data);
+            }
+            f(source, record)?;
+        }
+    }
+    Ok(())
+}
+
+fn display_markdown(
+    procs: &BTreeMap<DateTime<Utc>, Weak<RefCell<Process>>>,
+    indent: usize,
+    formatter: &TimestampFormatter,
+) {
     for proc in procs.values() {
         if let Some(proc) = proc.upgrade() {
-            println!("{}- {}", " ".repeat(indent), proc.borrow());
-            display_markdown(&proc.borrow().children, indent + 2);
+            println!("{}- {}", " ".repeat(indent), proc.borrow().to_markdown(formatter));
+            display_markdown(&proc.borrow().children, indent + 2, formatter);
         }
     }
 }
 
-fn display_latex(procs: &BTreeMap<DateTime<Utc>, Weak<RefCell<Process>>>) {
+fn display_latex(
+    procs: &BTreeMap<DateTime<Utc>, Weak<RefCell<Process>>>,
+    formatter: &TimestampFormatter,
+) {
     if !procs.is_empty() {
         println!("\\begin{{enumerate}}");
         for proc in procs.values() {
@@ -178,17 +295,20 @@ fn display_latex(procs: &BTreeMap<DateTime<Utc>, Weak<RefCell<Process>>>) {
                 let p = proc.borrow();
                 let pid = &p.new_process_id;
                 let filename = &p.new_process_name;
-                let timestamp = p.timestamp.format("%FT%T");
+                let timestamp = formatter.format(&p.timestamp);
                 let user = p.subject_user_name.replace('_', "\\_").replace('$', "\\$");
                 println!("\\item[\\texttt{{{pid}}}] \\filename{{{filename}}}, gestartet: \\ts{{{timestamp}}}, Benutzer: \\username{{{user}}}",);
-                display_latex(&proc.borrow().children);
+                display_latex(&proc.borrow().children, formatter);
             }
         }
         println!("\\end{{enumerate}}");
     }
 }
 
-fn display_dot(procs: &BTreeMap<DateTime<Utc>, Weak<RefCell<Process>>>) {
+fn display_dot(
+    procs: &BTreeMap<DateTime<Utc>, Weak<RefCell<Process>>>,
+    formatter: &TimestampFormatter,
+) {
     for proc in procs.values() {
         if let Some(proc) = proc.upgrade() {
             let p = proc.borrow();
@@ -197,9 +317,9 @@ fn display_dot(procs: &BTreeMap<DateTime<Utc>, Weak<RefCell<Process>>>) {
                 "p{} -> p{} [label=\"{}\"]",
                 p.process_id,
                 p.new_process_id,
-                p.timestamp.format("%FT%T")
+                formatter.format(&p.timestamp)
             );
-            display_dot(&proc.borrow().children);
+            display_dot(&proc.borrow().children, formatter);
         }
     }
 }
@@ -1,8 +1,11 @@
-use std::{collections::{BTreeMap, HashMap}, rc::Weak, cell::RefCell, fmt::Display};
+use std::{collections::{BTreeMap, HashMap}, rc::Weak, cell::RefCell};
 
 use anyhow::bail;
 use chrono::{DateTime, Utc};
 use evtx::SerializedEvtxRecord;
+use libevtx::{
+    AccountReference, EvtxSource, Identity, IdentityStore, TimesketchEvent, TimestampFormatter,
+};
 use serde_json::{Value, json};
 
 
@@ -24,24 +27,31 @@ pub (crate) struct Process {
     pub (crate) target_logon_id: String,
     pub (crate) parent_process_name: Option<String>,
     pub (crate) mandatory_label: Option<String>,
+    pub (crate) subject_identity: Option<Identity>,
+    pub (crate) target_identity: Option<Identity>,
     pub (crate) children: BTreeMap<DateTime<Utc>, Weak<RefCell<Self>>>,
     pub (crate) is_root: bool,
+
+    /// the evtx file and the system fields of the record, which are needed
+    /// to find the partition of this process
+    pub (crate) source: EvtxSource,
+    pub (crate) system: SerializedEvtxRecord<Value>,
 }
 
-impl From<&Process> for Value {
-    fn from(process: &Process) -> Self {
+impl Process {
+    pub fn to_json(&self, formatter: &TimestampFormatter) -> Value {
+        let process = self;
         let children: BTreeMap<_, _> = process
             .children
             .values()
             .filter_map(|x| x.upgrade())
             .map(|p| {
                 let p: &Process = &p.borrow();
-                let v: Value = p.into();
-                (p.timestamp, v)
+                (p.timestamp, p.to_json(formatter))
             })
             .collect();
         let mut result: HashMap<_, _> = vec![
-            ("timestamp".to_owned(), json!(process.timestamp)),
+            ("timestamp".to_owned(), json!(formatter.format(&process.timestamp))),
             ("event_record_id".to_owned(), json!(process.event_record_id)),
             ("SubjectUserSid".to_owned(), json!(process.subject_user_sid)),
             (
@@ -73,6 +83,8 @@ impl From<&Process> for Value {
                 json!(process.parent_process_name),
             ),
             ("MandatoryLabel".to_owned(), json!(process.mandatory_label)),
+            ("SubjectIdentity".to_owned(), json!(process.subject_identity)),
+            ("TargetIdentity".to_owned(), json!(process.target_identity)),
         ]
         .into_iter()
         .collect();
@@ -80,24 +92,68 @@ impl From<&Process> for Value {
         result.extend(
             children
                 .into_iter()
-                .map(|(k, v)| (k.to_rfc3339_opts(chrono::SecondsFormat::Secs, true), v)),
+                .map(|(k, v)| (formatter.format(&k), v)),
         );
 
         json!(result)
     }
-}
 
-impl Display for Process {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
+    pub fn to_markdown(&self, formatter: &TimestampFormatter) -> String {
+        format!(
             "`{}` (`0x{:04x}`, created *`{}`*, user is `{}`)",
             self.new_process_name,
             self.new_process_id,
-            self.timestamp
-                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
-            self.target_user_name
+            formatter.format(&self.timestamp),
+            match self.target_identity.as_ref() {
+                Some(identity) => identity.to_string(),
+                None => self.target_user_name.clone(),
+            }
+        )
+    }
+
+    pub fn to_timesketch(&self) -> TimesketchEvent {
+        let user = match self.target_identity.as_ref() {
+            Some(identity) => identity.to_string(),
+            None => self.target_user_name.clone(),
+        };
+        TimesketchEvent::new(
+            self.timestamp,
+            format!(
+                "{} (0x{:04x}) started by {user}: {}",
+                self.new_process_name, self.new_process_id, self.command_line
+            ),
+            "Process Creation",
+            "windows:evtx:process",
         )
+        .with_field("event_record_id", self.event_record_id)
+        .with_field("NewProcessId", self.new_process_id)
+        .with_field("NewProcessName", self.new_process_name.clone())
+        .with_field("ProcessId", self.process_id)
+        .with_field("ParentProcessName", self.parent_process_name.clone())
+        .with_field("CommandLine", self.command_line.clone())
+        .with_field("TokenElevationType", self.token_elevation_type.clone())
+        .with_field("MandatoryLabel", self.mandatory_label.clone())
+        .with_field("SubjectUserSid", self.subject_user_sid.clone())
+        .with_field("SubjectLogonId", self.subject_logon_id.clone())
+        .with_field("SubjectIdentity", self.subject_identity.as_ref().map(|i| i.to_string()))
+        .with_field("TargetUserSid", self.target_user_sid.clone())
+        .with_field("TargetLogonId", self.target_logon_id.clone())
+        .with_field("TargetIdentity", user)
+    }
+
+    /// replaces the user information of this process by the canonical
+    /// identities which are known to `identities`
+    pub fn resolve_identities(&mut self, identities: &IdentityStore) {
+        self.subject_identity = identities.resolve(&AccountReference::new(
+            Some(&self.subject_user_sid),
+            Some(&self.subject_user_name),
+            Some(&self.subject_domain_name),
+        ));
+        self.target_identity = identities.resolve(&AccountReference::new(
+            Some(&self.target_user_sid),
+            Some(&self.target_user_name),
+            Some(&self.target_domain_name),
+        ));
     }
 }
 
@@ -141,7 +197,10 @@ fn u64_from_hex_value(value: &Value) -> anyhow::Result<u64> {
 }
 
 impl Process {
-    pub fn try_from(record: SerializedEvtxRecord<Value>) -> anyhow::Result<Option<Self>> {
+    pub fn try_from(
+        source: &EvtxSource,
+        record: SerializedEvtxRecord<Value>,
+    ) -> anyhow::Result<Option<Self>> {
         let value = &record.data;
         let event = from_json!(value, "Event");
         let system = from_json!(event, "System");
@@ -230,8 +289,16 @@ impl Process {
             target_logon_id,
             parent_process_name,
             mandatory_label,
+            subject_identity: None,
+            target_identity: None,
             children: Default::default(),
             is_root: true,
+            source: source.clone(),
+            system: SerializedEvtxRecord {
+                event_record_id: record.event_record_id,
+                timestamp: record.timestamp,
+                data: json!({"Event": {"System": system}}),
+            },
         }))
     }
 }
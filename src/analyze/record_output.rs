@@ -0,0 +1,196 @@
+use std::io::{stdout, Stdout};
+
+use anyhow::anyhow;
+use eventdata::{Channel, Computer, EventId};
+use evtx::SerializedEvtxRecord;
+use libevtx::{
+    EvtxSource, PartitionTemplate, PartitionedWriter, TimeZoneSpec, TimesketchEvent,
+    TimesketchFormat, TimesketchWriter,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// the columns which identify the record of a match
+pub(crate) struct RecordColumns {
+    computer: Option<String>,
+    channel: Option<String>,
+    event_id: Option<u16>,
+    event_record_id: u64,
+    evtx_file: String,
+}
+
+impl RecordColumns {
+    pub const HEADER: [&'static str; 5] = [
+        "computer",
+        "channel",
+        "event_id",
+        "event_record_id",
+        "evtx_file",
+    ];
+
+    pub fn new(source: &EvtxSource, record: &SerializedEvtxRecord<Value>) -> Self {
+        Self {
+            computer: Computer::try_from(record).ok().map(|c| c.to_string()),
+            channel: Channel::try_from(record).ok().map(|c| c.to_string()),
+            event_id: EventId::try_from(record).ok().map(|id| id.value()),
+            event_record_id: record.event_record_id,
+            evtx_file: source.to_string(),
+        }
+    }
+
+    /// the values of the columns, in the order of [`Self::HEADER`]
+    pub fn to_csv(&self) -> [String; 5] {
+        [
+            self.computer.clone().unwrap_or_default(),
+            self.channel.clone().unwrap_or_default(),
+            self.event_id.map(|id| id.to_string()).unwrap_or_default(),
+            self.event_record_id.to_string(),
+            self.evtx_file.clone(),
+        ]
+    }
+
+    /// adds the columns to `object`, which must be a JSON object
+    pub fn add_to(&self, object: &mut Value) {
+        object["computer"] = json!(self.computer);
+        object["channel"] = json!(self.channel);
+        object["event_id"] = json!(self.event_id);
+        object["event_record_id"] = json!(self.event_record_id);
+        object["evtx_file"] = json!(self.evtx_file);
+    }
+}
+
+/// writes the rows of a CSV table, which describe records, to stdout or into
+/// the files of the records' partitions. Every file gets its own header
+pub(crate) enum CsvOutput {
+    Stdout(csv::Writer<Stdout>),
+    Partitioned {
+        writer: PartitionedWriter,
+        header: String,
+    },
+}
+
+impl CsvOutput {
+    pub fn new(
+        header: &[&str],
+        partition: Option<&PartitionTemplate>,
+        timezone: &TimeZoneSpec,
+    ) -> anyhow::Result<Self> {
+        match partition {
+            None => {
+                // the header is written here, not when the first row is
+                // serialized
+                let mut writer = csv::WriterBuilder::new()
+                    .has_headers(false)
+                    .from_writer(stdout());
+                writer.write_record(header)?;
+                Ok(Self::Stdout(writer))
+            }
+            Some(template) => Ok(Self::Partitioned {
+                writer: PartitionedWriter::new(template.clone(), timezone.clone()),
+                header: csv_line(header)?,
+            }),
+        }
+    }
+
+    pub fn write<T: AsRef<[u8]>>(
+        &mut self,
+        source: &EvtxSource,
+        record: &SerializedEvtxRecord<Value>,
+        row: impl IntoIterator<Item = T>,
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Stdout(writer) => Ok(writer.write_record(row)?),
+            Self::Partitioned { writer, header } => {
+                writer.write_line(source, record, Some(header), &csv_line(row)?)
+            }
+        }
+    }
+
+    /// writes a row which is given as struct, whose fields must match the
+    /// header
+    pub fn serialize(
+        &mut self,
+        source: &EvtxSource,
+        record: &SerializedEvtxRecord<Value>,
+        row: impl Serialize,
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Stdout(writer) => Ok(writer.serialize(row)?),
+            Self::Partitioned { writer, header } => {
+                let mut line = headerless_csv_writer();
+                line.serialize(row)?;
+                writer.write_line(source, record, Some(header), &into_line(line)?)
+            }
+        }
+    }
+
+    pub fn flush(&mut self) -> anyhow::Result<()> {
+        match self {
+            Self::Stdout(writer) => Ok(writer.flush()?),
+            Self::Partitioned { writer, .. } => writer.flush(),
+        }
+    }
+}
+
+/// writes Timesketch events, which describe records, as JSON lines to stdout
+/// or into the files of the records' partitions
+pub(crate) enum TimesketchOutput {
+    Stdout(Box<TimesketchWriter<Stdout>>),
+    Partitioned(PartitionedWriter),
+}
+
+impl TimesketchOutput {
+    pub fn new(partition: Option<&PartitionTemplate>, timezone: &TimeZoneSpec) -> Self {
+        match partition {
+            None => Self::Stdout(Box::new(TimesketchWriter::new(
+                stdout(),
+                TimesketchFormat::Jsonl,
+            ))),
+            Some(template) => {
+                Self::Partitioned(PartitionedWriter::new(template.clone(), timezone.clone()))
+            }
+        }
+    }
+
+    pub fn write(
+        &mut self,
+        source: &EvtxSource,
+        record: &SerializedEvtxRecord<Value>,
+        event: TimesketchEvent,
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Stdout(writer) => writer.write(event),
+            Self::Partitioned(writer) => {
+                writer.write_line(source, record, None, &event.to_json().to_string())
+            }
+        }
+    }
+
+    pub fn flush(&mut self) -> anyhow::Result<()> {
+        match self {
+            Self::Stdout(writer) => writer.flush(),
+            Self::Partitioned(writer) => writer.flush(),
+        }
+    }
+}
+
+fn csv_line<T: AsRef<[u8]>>(row: impl IntoIterator<Item = T>) -> anyhow::Result<String> {
+    let mut writer = headerless_csv_writer();
+    writer.write_record(row)?;
+    into_line(writer)
+}
+
+fn headerless_csv_writer() -> csv::Writer<Vec<u8>> {
+    csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new())
+}
+
+fn into_line(writer: csv::Writer<Vec<u8>>) -> anyhow::Result<String> {
+    let line = writer
+        .into_inner()
+        .map_err(|why| anyhow!("unable to write CSV: {why}"))?;
+    Ok(String::from_utf8(line)?
+        .trim_end_matches(['\r', '\n'])
+        .to_owned())
+}
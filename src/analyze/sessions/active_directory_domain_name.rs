@@ -1,6 +1,7 @@
 use std::fmt::Display;
 use std::hash::Hash;
 
+use libevtx::domain_names_match;
 use serde::Serialize;
 
 #[derive(Debug, Clone, Eq, Serialize)]
@@ -26,16 +27,7 @@ impl Display for ActiveDirectoryDomainName {
 
 impl PartialEq for ActiveDirectoryDomainName {
     fn eq(&self, other: &Self) -> bool {
-        let me: String = self.0.to_lowercase();
-        let other = other.0.to_lowercase();
-
-        if me.len() > 15 && other.len() == 15 {
-            me[..15] == other
-        } else if me.len() == 15 && other.len() > 15 {
-            me == other[..15]
-        } else {
-            me == other
-        }
+        domain_names_match(&self.0, &other.0)
     }
 }
 
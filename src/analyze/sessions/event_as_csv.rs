@@ -1,5 +1,5 @@
-use chrono::{DateTime, Utc};
-use eventdata::{EventId, EventLevel};
+use eventdata::{DisconnectReason, EventId, EventLevel, LogonType, NtStatus};
+use libevtx::TimestampFormatter;
 use serde::Serialize;
 
 use super::SessionEvent;
@@ -7,7 +7,7 @@ use super::SessionEvent;
 #[derive(Serialize)]
 pub struct EventAsCsv {
     level: Option<EventLevel>,
-    timestamp: DateTime<Utc>,
+    timestamp: String,
     event_id: EventId,
     description: String,
 
@@ -20,11 +20,16 @@ pub struct EventAsCsv {
 
     pub server_address: Option<String>,
     pub server_name: Option<String>,
+
+    pub logon_type: Option<LogonType>,
+    pub status: Option<NtStatus>,
+    pub sub_status: Option<NtStatus>,
+    pub disconnect_reason: Option<DisconnectReason>,
 }
 
-impl From<&SessionEvent> for EventAsCsv {
-    fn from(value: &SessionEvent) -> Self {
-        let timestamp = value.record().timestamp;
+impl EventAsCsv {
+    pub fn new(value: &SessionEvent, formatter: &TimestampFormatter) -> Self {
+        let timestamp = formatter.format(&value.record().timestamp);
         let event_id = value.event_type().event_id();
         let description = value.event_type().description().to_owned();
 
@@ -39,6 +44,10 @@ impl From<&SessionEvent> for EventAsCsv {
             client_name: value.event_type().client_hostname(value.record()),
             server_address: value.event_type().server_address(value.record()),
             server_name: value.event_type().server_hostname(value.record()),
+            logon_type: LogonType::try_from(value.record()).ok(),
+            status: NtStatus::from_event_data(value.record(), "Status").ok(),
+            sub_status: NtStatus::from_event_data(value.record(), "SubStatus").ok(),
+            disconnect_reason: DisconnectReason::try_from(value.record()).ok(),
         }
     }
 }
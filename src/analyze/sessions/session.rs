@@ -1,19 +1,22 @@
-use std::{
-    collections::{BTreeSet, HashSet},
-    io::Write,
-};
+use std::collections::{BTreeSet, HashSet};
 
 use super::{ActiveDirectoryDomainName, SessionAsCsv, SessionAsJson, SessionEvent};
-use eventdata::SessionId;
+use eventdata::{DisconnectReason, LogonType, NtStatus, SessionId};
+use libevtx::{AccountReference, Identity, IdentityStore, TimesketchEvent, TimestampFormatter};
+use serde_json::Value;
 
 pub struct Session {
     events: BTreeSet<SessionEvent>,
     session_id: SessionId,
     domain: Option<ActiveDirectoryDomainName>,
     usernames: HashSet<String>,
+    identities: BTreeSet<Identity>,
     clients: HashSet<String>,
     server: Option<String>,
     computer: String,
+    logon_types: BTreeSet<String>,
+    failure_reasons: BTreeSet<String>,
+    disconnect_reasons: BTreeSet<String>,
 }
 
 impl Session {
@@ -91,9 +94,52 @@ impl Session {
             }
         }
 
+        if let Ok(logon_type) = LogonType::try_from(event.record()) {
+            self.logon_types.insert(logon_type.to_string());
+        }
+
+        // the substatus is more specific, if it is set
+        let failure_reason = match NtStatus::from_event_data(event.record(), "SubStatus") {
+            Ok(sub_status) if !sub_status.is_success() => Some(sub_status),
+            _ => NtStatus::from_event_data(event.record(), "Status")
+                .ok()
+                .filter(|status| !status.is_success()),
+        };
+        if let Some(failure_reason) = failure_reason {
+            self.failure_reasons.insert(failure_reason.to_string());
+        }
+
+        if let Ok(reason) = DisconnectReason::try_from(event.record()) {
+            self.disconnect_reasons.insert(reason.to_string());
+        }
+
         self.events.insert(event);
     }
 
+    /// determines the canonical identities of all users of this session
+    pub fn resolve_identities(&mut self, identities: &IdentityStore) {
+        self.identities = self
+            .events
+            .iter()
+            .filter_map(|event| {
+                let username = event.event_type().username(event.record())?;
+                let mut reference = AccountReference::from(&username[..]);
+                if reference.domain.is_none() {
+                    reference.domain = event.event_type().domain(event.record());
+                }
+                identities.resolve(&reference)
+            })
+            .collect();
+
+        if let Some(domain) = self.domain.as_ref() {
+            self.domain = Some(identities.canonical_domain(&domain.to_string()).into());
+        }
+    }
+
+    pub fn identities(&self) -> impl Iterator<Item = &Identity> {
+        self.identities.iter()
+    }
+
     pub fn first_event(&self) -> &SessionEvent {
         debug_assert!(!self.events.is_empty());
         self.events.first().unwrap()
@@ -104,11 +150,43 @@ impl Session {
         self.events.last().unwrap()
     }
 
-    pub fn into_csv<W>(self, writer: &mut csv::Writer<W>) -> csv::Result<()>
-    where
-        W: Write,
-    {
-        writer.serialize(&Into::<SessionAsCsv>::into(self))
+    /// converts the session into two Timesketch events, which mark the
+    /// begin and the end of the session, together with the session events
+    /// they have been created from
+    pub fn to_timesketch(
+        &self,
+        formatter: &TimestampFormatter,
+    ) -> Vec<(&SessionEvent, TimesketchEvent)> {
+        let session = self.to_session_as_csv(formatter);
+        let description = format!(
+            "session of {} from {} on {}",
+            if session.usernames.is_empty() { "unknown user" } else { &session.usernames },
+            if session.clients.is_empty() { "unknown client" } else { &session.clients },
+            session.computer
+        );
+
+        let fields = match serde_json::to_value(&session) {
+            Ok(Value::Object(fields)) => fields,
+            _ => Default::default(),
+        };
+        [
+            (self.first_event(), "Session Start"),
+            (self.last_event(), "Session End"),
+        ]
+        .into_iter()
+        .map(|(session_event, timestamp_desc)| {
+            let mut event = TimesketchEvent::new(
+                session_event.record().timestamp,
+                format!("{timestamp_desc}: {description}"),
+                timestamp_desc,
+                "windows:evtx:session",
+            );
+            for (name, value) in fields.iter() {
+                event.add_field(name, value.clone());
+            }
+            (session_event, event)
+        })
+        .collect()
     }
 
     pub fn is_anonymous(&self) -> bool {
@@ -140,9 +218,13 @@ impl From<SessionEvent> for Session {
             session_id,
             domain: None,
             usernames: HashSet::new(),
+            identities: BTreeSet::new(),
             clients: HashSet::new(),
             server: None,
             computer,
+            logon_types: BTreeSet::new(),
+            failure_reasons: BTreeSet::new(),
+            disconnect_reasons: BTreeSet::new(),
         };
 
         me.add_event(value);
@@ -183,33 +265,39 @@ impl Into<SessionAsJson> for Session {
             end,
             duration,
             session_id,
-            usernames: self.usernames,
+            usernames: self.identities.iter().map(|i| i.to_string()).collect(),
             events,
         }
     }
 }
 
-#[allow(clippy::from_over_into)]
-impl Into<SessionAsCsv> for Session {
-    fn into(self) -> SessionAsCsv {
+impl Session {
+    pub fn to_session_as_csv(&self, formatter: &TimestampFormatter) -> SessionAsCsv {
         let begin = self.first_event().record().timestamp;
         let end = self.last_event().record().timestamp;
         let duration = end - begin;
         let session_id = self.session_id().clone();
         let events = self.events.len();
-        let usernames: Vec<_> = self.usernames.into_iter().collect();
-        let clients: Vec<_> = self.clients.into_iter().collect();
+        let usernames: Vec<_> = self.identities.iter().map(|i| i.to_string()).collect();
+        let clients: Vec<_> = self.clients.iter().cloned().collect();
         SessionAsCsv {
-            begin,
-            end,
+            begin: formatter.format(&begin),
+            end: formatter.format(&end),
             duration,
             session_id,
             domain: self.domain.clone(),
             usernames: usernames.join(", "),
             clients: clients.join(", "),
-            server: self.server,
-            computer: self.computer,
+            server: self.server.clone(),
+            computer: self.computer.clone(),
             events,
+            logon_types: join(&self.logon_types),
+            failure_reasons: join(&self.failure_reasons),
+            disconnect_reasons: join(&self.disconnect_reasons),
         }
     }
 }
+
+fn join(values: &BTreeSet<String>) -> String {
+    values.iter().cloned().collect::<Vec<_>>().join(", ")
+}
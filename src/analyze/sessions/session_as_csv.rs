@@ -1,4 +1,4 @@
-use chrono::{DateTime, Duration, Utc};
+use chrono::Duration;
 use eventdata::SessionId;
 use serde::{Serialize, Serializer};
 
@@ -6,11 +6,9 @@ use super::ActiveDirectoryDomainName;
 
 #[derive(Serialize, Debug)]
 pub struct SessionAsCsv {
-    #[serde(serialize_with = "serialize_timestamp")]
-    pub begin: DateTime<Utc>,
+    pub begin: String,
 
-    #[serde(serialize_with = "serialize_timestamp")]
-    pub end: DateTime<Utc>,
+    pub end: String,
 
     #[serde(serialize_with = "serialize_duration")]
     pub duration: Duration,
@@ -27,20 +25,37 @@ pub struct SessionAsCsv {
 
     pub session_id: SessionId,
 
+    pub logon_types: String,
+
+    pub failure_reasons: String,
+
+    pub disconnect_reasons: String,
+
     pub events: usize,
 }
 
-fn serialize_duration<S>(duration: &Duration, s: S) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-{
-    s.serialize_str(&format!("{duration}"))
+impl SessionAsCsv {
+    /// the names of the columns, in the order of the fields
+    pub const HEADER: [&'static str; 13] = [
+        "begin",
+        "end",
+        "duration",
+        "domain",
+        "usernames",
+        "clients",
+        "server",
+        "computer",
+        "session_id",
+        "logon_types",
+        "failure_reasons",
+        "disconnect_reasons",
+        "events",
+    ];
 }
 
-
-fn serialize_timestamp<S>(ts: &DateTime<Utc>, s: S) -> Result<S::Ok, S::Error>
+fn serialize_duration<S>(duration: &Duration, s: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
-    s.serialize_str(&ts.format("%Y-%m-%dT%H:%M:%S").to_string())
+    s.serialize_str(&format!("{duration}"))
 }
@@ -4,6 +4,7 @@ use eventdata::{
     EventId, EventProvider, SessionId
 };
 use evtx::SerializedEvtxRecord;
+use libevtx::{EvtxSource, TimestampFormatter};
 use serde_json::Value;
 
 use eventdata::SessionEventInfo;
@@ -14,12 +15,13 @@ use super::SessionEventError;
 
 pub struct SessionEvent {
     event_type: Box<dyn SessionEventInfo>,
+    source: EvtxSource,
     record: SerializedEvtxRecord<serde_json::Value>,
     session_id: SessionId,
 }
 
 impl SessionEvent {
-    fn new<I>(record: SerializedEvtxRecord<serde_json::Value>) -> Self
+    fn new<I>(source: &EvtxSource, record: SerializedEvtxRecord<serde_json::Value>) -> Self
     where
         I: SessionEventInfo + Default + 'static,
     {
@@ -27,6 +29,7 @@ impl SessionEvent {
         let session_id = event_type.generate_id(&record);
         Self {
             event_type,
+            source: source.clone(),
             record,
             session_id,
         }
@@ -36,6 +39,11 @@ impl SessionEvent {
         self.event_type.as_ref()
     }
 
+    /// the evtx file which contains the record of this event
+    pub fn source(&self) -> &EvtxSource {
+        &self.source
+    }
+
     pub fn record(&self) -> &SerializedEvtxRecord<Value> {
         &self.record
     }
@@ -44,47 +52,53 @@ impl SessionEvent {
         &self.session_id
     }
 
-    pub fn to_csv<W>(&self, writer: &mut csv::Writer<W>) -> csv::Result<()>
+    pub fn to_csv<W>(
+        &self,
+        writer: &mut csv::Writer<W>,
+        formatter: &TimestampFormatter,
+    ) -> csv::Result<()>
     where
         W: Write,
     {
-        writer.serialize(&Into::<EventAsCsv>::into(self))
+        writer.serialize(EventAsCsv::new(self, formatter))
     }
 }
 
-impl TryFrom<SerializedEvtxRecord<serde_json::Value>> for SessionEvent {
+impl TryFrom<(&EvtxSource, SerializedEvtxRecord<serde_json::Value>)> for SessionEvent {
     type Error = SessionEventError;
 
-    fn try_from(record: SerializedEvtxRecord<serde_json::Value>) -> Result<Self, Self::Error> {
+    fn try_from(
+        (source, record): (&EvtxSource, SerializedEvtxRecord<serde_json::Value>),
+    ) -> Result<Self, Self::Error> {
         let event_id = EventId::try_from(&record)?;
         let provider = EventProvider::try_from(&record)?;
         let event = match provider {
             EventProvider::TerminalServicesRemoteConnectionManager => match event_id.value() {
-                1149 => Self::new::<TSRCMUserAuthenticationSucceeded>(record),
+                1149 => Self::new::<TSRCMUserAuthenticationSucceeded>(source, record),
                 _ => return Err(SessionEventError::NoSessionEvent),
             },
             EventProvider::TerminalServicesLocalSessionManager => match event_id.value() {
-                21 => Self::new::<TSLCMSessionLogonSucceeded>(record),
-                22 => Self::new::<TSLCMShellStartNotificationReceived>(record),
-                23 => Self::new::<TSLCMSessionLogoffSucceeded>(record),
-                24 => Self::new::<TSLCMSessionHasBeenDisconnected>(record),
-                25 => Self::new::<TSLCMSessionReconnectionSucceeded>(record),
-                39 => Self::new::<TSLCMSessionXHasBeenDisconnectedBySessionY>(record),
-                40 => Self::new::<TSLCMSessionXHasBeenDisconnectedReasonCodeZ>(record),
+                21 => Self::new::<TSLCMSessionLogonSucceeded>(source, record),
+                22 => Self::new::<TSLCMShellStartNotificationReceived>(source, record),
+                23 => Self::new::<TSLCMSessionLogoffSucceeded>(source, record),
+                24 => Self::new::<TSLCMSessionHasBeenDisconnected>(source, record),
+                25 => Self::new::<TSLCMSessionReconnectionSucceeded>(source, record),
+                39 => Self::new::<TSLCMSessionXHasBeenDisconnectedBySessionY>(source, record),
+                40 => Self::new::<TSLCMSessionXHasBeenDisconnectedReasonCodeZ>(source, record),
                 _ => return Err(SessionEventError::NoSessionEvent),
             },
             EventProvider::SecurityAuditing => match event_id.value() {
-                4624 => Self::new::<SecuritySuccessfulLogin>(record),
-                4625 => Self::new::<SecurityFailedLogin>(record),
-                4627 => Self::new::<SecurityGroupMembership>(record),
-                4634 => Self::new::<SecuritySuccessfulLogoff>(record),
-                4647 => Self::new::<SecurityUserInitiatedLogoff>(record),
-                4778 => Self::new::<SecuritySessionWasReconnected>(record),
-                4779 => Self::new::<SecuritySessionWasDisconnected>(record),
+                4624 => Self::new::<SecuritySuccessfulLogin>(source, record),
+                4625 => Self::new::<SecurityFailedLogin>(source, record),
+                4627 => Self::new::<SecurityGroupMembership>(source, record),
+                4634 => Self::new::<SecuritySuccessfulLogoff>(source, record),
+                4647 => Self::new::<SecurityUserInitiatedLogoff>(source, record),
+                4778 => Self::new::<SecuritySessionWasReconnected>(source, record),
+                4779 => Self::new::<SecuritySessionWasDisconnected>(source, record),
                 _ => return Err(SessionEventError::NoSessionEvent),
             },
             EventProvider::RemoteDesktopServicesRdpCoreTS => match event_id.value() {
-                131 => Self::new::<RdpAcceptedConnection>(record),
+                131 => Self::new::<RdpAcceptedConnection>(source, record),
                 _ => return Err(SessionEventError::NoSessionEvent),
             }
             _ => {
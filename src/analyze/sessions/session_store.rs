@@ -1,7 +1,7 @@
-use std::{collections::HashMap, path::Path};
+use std::collections::HashMap;
 
-use eventdata::SessionId;
-use evtx::EvtxParser;
+use eventdata::{resolve_parameter_messages_in, SessionId};
+use libevtx::{AccountReference, EvtxSource, IdentityStore, Pseudonymizer};
 
 use super::{Session, SessionEvent};
 
@@ -14,35 +14,59 @@ static KNOWN_FILES: & [&str] = &[
 
 pub struct SessionStore {
     sessions: HashMap<SessionId, Session>,
+    identities: IdentityStore,
 }
 
 impl SessionStore {
-    pub fn import(evtx_files_dir: &Path, include_anonymous: bool) -> Result<Self, anyhow::Error> {
+    pub fn import(
+        sources: &[EvtxSource],
+        include_anonymous: bool,
+        resolve_parameters: bool,
+        mut pseudonymizer: Option<&mut Pseudonymizer>,
+    ) -> Result<Self, anyhow::Error> {
         let mut sessions = Self {
             sessions: HashMap::<SessionId, Session>::new(),
+            identities: IdentityStore::default(),
         };
+        let mut identities = IdentityStore::default();
 
 
         for filename in KNOWN_FILES {
-            let path = evtx_files_dir.join(filename);
-            if !(path.exists() && path.is_file()) {
-                log::warn!("unable to read file {}", path.display());
+            let known_sources: Vec<_> = sources
+                .iter()
+                .filter(|s| s.file_name().eq_ignore_ascii_case(filename))
+                .collect();
+            if known_sources.is_empty() {
+                log::warn!("unable to find file {filename}");
                 continue;
             }
 
-            log::info!("importing {} into session store", path.to_string_lossy());
+            for source in known_sources {
+                log::info!("importing {source} into session store");
 
-            for event in EvtxParser::from_path(path)?
-                .records_json_value()
-                .map(|r| r.expect("error reading event"))
-                .map(SessionEvent::try_from)
-                .filter_map(|r| r.ok())
-            {
-                log::trace!("found session event at {}", event.record().timestamp);
-                sessions.add_event(event);
+                for mut r in source.open()?.records_json_value().filter_map(|r| {
+                    r.map_err(|why| log::warn!("error while reading {source}: {why}"))
+                        .ok()
+                }) {
+                    if resolve_parameters {
+                        resolve_parameter_messages_in(&mut r.data);
+                    }
+                    if let Some(pseudonymizer) = pseudonymizer.as_deref_mut() {
+                        pseudonymizer.pseudonymize_record(&mut r)?;
+                    }
+                    identities.learn_record(&r);
+                    if let Ok(event) = SessionEvent::try_from((source, r)) {
+                        log::trace!("found session event at {}", event.record().timestamp);
+                        sessions.add_event(event);
+                    }
+                }
             }
         }
 
+        for session in sessions.sessions.values_mut() {
+            session.resolve_identities(&identities);
+        }
+
         Ok(Self {
             sessions: sessions
                 .sessions
@@ -55,9 +79,25 @@ impl SessionStore {
                     }
                 })
                 .collect(),
+            identities,
         })
     }
 
+    /// removes all sessions which do not belong to the account referenced
+    /// by `pattern`
+    pub fn retain_identity(&mut self, pattern: &str) {
+        let pattern = match self.identities.resolve(&AccountReference::from(pattern)) {
+            Some(pattern) => pattern,
+            None => return,
+        };
+        let identities = &self.identities;
+        self.sessions.retain(|_, session| {
+            session
+                .identities()
+                .any(|identity| identities.matches(&pattern, identity))
+        });
+    }
+
     fn add_event(&mut self, event: SessionEvent) {
         if self.sessions.contains_key(event.session_id()) {
             self.sessions
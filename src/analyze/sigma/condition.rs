@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail};
+
+/// the condition of a Sigma rule, which combines the results of the
+/// searches of its detection section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    Search(String),
+    Not(Box<Condition>),
+    And(Vec<Condition>),
+    Or(Vec<Condition>),
+
+    /// at least `count` of the searches must match. `None` means that all
+    /// searches must match
+    Quantified {
+        count: Option<usize>,
+        searches: Vec<String>,
+    },
+}
+
+impl Condition {
+    /// parses a condition expression. `searches` are the names of all
+    /// searches of the rule, which are needed to expand patterns like
+    /// `1 of selection_*`
+    pub fn parse(expression: &str, searches: &[&str]) -> anyhow::Result<Self> {
+        if expression.contains('|') {
+            bail!("aggregations are not supported: '{expression}'");
+        }
+
+        let tokens = tokenize(expression);
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+            searches,
+        };
+        let condition = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(condition),
+            Some(token) => bail!("unexpected '{token}' in condition '{expression}'"),
+        }
+    }
+
+    pub fn evaluate(&self, results: &HashMap<&str, bool>) -> bool {
+        let result = |name: &String| results.get(name.as_str()).copied().unwrap_or(false);
+        match self {
+            Condition::Search(name) => result(name),
+            Condition::Not(condition) => !condition.evaluate(results),
+            Condition::And(conditions) => conditions.iter().all(|c| c.evaluate(results)),
+            Condition::Or(conditions) => conditions.iter().any(|c| c.evaluate(results)),
+            Condition::Quantified {
+                count: None,
+                searches,
+            } => searches.iter().all(result),
+            Condition::Quantified {
+                count: Some(count),
+                searches,
+            } => searches.iter().filter(|s| result(s)).count() >= *count,
+        }
+    }
+}
+
+fn tokenize(expression: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    for c in expression.chars() {
+        if c.is_whitespace() || c == '(' || c == ')' {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+            if !c.is_whitespace() {
+                tokens.push(c.to_string());
+            }
+        } else {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+struct Parser<'t> {
+    tokens: &'t [String],
+    position: usize,
+    searches: &'t [&'t str],
+}
+
+impl<'t> Parser<'t> {
+    fn peek(&self) -> Option<&'t str> {
+        self.tokens.get(self.position).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Option<&'t str> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        self.peek().is_some_and(|t| t.eq_ignore_ascii_case(keyword))
+    }
+
+    fn parse_or(&mut self) -> anyhow::Result<Condition> {
+        let mut conditions = vec![self.parse_and()?];
+        while self.peek_keyword("or") {
+            self.next();
+            conditions.push(self.parse_and()?);
+        }
+        Ok(match conditions.len() {
+            1 => conditions.remove(0),
+            _ => Condition::Or(conditions),
+        })
+    }
+
+    fn parse_and(&mut self) -> anyhow::Result<Condition> {
+        let mut conditions = vec![self.parse_not()?];
+        while self.peek_keyword("and") {
+            self.next();
+            conditions.push(self.parse_not()?);
+        }
+        Ok(match conditions.len() {
+            1 => conditions.remove(0),
+            _ => Condition::And(conditions),
+        })
+    }
+
+    fn parse_not(&mut self) -> anyhow::Result<Condition> {
+        if self.peek_keyword("not") {
+            self.next();
+            Ok(Condition::Not(Box::new(self.parse_not()?)))
+        } else {
+            self.parse_primary()
+        }
+    }
+
+    fn parse_primary(&mut self) -> anyhow::Result<Condition> {
+        let token = self
+            .next()
+            .ok_or_else(|| anyhow!("unexpected end of condition"))?;
+
+        if token == "(" {
+            let condition = self.parse_or()?;
+            return match self.next() {
+                Some(")") => Ok(condition),
+                _ => Err(anyhow!("missing ')' in condition")),
+            };
+        }
+
+        if self.peek_keyword("of") {
+            self.next();
+            let count = match token.to_lowercase().as_str() {
+                "all" => None,
+                "any" => Some(1),
+                count => Some(
+                    count
+                        .parse()
+                        .map_err(|_| anyhow!("invalid quantifier '{token}'"))?,
+                ),
+            };
+            let pattern = self
+                .next()
+                .ok_or_else(|| anyhow!("missing search pattern after '{token} of'"))?;
+            return Ok(Condition::Quantified {
+                count,
+                searches: self.expand(pattern)?,
+            });
+        }
+
+        if self.searches.contains(&token) {
+            Ok(Condition::Search(token.to_owned()))
+        } else {
+            bail!("unknown search '{token}' in condition")
+        }
+    }
+
+    /// returns the names of all searches which match `pattern`. `them`
+    /// matches all searches whose name doesn't start with an underscore
+    fn expand(&self, pattern: &str) -> anyhow::Result<Vec<String>> {
+        let matches = |name: &str| {
+            if pattern == "them" {
+                !name.starts_with('_')
+            } else if let Some(prefix) = pattern.strip_suffix('*') {
+                name.starts_with(prefix)
+            } else {
+                name == pattern
+            }
+        };
+        let searches: Vec<String> = self
+            .searches
+            .iter()
+            .filter(|s| matches(s))
+            .map(|s| s.to_string())
+            .collect();
+        if searches.is_empty() {
+            bail!("no search matches '{pattern}'");
+        }
+        Ok(searches)
+    }
+}
@@ -0,0 +1,515 @@
+use std::{collections::HashMap, net::IpAddr};
+
+use anyhow::{anyhow, bail};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use libevtx::flatten_fields;
+use regex::Regex;
+use serde_json::Value;
+use serde_yaml::Value as YamlValue;
+
+use super::Condition;
+
+/// fields of `System` which can be used in rules, with their path
+const SYSTEM_FIELDS: &[(&str, &[&str])] = &[
+    ("EventID", &["EventID"]),
+    ("Provider_Name", &["Provider", "#attributes", "Name"]),
+    ("Channel", &["Channel"]),
+    ("Computer", &["Computer"]),
+    ("Level", &["Level"]),
+    ("Task", &["Task"]),
+    ("Opcode", &["Opcode"]),
+    ("Keywords", &["Keywords"]),
+    ("EventRecordID", &["EventRecordID"]),
+    ("Version", &["Version"]),
+];
+
+/// rules use the field names of Sysmon, but some events of the Security log
+/// contain the same information in differently named fields
+const FIELD_ALIASES: &[(&str, &str)] = &[
+    ("Image", "NewProcessName"),
+    ("ParentImage", "ParentProcessName"),
+    ("ProcessId", "NewProcessId"),
+];
+
+/// how a string value of a rule is compared to the value of a field
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MatchType {
+    Exact,
+    Contains,
+    StartsWith,
+    EndsWith,
+}
+
+/// the character encoding which is used before a value is base64 encoded
+#[derive(Clone, Copy)]
+enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf16,
+}
+
+#[derive(Debug)]
+enum ValueMatcher {
+    Pattern(Regex),
+
+    /// matches if the field is missing or empty
+    Null,
+    Exists(bool),
+    Compare(fn(f64, f64) -> bool, f64),
+    Cidr(IpAddr, u8),
+}
+
+impl ValueMatcher {
+    fn matches(&self, value: &str) -> bool {
+        match self {
+            ValueMatcher::Pattern(regex) => regex.is_match(value),
+            ValueMatcher::Null | ValueMatcher::Exists(_) => false,
+            ValueMatcher::Compare(compare, expected) => value
+                .trim()
+                .parse::<f64>()
+                .is_ok_and(|value| compare(value, *expected)),
+            ValueMatcher::Cidr(network, prefix) => value
+                .trim()
+                .parse::<IpAddr>()
+                .is_ok_and(|address| in_network(&address, network, *prefix)),
+        }
+    }
+}
+
+/// compares the value of one field to one or more values of a rule
+#[derive(Debug)]
+struct FieldMatcher {
+    field: String,
+
+    /// the alternatives of every value of the rule; a value matches if any
+    /// of its alternatives does (e.g. the variants of `|windash`)
+    matchers: Vec<Vec<ValueMatcher>>,
+
+    /// all values must match, instead of any of them
+    all: bool,
+}
+
+impl FieldMatcher {
+    fn new(key: &str, value: &YamlValue) -> anyhow::Result<Self> {
+        let mut parts = key.split('|');
+        let field = parts.next().unwrap_or_default().to_owned();
+        let modifiers: Vec<&str> = parts.collect();
+
+        let values = match value {
+            YamlValue::Sequence(values) => values.iter().collect(),
+            value => vec![value],
+        };
+        let matchers = values
+            .into_iter()
+            .map(|value| compile_value(value, &modifiers))
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
+            field,
+            matchers,
+            all: modifiers.contains(&"all"),
+        })
+    }
+
+    fn matches(&self, event: &Value) -> bool {
+        let values = field_values(event, &self.field);
+        let is_match = |matcher: &ValueMatcher| match matcher {
+            ValueMatcher::Null => values.iter().all(|v| v.is_empty()),
+            ValueMatcher::Exists(exists) => values.is_empty() != *exists,
+            matcher => values.iter().any(|v| matcher.matches(v)),
+        };
+        let is_match = |alternatives: &Vec<ValueMatcher>| alternatives.iter().any(is_match);
+        if self.all {
+            self.matchers.iter().all(is_match)
+        } else {
+            self.matchers.iter().any(is_match)
+        }
+    }
+}
+
+/// one named search of the detection section
+#[derive(Debug)]
+enum Search {
+    /// all fields must match
+    Fields(Vec<FieldMatcher>),
+
+    /// any of the searches must match
+    AnyOf(Vec<Search>),
+
+    /// any of the keywords must be contained in any field
+    Keywords(Vec<ValueMatcher>),
+}
+
+impl Search {
+    fn new(value: &YamlValue) -> anyhow::Result<Self> {
+        match value {
+            YamlValue::Mapping(fields) => Ok(Self::Fields(
+                fields
+                    .iter()
+                    .map(|(key, value)| {
+                        let key = key
+                            .as_str()
+                            .ok_or_else(|| anyhow!("invalid field name: {key:?}"))?;
+                        FieldMatcher::new(key, value)
+                    })
+                    .collect::<anyhow::Result<_>>()?,
+            )),
+            YamlValue::Sequence(values) if values.iter().all(YamlValue::is_mapping) => Ok(
+                Self::AnyOf(values.iter().map(Self::new).collect::<anyhow::Result<_>>()?),
+            ),
+            YamlValue::Sequence(values) => {
+                let mut matchers = Vec::new();
+                for value in values {
+                    matchers.extend(compile_value(value, &["contains"])?);
+                }
+                Ok(Self::Keywords(matchers))
+            }
+            value => Ok(Self::Keywords(compile_value(value, &["contains"])?)),
+        }
+    }
+
+    fn matches(&self, event: &Value, custom_data: &mut Option<Vec<String>>) -> bool {
+        match self {
+            Search::Fields(fields) => fields.iter().all(|f| f.matches(event)),
+            Search::AnyOf(searches) => searches.iter().any(|s| s.matches(event, custom_data)),
+            Search::Keywords(keywords) => {
+                let values = custom_data.get_or_insert_with(|| custom_data_values(event));
+                keywords
+                    .iter()
+                    .any(|keyword| values.iter().any(|v| keyword.matches(v)))
+            }
+        }
+    }
+}
+
+/// the detection section of a Sigma rule
+#[derive(Debug)]
+pub struct Detection {
+    searches: HashMap<String, Search>,
+    condition: Condition,
+}
+
+impl Detection {
+    pub fn new(detection: &YamlValue) -> anyhow::Result<Self> {
+        let detection = detection
+            .as_mapping()
+            .ok_or_else(|| anyhow!("detection is no mapping"))?;
+
+        let mut searches = HashMap::new();
+        let mut conditions = Vec::new();
+        for (name, value) in detection.iter() {
+            let name = name
+                .as_str()
+                .ok_or_else(|| anyhow!("invalid search name: {name:?}"))?;
+            match name {
+                "condition" => match value {
+                    YamlValue::String(condition) => conditions.push(condition.clone()),
+                    YamlValue::Sequence(values) => {
+                        for value in values {
+                            match value.as_str() {
+                                Some(condition) => conditions.push(condition.to_owned()),
+                                None => bail!("invalid condition: {value:?}"),
+                            }
+                        }
+                    }
+                    value => bail!("invalid condition: {value:?}"),
+                },
+                "timeframe" => bail!("timeframes are not supported"),
+                name => {
+                    searches.insert(name.to_owned(), Search::new(value)?);
+                }
+            }
+        }
+
+        let names: Vec<&str> = searches.keys().map(String::as_str).collect();
+        let mut conditions = conditions
+            .iter()
+            .map(|c| Condition::parse(c, &names))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let condition = match conditions.len() {
+            0 => bail!("missing condition"),
+            1 => conditions.remove(0),
+            _ => Condition::Or(conditions),
+        };
+        Ok(Self {
+            searches,
+            condition,
+        })
+    }
+
+    /// `event` is the `Event` element of a record
+    pub fn matches(&self, event: &Value) -> bool {
+        let mut custom_data = None;
+        let results = self
+            .searches
+            .iter()
+            .map(|(name, search)| (name.as_str(), search.matches(event, &mut custom_data)))
+            .collect();
+        self.condition.evaluate(&results)
+    }
+}
+
+/// returns all values of the field `name`, which is searched in `EventData`,
+/// `UserData` and `System`
+fn field_values(event: &Value, name: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut lookup = |name: &str| {
+        let path: Vec<&str> = name.split('.').collect();
+        if let Some(value) = get_path(&event["EventData"], &path) {
+            push_value(value, &mut values);
+        }
+        if let Some(user_data) = event["UserData"].as_object() {
+            for wrapper in user_data.values() {
+                if let Some(value) = get_path(wrapper, &path) {
+                    push_value(value, &mut values);
+                }
+            }
+        }
+    };
+
+    lookup(name);
+    if values.is_empty() {
+        if let Some((_, path)) = SYSTEM_FIELDS.iter().find(|(field, _)| *field == name) {
+            if let Some(value) = get_path(&event["System"], path) {
+                push_value(value, &mut values);
+            }
+        }
+    }
+    if values.is_empty() {
+        if let Some((_, alias)) = FIELD_ALIASES.iter().find(|(field, _)| *field == name) {
+            let mut aliased = Vec::new();
+            if let Some(value) = get_path(&event["EventData"], &[alias]) {
+                push_value(value, &mut aliased);
+            }
+            values = aliased;
+        }
+    }
+    values
+}
+
+fn get_path<'v>(value: &'v Value, path: &[&str]) -> Option<&'v Value> {
+    let mut value = value;
+    for part in path {
+        value = value.get(part)?;
+    }
+    Some(value)
+}
+
+fn push_value(value: &Value, values: &mut Vec<String>) {
+    match value {
+        Value::Null => (),
+        Value::String(s) => values.push(s.clone()),
+        Value::Array(elements) => elements.iter().for_each(|e| push_value(e, values)),
+        Value::Object(object) => match object.get("#text") {
+            Some(text) => push_value(text, values),
+            None => values.push(value.to_string()),
+        },
+        value => values.push(value.to_string()),
+    }
+}
+
+/// all values of `EventData` and `UserData`, which are searched for keywords
+fn custom_data_values(event: &Value) -> Vec<String> {
+    flatten_fields(&event["EventData"])
+        .into_values()
+        .chain(flatten_fields(&event["UserData"]).into_values())
+        .map(|v| match v {
+            Value::String(s) => s,
+            v => v.to_string(),
+        })
+        .collect()
+}
+
+/// compiles one value of a rule, using the modifiers of its field
+fn compile_value(value: &YamlValue, modifiers: &[&str]) -> anyhow::Result<Vec<ValueMatcher>> {
+    let value = match value {
+        YamlValue::Null => return Ok(vec![ValueMatcher::Null]),
+        YamlValue::String(s) => s.clone(),
+        YamlValue::Number(n) => n.to_string(),
+        YamlValue::Bool(b) => b.to_string(),
+        value => bail!("unsupported value: {value:?}"),
+    };
+
+    let mut match_type = MatchType::Exact;
+    let mut cased = false;
+    let mut encoding = Encoding::Utf8;
+    let mut regex_flags = String::new();
+    let mut is_regex = false;
+
+    // values which have been encoded contain no wildcards
+    let mut literal = false;
+    let mut variants = vec![value.clone()];
+
+    for modifier in modifiers {
+        match *modifier {
+            "contains" => match_type = MatchType::Contains,
+            "startswith" => match_type = MatchType::StartsWith,
+            "endswith" => match_type = MatchType::EndsWith,
+            "all" => (),
+            "cased" => cased = true,
+            "exists" => {
+                return Ok(vec![ValueMatcher::Exists(
+                    value.eq_ignore_ascii_case("true"),
+                )])
+            }
+            "gt" | "gte" | "lt" | "lte" => {
+                let compare: fn(f64, f64) -> bool = match *modifier {
+                    "gt" => |a, b| a > b,
+                    "gte" => |a, b| a >= b,
+                    "lt" => |a, b| a < b,
+                    _ => |a, b| a <= b,
+                };
+                let expected = value
+                    .parse()
+                    .map_err(|_| anyhow!("'{value}' is no number"))?;
+                return Ok(vec![ValueMatcher::Compare(compare, expected)]);
+            }
+            "cidr" => {
+                let (network, prefix) = value
+                    .split_once('/')
+                    .ok_or_else(|| anyhow!("invalid network: '{value}'"))?;
+                return Ok(vec![ValueMatcher::Cidr(network.parse()?, prefix.parse()?)]);
+            }
+            "re" => is_regex = true,
+            "i" | "m" | "s" if is_regex => regex_flags.push_str(modifier),
+            "wide" | "utf16le" => encoding = Encoding::Utf16Le,
+            "utf16be" => encoding = Encoding::Utf16Be,
+            "utf16" => encoding = Encoding::Utf16,
+            "windash" => {
+                variants = variants.iter().flat_map(|v| windash_variants(v)).collect();
+            }
+            "base64" => {
+                variants = variants
+                    .iter()
+                    .map(|v| STANDARD.encode(encode(v, encoding)))
+                    .collect();
+                literal = true;
+            }
+            "base64offset" => {
+                variants = variants
+                    .iter()
+                    .flat_map(|v| base64_offsets(&encode(v, encoding)))
+                    .collect();
+                literal = true;
+            }
+            modifier => bail!("unsupported modifier '{modifier}'"),
+        }
+    }
+
+    if is_regex {
+        let flags = if regex_flags.is_empty() {
+            String::new()
+        } else {
+            format!("(?{regex_flags})")
+        };
+        return Ok(vec![ValueMatcher::Pattern(Regex::new(&format!(
+            "{flags}{value}"
+        ))?)]);
+    }
+
+    variants
+        .iter()
+        .map(|v| {
+            let body = if literal {
+                regex::escape(v)
+            } else {
+                wildcard_to_regex(v)
+            };
+            let body = match match_type {
+                MatchType::Exact => format!("^{body}$"),
+                MatchType::Contains => body,
+                MatchType::StartsWith => format!("^{body}"),
+                MatchType::EndsWith => format!("{body}$"),
+            };
+            let flags = if cased { "(?s)" } else { "(?si)" };
+            Ok(ValueMatcher::Pattern(Regex::new(&format!("{flags}{body}"))?))
+        })
+        .collect()
+}
+
+/// converts a value with Sigma wildcards (`*` and `?`, which can be escaped
+/// using `\`) into a regular expression
+fn wildcard_to_regex(value: &str) -> String {
+    let mut regex = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '\\' => match chars.peek() {
+                Some(next @ ('*' | '?' | '\\')) => {
+                    regex.push_str(&regex::escape(&next.to_string()));
+                    chars.next();
+                }
+                _ => regex.push_str(r"\\"),
+            },
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex
+}
+
+fn encode(value: &str, encoding: Encoding) -> Vec<u8> {
+    match encoding {
+        Encoding::Utf8 => value.as_bytes().to_vec(),
+        Encoding::Utf16Le => value.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+        Encoding::Utf16Be => value.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+        Encoding::Utf16 => [0xFF, 0xFE]
+            .into_iter()
+            .chain(value.encode_utf16().flat_map(u16::to_le_bytes))
+            .collect(),
+    }
+}
+
+/// the value can be found at any position of base64 encoded data, which
+/// results in three different encodings, depending on the offset of the
+/// value. Characters which depend on the surrounding data are removed
+fn base64_offsets(value: &[u8]) -> Vec<String> {
+    const START: [usize; 3] = [0, 2, 3];
+    const END: [usize; 3] = [0, 3, 2];
+    (0..3)
+        .map(|offset| {
+            let mut data = vec![b' '; offset];
+            data.extend_from_slice(value);
+            let encoded = STANDARD.encode(&data);
+            let end = encoded.len() - END[(value.len() + offset) % 3];
+            encoded[START[offset]..end].to_owned()
+        })
+        .collect()
+}
+
+/// command line options can be prefixed with `-`, `/` or dashes which look
+/// similar to `-`
+fn windash_variants(value: &str) -> Vec<String> {
+    ['-', '/', '\u{2013}', '\u{2014}', '\u{2015}']
+        .iter()
+        .map(|dash| {
+            let mut result = String::new();
+            let mut previous = None;
+            for c in value.chars() {
+                if c == '-' && previous.is_none_or(char::is_whitespace) {
+                    result.push(*dash);
+                } else {
+                    result.push(c);
+                }
+                previous = Some(c);
+            }
+            result
+        })
+        .collect()
+}
+
+fn in_network(address: &IpAddr, network: &IpAddr, prefix: u8) -> bool {
+    match (address, network) {
+        (IpAddr::V4(address), IpAddr::V4(network)) => {
+            let mask = u32::MAX.checked_shl(32 - u32::from(prefix.min(32))).unwrap_or(0);
+            u32::from(*address) & mask == u32::from(*network) & mask
+        }
+        (IpAddr::V6(address), IpAddr::V6(network)) => {
+            let mask = u128::MAX.checked_shl(128 - u32::from(prefix.min(128))).unwrap_or(0);
+            u128::from(*address) & mask == u128::from(*network) & mask
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,144 @@
+use anyhow::bail;
+use eventdata::{Channel, EventId};
+use evtx::SerializedEvtxRecord;
+use serde_json::Value;
+use serde_yaml::Value as YamlValue;
+
+const SYSMON: &str = "Microsoft-Windows-Sysmon/Operational";
+const POWERSHELL: &str = "Microsoft-Windows-PowerShell/Operational";
+const POWERSHELL_CLASSIC: &str = "Windows PowerShell";
+
+/// the channels of the Windows services used by Sigma rules
+const SERVICES: &[(&str, &[&str])] = &[
+    ("security", &["Security"]),
+    ("system", &["System"]),
+    ("application", &["Application"]),
+    ("sysmon", &[SYSMON]),
+    ("powershell", &[POWERSHELL]),
+    ("powershell-classic", &[POWERSHELL_CLASSIC]),
+    ("taskscheduler", &["Microsoft-Windows-TaskScheduler/Operational"]),
+    ("wmi", &["Microsoft-Windows-WMI-Activity/Operational"]),
+    ("windefend", &["Microsoft-Windows-Windows Defender/Operational"]),
+    ("bits-client", &["Microsoft-Windows-Bits-Client/Operational"]),
+    ("dns-server", &["DNS Server"]),
+    ("driver-framework", &["Microsoft-Windows-DriverFrameworks-UserMode/Operational"]),
+    ("firewall-as", &["Microsoft-Windows-Windows Firewall With Advanced Security/Firewall"]),
+    ("ntlm", &["Microsoft-Windows-NTLM/Operational"]),
+    ("codeintegrity-operational", &["Microsoft-Windows-CodeIntegrity/Operational"]),
+    ("printservice-operational", &["Microsoft-Windows-PrintService/Operational"]),
+    ("printservice-admin", &["Microsoft-Windows-PrintService/Admin"]),
+    ("smbclient-security", &["Microsoft-Windows-SmbClient/Security"]),
+    ("shell-core", &["Microsoft-Windows-Shell-Core/Operational"]),
+    ("openssh", &["OpenSSH/Operational"]),
+    ("msexchange-management", &["MSExchange Management"]),
+    (
+        "terminalservices-localsessionmanager",
+        &["Microsoft-Windows-TerminalServices-LocalSessionManager/Operational"],
+    ),
+    (
+        "applocker",
+        &[
+            "Microsoft-Windows-AppLocker/EXE and DLL",
+            "Microsoft-Windows-AppLocker/MSI and Script",
+            "Microsoft-Windows-AppLocker/Packaged app-Deployment",
+            "Microsoft-Windows-AppLocker/Packaged app-Execution",
+        ],
+    ),
+];
+
+/// a channel and the ids of the events in it. No event ids means all events
+type Source = (&'static str, &'static [u16]);
+
+/// the channels and event ids of the categories used by Sigma rules
+const CATEGORIES: &[(&str, &[Source])] = &[
+    ("process_creation", &[(SYSMON, &[1]), ("Security", &[4688])]),
+    ("process_termination", &[(SYSMON, &[5]), ("Security", &[4689])]),
+    ("network_connection", &[(SYSMON, &[3])]),
+    ("driver_load", &[(SYSMON, &[6])]),
+    ("image_load", &[(SYSMON, &[7])]),
+    ("create_remote_thread", &[(SYSMON, &[8])]),
+    ("raw_access_thread", &[(SYSMON, &[9])]),
+    ("process_access", &[(SYSMON, &[10])]),
+    ("file_event", &[(SYSMON, &[11])]),
+    ("registry_event", &[(SYSMON, &[12, 13, 14])]),
+    ("registry_add", &[(SYSMON, &[12])]),
+    ("registry_delete", &[(SYSMON, &[12])]),
+    ("registry_set", &[(SYSMON, &[13])]),
+    ("registry_rename", &[(SYSMON, &[14])]),
+    ("create_stream_hash", &[(SYSMON, &[15])]),
+    ("pipe_created", &[(SYSMON, &[17, 18])]),
+    ("wmi_event", &[(SYSMON, &[19, 20, 21])]),
+    ("dns_query", &[(SYSMON, &[22])]),
+    ("file_delete", &[(SYSMON, &[23, 26])]),
+    ("clipboard_capture", &[(SYSMON, &[24])]),
+    ("process_tampering", &[(SYSMON, &[25])]),
+    ("file_executable_detected", &[(SYSMON, &[29])]),
+    ("ps_module", &[(POWERSHELL, &[4103])]),
+    ("ps_script", &[(POWERSHELL, &[4104])]),
+    ("ps_classic_start", &[(POWERSHELL_CLASSIC, &[400])]),
+    ("ps_classic_provider_start", &[(POWERSHELL_CLASSIC, &[600])]),
+];
+
+/// the records a rule applies to, given by their channel and event id. A
+/// record is selected if any of the sources matches
+#[derive(Debug)]
+pub struct LogSource {
+    sources: Vec<Source>,
+}
+
+impl LogSource {
+    /// only Windows log sources are supported. Rules with other log sources
+    /// result in an error
+    pub fn new(logsource: &YamlValue) -> anyhow::Result<Self> {
+        let field = |name: &str| logsource.get(name).and_then(YamlValue::as_str);
+
+        if let Some(product) = field("product") {
+            if product != "windows" {
+                bail!("unsupported product '{product}'");
+            }
+        }
+
+        let service_channels = match field("service") {
+            None => None,
+            Some(service) => match SERVICES.iter().find(|(s, _)| *s == service) {
+                Some((_, channels)) => Some(*channels),
+                None => bail!("unsupported service '{service}'"),
+            },
+        };
+
+        let sources: Vec<Source> = match field("category") {
+            None => match service_channels {
+                Some(channels) => channels.iter().map(|c| (*c, &[][..])).collect(),
+                None => bail!("missing service or category"),
+            },
+            Some(category) => match CATEGORIES.iter().find(|(c, _)| *c == category) {
+                Some((_, sources)) => sources
+                    .iter()
+                    .filter(|(channel, _)| {
+                        service_channels.is_none_or(|channels| channels.contains(channel))
+                    })
+                    .copied()
+                    .collect(),
+                None => bail!("unsupported category '{category}'"),
+            },
+        };
+
+        if sources.is_empty() {
+            bail!("the service does not provide records of the category");
+        }
+        Ok(Self { sources })
+    }
+
+    pub fn matches(&self, record: &SerializedEvtxRecord<Value>) -> bool {
+        let channel = match Channel::try_from(record) {
+            Ok(channel) => channel.to_string(),
+            Err(_) => return false,
+        };
+        let event_id = EventId::try_from(record).ok().map(u16::from);
+
+        self.sources.iter().any(|(c, event_ids)| {
+            c.eq_ignore_ascii_case(&channel)
+                && (event_ids.is_empty() || event_id.is_some_and(|id| event_ids.contains(&id)))
+        })
+    }
+}
@@ -0,0 +1,10 @@
+mod condition;
+mod detection;
+mod log_source;
+mod rule;
+mod sigma_match;
+pub use condition::*;
+pub use detection::*;
+pub use log_source::*;
+pub use rule::*;
+pub use sigma_match::*;
@@ -0,0 +1,164 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use evtx::SerializedEvtxRecord;
+use serde::Deserialize;
+use serde_json::Value;
+use serde_yaml::Value as YamlValue;
+use walkdir::WalkDir;
+
+use super::{Detection, LogSource};
+
+/// a Sigma rule, which has been compiled to be matched against evtx records
+#[derive(Debug)]
+pub struct SigmaRule {
+    title: String,
+    id: Option<String>,
+    level: Option<String>,
+    tags: Vec<String>,
+    path: PathBuf,
+    logsource: LogSource,
+    detection: Detection,
+}
+
+impl SigmaRule {
+    fn new(rule: &YamlValue, path: &Path) -> anyhow::Result<Self> {
+        let string = |name: &str| rule.get(name).and_then(YamlValue::as_str).map(str::to_owned);
+
+        let title = string("title").ok_or_else(|| anyhow!("missing title"))?;
+        let logsource = LogSource::new(
+            rule.get("logsource")
+                .ok_or_else(|| anyhow!("missing logsource"))?,
+        )?;
+        let detection = Detection::new(
+            rule.get("detection")
+                .ok_or_else(|| anyhow!("missing detection"))?,
+        )?;
+        let tags = rule
+            .get("tags")
+            .and_then(YamlValue::as_sequence)
+            .map(|tags| {
+                tags.iter()
+                    .filter_map(YamlValue::as_str)
+                    .map(str::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            title,
+            id: string("id"),
+            level: string("level"),
+            tags,
+            path: path.to_owned(),
+            logsource,
+            detection,
+        })
+    }
+
+    /// loads all rules from the `*.yml` and `*.yaml` files in `path`, which
+    /// is searched recursively. Rules which cannot be used are skipped with
+    /// a warning
+    pub fn load_all(path: &Path) -> anyhow::Result<Vec<Self>> {
+        let mut rules = Vec::new();
+        for entry in WalkDir::new(path).sort_by_file_name() {
+            let entry = entry?;
+            let is_rule = entry.file_type().is_file()
+                && entry
+                    .path()
+                    .extension()
+                    .is_some_and(|e| e.eq_ignore_ascii_case("yml") || e.eq_ignore_ascii_case("yaml"));
+            if !is_rule {
+                continue;
+            }
+
+            let content = std::fs::read_to_string(entry.path())?;
+
+            // rule collections may contain a document with `action: global`,
+            // whose values apply to all following documents, and documents
+            // with `action: repeat`, which extend the previous document
+            let mut global = YamlValue::Null;
+            let mut previous = YamlValue::Null;
+            for document in serde_yaml::Deserializer::from_str(&content) {
+                let mut document = match YamlValue::deserialize(document) {
+                    Ok(document) => document,
+                    Err(why) => {
+                        log::warn!("unable to parse {}: {why}", entry.path().display());
+                        break;
+                    }
+                };
+
+                let action = document
+                    .as_mapping_mut()
+                    .and_then(|document| document.remove("action"));
+                let rule = match action.as_ref().and_then(YamlValue::as_str) {
+                    Some("global") => {
+                        global = merge(global, document);
+                        continue;
+                    }
+                    Some("reset") => {
+                        global = YamlValue::Null;
+                        continue;
+                    }
+                    Some("repeat") => merge(previous.clone(), document),
+                    _ => merge(global.clone(), document),
+                };
+                previous = rule.clone();
+
+                if rule.get("detection").is_none() {
+                    log::info!("skipping document without detection in {}", entry.path().display());
+                    continue;
+                }
+
+                match Self::new(&rule, entry.path()) {
+                    Ok(rule) => rules.push(rule),
+                    Err(why) => log::warn!("skipping rule {}: {why}", entry.path().display()),
+                }
+            }
+        }
+        Ok(rules)
+    }
+
+    pub fn matches(&self, record: &SerializedEvtxRecord<Value>) -> bool {
+        self.logsource.matches(record) && self.detection.matches(&record.data["Event"])
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    pub fn level(&self) -> Option<&str> {
+        self.level.as_deref()
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// merges `overlay` into `base`: mappings are merged recursively, all other
+/// values of `overlay` replace those of `base`
+fn merge(base: YamlValue, overlay: YamlValue) -> YamlValue {
+    match (base, overlay) {
+        (YamlValue::Mapping(mut base), YamlValue::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                let value = match base.remove(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => value,
+                };
+                base.insert(key, value);
+            }
+            YamlValue::Mapping(base)
+        }
+        (base, YamlValue::Null) => base,
+        (_, overlay) => overlay,
+    }
+}
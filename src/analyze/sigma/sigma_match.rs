@@ -0,0 +1,58 @@
+use eventdata::resolve_parameter_messages_in;
+use evtx::SerializedEvtxRecord;
+use libevtx::EvtxSource;
+use serde_json::Value;
+
+use super::SigmaRule;
+
+/// a record which is matched by at least one rule
+pub struct SigmaMatch {
+    /// the indices of the matching rules
+    pub rules: Vec<usize>,
+
+    /// the index of the evtx file which contains the record
+    pub source: usize,
+
+    pub record: SerializedEvtxRecord<Value>,
+}
+
+/// matches all records of `sources` against `rules`. The matches are sorted
+/// by their timestamp
+pub fn find_sigma_matches(
+    rules: &[SigmaRule],
+    sources: &[EvtxSource],
+    resolve_parameters: bool,
+) -> anyhow::Result<Vec<SigmaMatch>> {
+    let mut matches = Vec::new();
+    for (source_index, source) in sources.iter().enumerate() {
+        log::info!("scanning {source}");
+        for record in source.open()?.records_json_value() {
+            let mut record = match record {
+                Ok(record) => record,
+                Err(why) => {
+                    log::warn!("error while reading {source}: {why}");
+                    continue;
+                }
+            };
+            if resolve_parameters {
+                resolve_parameter_messages_in(&mut record.data);
+            }
+
+            let matching_rules: Vec<usize> = rules
+                .iter()
+                .enumerate()
+                .filter(|(_, rule)| rule.matches(&record))
+                .map(|(index, _)| index)
+                .collect();
+            if !matching_rules.is_empty() {
+                matches.push(SigmaMatch {
+                    rules: matching_rules,
+                    source: source_index,
+                    record,
+                });
+            }
+        }
+    }
+    matches.sort_by_key(|m| m.record.timestamp);
+    Ok(matches)
+}
@@ -0,0 +1,363 @@
+use std::{collections::BTreeMap, fmt::Display, thread, time::Duration};
+
+use chrono::SecondsFormat;
+use eventdata::{Channel, Computer};
+use evtx::SerializedEvtxRecord;
+use serde_json::{json, Value};
+
+use crate::{ecs_document, EvtxSource};
+
+/// the default name of the index, see [`BulkIngester::index_name`]
+pub const DEFAULT_INDEX_PATTERN: &str = "evtx-{host}-{channel}";
+
+/// the delay before the first retry, which is doubled with every attempt
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// characters which are not allowed in the names of indices
+const INVALID_INDEX_CHARACTERS: &[char] =
+    &['\\', '/', '*', '?', '"', '<', '>', '|', ' ', ',', '#', ':'];
+
+/// the number of documents which have been sent to the cluster
+#[derive(Default, Debug, Clone)]
+pub struct IngestSummary {
+    pub indexed: usize,
+    pub failed: usize,
+
+    /// the number of failed documents by reason of failure
+    pub errors: BTreeMap<String, usize>,
+}
+
+impl IngestSummary {
+    fn add_failure(&mut self, reason: String, count: usize) {
+        self.failed += count;
+        *self.errors.entry(reason).or_default() += count;
+    }
+}
+
+impl Display for IngestSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} documents have been indexed, {} documents failed",
+            self.indexed, self.failed
+        )?;
+        for (reason, count) in self.errors.iter() {
+            write!(f, "\n  {count} x {reason}")?;
+        }
+        Ok(())
+    }
+}
+
+/// sends records to the `_bulk` API of Elasticsearch or OpenSearch. Records
+/// are converted to ECS documents (see [`ecs_document`]) and sent in
+/// batches of `batch_size` documents.
+///
+/// Every document gets an id which is derived from its computer, channel,
+/// event record id and timestamp, so that ingesting the same file twice does
+/// not result in duplicates, and retried requests are harmless
+pub struct BulkIngester {
+    agent: ureq::Agent,
+    bulk_url: String,
+    index_pattern: String,
+    batch_size: usize,
+    max_retries: u32,
+
+    /// documents which have not been sent yet, together with their index
+    /// and id
+    pending: Vec<(String, String, Value)>,
+    summary: IngestSummary,
+}
+
+impl BulkIngester {
+    /// `url` is the base URL of the cluster, such as `http://localhost:9200`
+    pub fn new(url: &str, index_pattern: &str) -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new()
+                .timeout_connect(Duration::from_secs(10))
+                .timeout(Duration::from_secs(120))
+                .build(),
+            bulk_url: format!("{}/_bulk", url.trim_end_matches('/')),
+            index_pattern: index_pattern.to_owned(),
+            batch_size: 500,
+            max_retries: 5,
+            pending: Vec::new(),
+            summary: IngestSummary::default(),
+        }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn summary(&self) -> &IngestSummary {
+        &self.summary
+    }
+
+    /// returns the name of the index for a record. The placeholders `{host}`
+    /// and `{channel}` of the index pattern are replaced by the computer
+    /// name and the channel of the record. Index names are lowercase and
+    /// must not contain some special characters, which are replaced by `-`
+    pub fn index_name(&self, record: &SerializedEvtxRecord<Value>) -> String {
+        let host = Computer::try_from(record)
+            .map(|c| c.to_string())
+            .unwrap_or_else(|_| "unknown".to_owned());
+        let channel = Channel::try_from(record)
+            .map(|c| c.to_string())
+            .unwrap_or_else(|_| "unknown".to_owned());
+        self.index_pattern
+            .replace("{host}", &host)
+            .replace("{channel}", &channel)
+            .to_lowercase()
+            .replace(INVALID_INDEX_CHARACTERS, "-")
+            .trim_start_matches(['-', '_', '+'])
+            .to_owned()
+    }
+
+    /// adds a record to the current batch. The batch is sent if it is full
+    pub fn add(
+        &mut self,
+        source: &EvtxSource,
+        record: &SerializedEvtxRecord<Value>,
+        message: Option<String>,
+    ) -> anyhow::Result<()> {
+        let mut document = ecs_document(record, Some(source));
+        if let Some(message) = message {
+            document["message"] = message.into();
+        }
+        // event record ids start again at 1 if a log has been cleared, so
+        // they are only unique together with the timestamp
+        let id = format!(
+            "{}-{}-{}-{}",
+            Computer::try_from(record)
+                .map(|c| c.to_string())
+                .unwrap_or_default(),
+            Channel::try_from(record)
+                .map(|c| c.to_string())
+                .unwrap_or_default(),
+            record.event_record_id,
+            record
+                .timestamp
+                .to_rfc3339_opts(SecondsFormat::Micros, true)
+        );
+        self.pending.push((self.index_name(record), id, document));
+
+        if self.pending.len() >= self.batch_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// sends all pending documents. Documents which cannot be indexed are
+    /// counted in the summary; an error is only returned if the response of
+    /// the cluster cannot be understood
+    pub fn flush(&mut self) -> anyhow::Result<()> {
+        let mut documents = std::mem::take(&mut self.pending);
+        let mut attempt = 0;
+
+        while !documents.is_empty() {
+            let mut body = String::new();
+            for (index, id, document) in documents.iter() {
+                body.push_str(&json!({"index": {"_index": index, "_id": id}}).to_string());
+                body.push('\n');
+                body.push_str(&document.to_string());
+                body.push('\n');
+            }
+
+            let can_retry = attempt < self.max_retries;
+            let response = self
+                .agent
+                .post(&self.bulk_url)
+                .set("Content-Type", "application/x-ndjson")
+                .send_string(&body);
+
+            let response = match response {
+                Ok(response) => response,
+                Err(ureq::Error::Status(status, _)) if can_retry && is_transient(status) => {
+                    attempt = self.backoff(attempt);
+                    continue;
+                }
+                Err(ureq::Error::Transport(_)) if can_retry => {
+                    attempt = self.backoff(attempt);
+                    continue;
+                }
+                Err(ureq::Error::Status(status, response)) => {
+                    let reason = response.status_text().to_owned();
+                    self.summary
+                        .add_failure(format!("HTTP {status} {reason}"), documents.len());
+                    return Ok(());
+                }
+                Err(why) => {
+                    self.summary.add_failure(why.to_string(), documents.len());
+                    return Ok(());
+                }
+            };
+
+            let result: Value = serde_json::from_reader(response.into_reader())?;
+            let items = result["items"].as_array().cloned().unwrap_or_default();
+            if items.len() != documents.len() {
+                anyhow::bail!(
+                    "the cluster returned {} results for {} documents",
+                    items.len(),
+                    documents.len()
+                );
+            }
+
+            // documents which have been rejected because the cluster is
+            // overloaded are sent again
+            let mut rejected = Vec::new();
+            for (document, item) in documents.into_iter().zip(items) {
+                let item = &item["index"];
+                let status = item["status"].as_u64().unwrap_or_default() as u16;
+                if (200..300).contains(&status) {
+                    self.summary.indexed += 1;
+                } else if can_retry && is_transient(status) {
+                    rejected.push(document);
+                } else {
+                    let error = &item["error"];
+                    let reason = match (error["type"].as_str(), error["reason"].as_str()) {
+                        (Some(t), Some(reason)) => format!("{t}: {reason}"),
+                        (Some(t), None) => t.to_owned(),
+                        _ => format!("HTTP {status}"),
+                    };
+                    self.summary.add_failure(reason, 1);
+                }
+            }
+
+            documents = rejected;
+            if !documents.is_empty() {
+                attempt = self.backoff(attempt);
+            }
+        }
+        Ok(())
+    }
+
+    /// waits before the next attempt and returns the number of this attempt
+    fn backoff(&self, attempt: u32) -> u32 {
+        let delay = INITIAL_BACKOFF
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(MAX_BACKOFF);
+        thread::sleep(delay);
+        attempt + 1
+    }
+}
+
+/// status codes which indicate that the cluster is temporarily unable to
+/// process the request
+fn is_transient(status: u16) -> bool {
+    matches!(status, 429 | 502 | 503 | 504)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        path::PathBuf,
+        sync::mpsc,
+    };
+
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    fn record(event_record_id: u64) -> SerializedEvtxRecord<Value> {
+        SerializedEvtxRecord {
+            event_record_id,
+            timestamp: Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap(),
+            data: json!({"Event": {"System": {
+                "Computer": "PC1",
+                "Channel": "Security",
+                "EventID": 4624,
+            }}}),
+        }
+    }
+
+    /// reads one HTTP request and returns its body
+    fn read_request(reader: &mut impl BufRead) -> String {
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        String::from_utf8(body).unwrap()
+    }
+
+    #[test]
+    fn retries_and_counts_failed_documents() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        // the stand-in rejects the first request as a whole, and the second
+        // one partially
+        let (bodies, received) = mpsc::channel();
+        let server = thread::spawn(move || {
+            let responses = [
+                ("429 Too Many Requests", "{}".to_owned()),
+                (
+                    "200 OK",
+                    json!({"errors": true, "items": [
+                        {"index": {"status": 201}},
+                        {"index": {"status": 400, "error": {
+                            "type": "mapper_parsing_exception",
+                            "reason": "failed to parse"
+                        }}},
+                    ]})
+                    .to_string(),
+                ),
+            ];
+            for (status, response) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                bodies
+                    .send(read_request(&mut BufReader::new(&mut stream)))
+                    .unwrap();
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                    response.len()
+                )
+                .unwrap();
+            }
+        });
+
+        let source = EvtxSource::File(PathBuf::from("Security.evtx"));
+        let mut ingester = BulkIngester::new(&url, DEFAULT_INDEX_PATTERN).with_batch_size(10);
+        ingester.add(&source, &record(1), None).unwrap();
+        ingester.add(&source, &record(2), None).unwrap();
+        ingester.flush().unwrap();
+        server.join().unwrap();
+
+        let bodies: Vec<_> = received.try_iter().collect();
+        assert_eq!(bodies.len(), 2);
+        assert_eq!(bodies[0], bodies[1]);
+        assert!(bodies[0].contains(r#""_id":"PC1-Security-1-2024-01-02T03:04:05.000000Z""#));
+        assert!(bodies[0].contains(r#""_index":"evtx-pc1-security""#));
+
+        let summary = ingester.summary();
+        assert_eq!(summary.indexed, 1);
+        assert_eq!(summary.failed, 1);
+        assert_eq!(
+            summary
+                .errors
+                .get("mapper_parsing_exception: failed to parse"),
+            Some(&1)
+        );
+    }
+}
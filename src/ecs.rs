@@ -0,0 +1,212 @@
+use chrono::SecondsFormat;
+use eventdata::{
+    Channel, Computer, EventId, Keywords, Level, Opcode, ProcessId, ProviderName, Task, ThreadId,
+    UserId, Version,
+};
+use evtx::SerializedEvtxRecord;
+use serde_json::{Map, Value};
+
+use crate::{flatten_fields, EvtxSource};
+
+/// the version of the Elastic Common Schema which is used by
+/// [`ecs_document`]
+pub const ECS_VERSION: &str = "8.11.0";
+
+/// converts a record into a document which follows the Elastic Common
+/// Schema, using the same field names as Winlogbeat. The mapping is
+/// documented in `README.md` and is considered to be stable: fields may be
+/// added, but existing fields will not be renamed or removed.
+pub fn ecs_document(record: &SerializedEvtxRecord<Value>, source: Option<&EvtxSource>) -> Value {
+    let mut document = EcsDocument::default();
+    let event = &record.data["Event"];
+    let event_data = &event["EventData"];
+
+    document.set(
+        "@timestamp",
+        record.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+    );
+    document.set("ecs.version", ECS_VERSION);
+    document.set("event.kind", "event");
+
+    let event_id = EventId::try_from(record).ok().map(|id| id.value());
+    document.set("event.code", event_id.map(|id| id.to_string()));
+    document.set("winlog.event_id", event_id.map(|id| id.to_string()));
+
+    let provider = ProviderName::try_from(record).ok().map(|p| p.to_string());
+    document.set("event.provider", provider.clone());
+    document.set("winlog.provider_name", provider);
+    document.set(
+        "winlog.provider_guid",
+        event["System"]["Provider"]["#attributes"]["Guid"].clone(),
+    );
+
+    if let Ok(keywords) = Keywords::try_from(record) {
+        if keywords.is_audit_success() {
+            document.set("event.outcome", "success");
+        } else if keywords.is_audit_failure() {
+            document.set("event.outcome", "failure");
+        }
+    }
+
+    document.set("log.level", Level::try_from(record).ok().map(|l| level_name(l.value())));
+    if let Some(source) = source {
+        document.set("log.file.path", source.to_string());
+    }
+
+    let computer = Computer::try_from(record).ok().map(|c| c.to_string());
+    document.set("host.name", computer.clone());
+    document.set("winlog.computer_name", computer);
+    document.set("winlog.channel", Channel::try_from(record).ok().map(|c| c.to_string()));
+    document.set("winlog.record_id", record.event_record_id.to_string());
+    document.set("winlog.process.pid", ProcessId::try_from(record).ok().map(|p| p.0));
+    document.set("winlog.process.thread.id", ThreadId::try_from(record).ok().map(|t| t.0));
+    document.set("winlog.version", Version::try_from(record).ok().map(u8::from));
+    document.set("winlog.opcode", Opcode::try_from(record).ok().map(|o| o.0));
+    document.set("winlog.task", Task::try_from(record).ok().map(|t| t.0));
+    document.set("winlog.keywords", Keywords::try_from(record).ok().map(|k| k.to_string()));
+    document.set("winlog.user.identifier", UserId::try_from(record).ok().map(|u| u.to_string()));
+    document.set(
+        "winlog.activity_id",
+        event["System"]["Correlation"]["#attributes"]["ActivityID"].clone(),
+    );
+    document.set(
+        "winlog.related_activity_id",
+        event["System"]["Correlation"]["#attributes"]["RelatedActivityID"].clone(),
+    );
+
+    for (name, value) in flatten_fields(event_data) {
+        document.set_child("winlog.event_data", name, value);
+    }
+
+    // UserData contains one single element, which wraps the fields
+    if let Some(user_data) = event["UserData"].as_object() {
+        for wrapper in user_data.values() {
+            for (name, value) in flatten_fields(wrapper) {
+                document.set_child("winlog.user_data", name, value);
+            }
+        }
+    }
+
+    let field = |name: &str| {
+        event_data
+            .get(name)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|v| !v.is_empty() && *v != "-")
+    };
+
+    // the target of an action is more interesting than its subject
+    if field("TargetUserName").is_some() {
+        document.set("user.name", field("TargetUserName"));
+        document.set("user.domain", field("TargetDomainName"));
+        document.set("user.id", field("TargetUserSid").or(field("TargetSid")));
+    } else {
+        document.set("user.name", field("SubjectUserName"));
+        document.set("user.domain", field("SubjectDomainName"));
+        document.set("user.id", field("SubjectUserSid"));
+    }
+
+    document.set("source.ip", field("IpAddress").or(field("SourceIp")));
+    document.set(
+        "source.port",
+        field("IpPort")
+            .or(field("SourcePort"))
+            .and_then(|p| p.parse::<u16>().ok())
+            .filter(|p| *p != 0),
+    );
+    document.set("source.domain", field("WorkstationName").or(field("Workstation")));
+    document.set("destination.ip", field("DestinationIp"));
+    document.set(
+        "destination.port",
+        field("DestinationPort").and_then(|p| p.parse::<u16>().ok()),
+    );
+
+    document.set(
+        "process.executable",
+        field("NewProcessName").or(field("Image")),
+    );
+    document.set("process.command_line", field("CommandLine"));
+    document.set(
+        "process.parent.executable",
+        field("ParentProcessName").or(field("ParentImage")),
+    );
+
+    document.into()
+}
+
+fn level_name(level: u8) -> &'static str {
+    match level {
+        1 => "critical",
+        2 => "error",
+        3 => "warning",
+        5 => "verbose",
+        _ => "information",
+    }
+}
+
+/// a JSON object, whose fields are set using dotted paths
+#[derive(Default)]
+struct EcsDocument(Map<String, Value>);
+
+impl EcsDocument {
+    /// sets the field at `path`, unless `value` is `null`
+    fn set<V: Into<Value>>(&mut self, path: &str, value: V) {
+        let value = value.into();
+        if value.is_null() {
+            return;
+        }
+
+        let mut object = &mut self.0;
+        let mut parts = path.split('.').peekable();
+        while let Some(part) = parts.next() {
+            if parts.peek().is_none() {
+                object.insert(part.to_owned(), value);
+                return;
+            }
+            let child = object
+                .entry(part.to_owned())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if !child.is_object() {
+                *child = Value::Object(Map::new());
+            }
+            object = child.as_object_mut().expect("child is no object");
+        }
+    }
+
+    /// sets a field of an object, whose name might contain dots itself
+    fn set_child(&mut self, path: &str, name: String, value: Value) {
+        if value.is_null() {
+            return;
+        }
+        if self.get(path).is_none() {
+            self.set(path, Map::new());
+        }
+        if let Some(Value::Object(object)) = self.get_mut(path) {
+            object.insert(name, value);
+        }
+    }
+
+    fn get(&self, path: &str) -> Option<&Value> {
+        let mut parts = path.split('.');
+        let mut value = self.0.get(parts.next()?)?;
+        for part in parts {
+            value = value.get(part)?;
+        }
+        Some(value)
+    }
+
+    fn get_mut(&mut self, path: &str) -> Option<&mut Value> {
+        let mut parts = path.split('.');
+        let mut value = self.0.get_mut(parts.next()?)?;
+        for part in parts {
+            value = value.get_mut(part)?;
+        }
+        Some(value)
+    }
+}
+
+impl From<EcsDocument> for Value {
+    fn from(document: EcsDocument) -> Self {
+        Value::Object(document.0)
+    }
+}
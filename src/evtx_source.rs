@@ -0,0 +1,111 @@
+use std::{
+    borrow::Cow,
+    fmt::Display,
+    fs::File,
+    io::{Cursor, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use evtx::EvtxParser;
+
+/// an evtx file, which is either stored in the file system or is a member of
+/// a ZIP archive
+#[derive(Clone, Debug)]
+pub enum EvtxSource {
+    File(PathBuf),
+    ZipMember { archive: PathBuf, member: String },
+}
+
+/// reader which is used to parse an [`EvtxSource`]. Members of ZIP archives
+/// are decompressed into memory, because the parser needs to seek
+pub enum EvtxReader {
+    File(File),
+    Memory(Cursor<Vec<u8>>),
+}
+
+impl EvtxSource {
+    /// the name of the evtx file, without any directory or archive name
+    pub fn file_name(&self) -> Cow<'_, str> {
+        match self {
+            Self::File(path) => path
+                .file_name()
+                .map(|n| n.to_string_lossy())
+                .unwrap_or_default(),
+            Self::ZipMember { member, .. } => Cow::Borrowed(member_file_name(member)),
+        }
+    }
+
+    /// the file which contains this source, i.e. the evtx file itself or the
+    /// ZIP archive which contains it
+    pub fn container(&self) -> &Path {
+        match self {
+            Self::File(path) => path,
+            Self::ZipMember { archive, .. } => archive,
+        }
+    }
+
+    pub fn is_archive_member(&self) -> bool {
+        matches!(self, Self::ZipMember { .. })
+    }
+
+    /// tells if records read from these sources must be tagged with their
+    /// source, because it would be ambiguous otherwise
+    pub fn need_tagging(sources: &[Self]) -> bool {
+        sources.len() > 1 || sources.iter().any(Self::is_archive_member)
+    }
+
+    pub fn open(&self) -> anyhow::Result<EvtxParser<EvtxReader>> {
+        let reader = match self {
+            Self::File(path) => EvtxReader::File(
+                File::open(path).with_context(|| format!("unable to open {}", path.display()))?,
+            ),
+            Self::ZipMember { archive, member } => {
+                let file = File::open(archive)
+                    .with_context(|| format!("unable to open {}", archive.display()))?;
+                let mut zip = zip::ZipArchive::new(file)
+                    .with_context(|| format!("invalid ZIP archive: {}", archive.display()))?;
+                let mut entry = zip.by_name(member)?;
+                let mut data = Vec::with_capacity(entry.size().try_into().unwrap_or_default());
+                entry
+                    .read_to_end(&mut data)
+                    .with_context(|| format!("unable to read {self}"))?;
+                EvtxReader::Memory(Cursor::new(data))
+            }
+        };
+        EvtxParser::from_read_seek(reader).with_context(|| format!("unable to parse {self}"))
+    }
+}
+
+impl Display for EvtxSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::ZipMember { archive, member } => write!(f, "{}/{member}", archive.display()),
+        }
+    }
+}
+
+/// member names in ZIP archives use '/' as separator, but some tools on
+/// Windows use '\' instead
+pub(crate) fn member_file_name(member: &str) -> &str {
+    member.rsplit(['/', '\\']).next().unwrap_or(member)
+}
+
+impl Read for EvtxReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Self::File(f) => f.read(buf),
+            Self::Memory(c) => c.read(buf),
+        }
+    }
+}
+
+impl Seek for EvtxReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            Self::File(f) => f.seek(pos),
+            Self::Memory(c) => c.seek(pos),
+        }
+    }
+}
@@ -20,5 +20,8 @@ fn main() -> anyhow::Result<()> {
         analyze::Command::PsTree { .. } => display_pstree(&cli),
         analyze::Command::Sessions { .. } => cli.display_sessions(),
         analyze::Command::Session { .. } => cli.display_single_session(),
+        analyze::Command::Sigma { .. } => cli.display_sigma_matches(),
+        analyze::Command::Ioc { .. } => cli.display_ioc_matches(),
+        analyze::Command::Script { .. } => cli.run_script(),
     }
 }
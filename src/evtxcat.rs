@@ -1,6 +1,6 @@
 use std::{
+    borrow::Cow,
     collections::HashMap,
-    fs::File,
     io::{Read, Seek},
     path::PathBuf,
 };
@@ -8,18 +8,21 @@ use std::{
 use anyhow::Result;
 use clap::Parser;
 use colored_json::to_colored_json_auto;
+use chrono::SecondsFormat;
+use eventdata::{resolve_parameter_messages, resolve_parameter_messages_in};
 use evtx::{EvtxParser, SerializedEvtxRecord};
-
-mod event_id;
-
-mod range;
+use libevtx::{
+    EvtxReader, EvtxSource, InputArgs, MessageCatalogue, TimeArgs, TimestampFormat,
+    TimestampFormatter,
+};
 use term_table::{row::Row, table_cell::TableCell};
 
 /// Display one or more events from an evtx file
 #[derive(Parser)]
 #[clap(author,version,name=env!("CARGO_BIN_NAME"))]
 struct Cli {
-    /// Name of the evtx file to read from
+    /// Name of the evtx file to read from. If this is a directory or a ZIP
+    /// archive, all evtx files contained in it are read
     evtx_file: String,
 
     /// filter: minimal event record identifier
@@ -40,12 +43,31 @@ struct Cli {
 
     #[clap(value_enum, short('F'), long("format"), default_value_t = OutputFormat::Xml)]
     format: OutputFormat,
+
+    /// replace references to parameter messages (such as '%%1936') by their text
+    #[clap(short('P'), long("resolve-parameters"))]
+    resolve_parameters: bool,
+
+    /// read additional message templates from this YAML file. This option can
+    /// be specified multiple times
+    #[clap(long("message-catalogue"))]
+    message_catalogues: Vec<PathBuf>,
+
+    #[clap(flatten)]
+    input: InputArgs,
+
+    #[clap(flatten)]
+    time: TimeArgs,
 }
 
 #[derive(clap::ValueEnum, Clone)]
 enum OutputFormat {
     Json,
     Xml,
+
+    /// human readable message. Records without a message template are
+    /// displayed as JSON
+    Message,
 }
 
 trait RecordFilter: Sized {
@@ -53,6 +75,15 @@ trait RecordFilter: Sized {
 
     fn unfiltered(parser: &mut EvtxParser<Self::ReaderType>) -> Unfiltered<Self>;
 
+    /// replaces parameter message references (`%%<id>`) by their text
+    fn resolve_parameter_messages(&mut self);
+
+    fn resolve_parameters_in(records: &mut HashMap<u64, SerializedEvtxRecord<Self>>) {
+        for record in records.values_mut() {
+            record.data.resolve_parameter_messages();
+        }
+    }
+
     fn filter_by_id(
         mut parser: EvtxParser<Self::ReaderType>,
         filter_id: u64,
@@ -98,23 +129,33 @@ trait RecordFilter: Sized {
 }
 
 impl RecordFilter for serde_json::Value {
-    type ReaderType = File;
+    type ReaderType = EvtxReader;
 
     fn unfiltered(parser: &mut EvtxParser<Self::ReaderType>) -> Unfiltered<Self> {
         Unfiltered {
             inner: Box::new(parser.records_json_value()),
         }
     }
+
+    fn resolve_parameter_messages(&mut self) {
+        resolve_parameter_messages_in(self);
+    }
 }
 
 impl RecordFilter for String {
-    type ReaderType = File;
+    type ReaderType = EvtxReader;
 
     fn unfiltered(parser: &mut EvtxParser<Self::ReaderType>) -> Unfiltered<Self> {
         Unfiltered {
             inner: Box::new(parser.records()),
         }
     }
+
+    fn resolve_parameter_messages(&mut self) {
+        if let Cow::Owned(resolved) = resolve_parameter_messages(self) {
+            *self = resolved;
+        }
+    }
 }
 
 struct Unfiltered<'a, V> {
@@ -136,8 +177,13 @@ trait RecordListFormatter: Sized {
         record_ids: Vec<u64>,
         records: HashMap<u64, SerializedEvtxRecord<Self>>,
         cli: &Cli,
+        formatter: &TimestampFormatter,
+        source: Option<&EvtxSource>,
     ) {
         if !cli.show_table {
+            if let Some(source) = source {
+                println!("==> {source} <==");
+            }
             for id in record_ids.into_iter() {
                 let record = &records[&id];
                 println!("{}", Self::format(record));
@@ -145,15 +191,32 @@ trait RecordListFormatter: Sized {
         } else {
             let mut table = term_table::Table::new();
             if let Some(size) = termsize::get() {
-                table.set_max_column_widths(vec![(0, 12), (1, (size.cols - 16).into())])
+                if source.is_some() {
+                    table.set_max_column_widths(vec![
+                        (0, 32),
+                        (1, 12),
+                        (2, 32),
+                        (3, size.cols.saturating_sub(84).into()),
+                    ])
+                } else {
+                    table.set_max_column_widths(vec![
+                        (0, 12),
+                        (1, 32),
+                        (2, size.cols.saturating_sub(48).into()),
+                    ])
+                }
             }
 
             for id in record_ids.into_iter() {
                 let record = &records[&id];
-                table.add_row(Row::new(vec![
-                    TableCell::new(id),
-                    TableCell::new(Self::format(record)),
-                ]));
+                let mut cells = Vec::new();
+                if let Some(source) = source {
+                    cells.push(TableCell::new(source));
+                }
+                cells.push(TableCell::new(id));
+                cells.push(TableCell::new(formatter.format(&record.timestamp)));
+                cells.push(TableCell::new(Self::format(record)));
+                table.add_row(Row::new(cells));
             }
             println!("{}", table.render());
         }
@@ -175,30 +238,69 @@ impl RecordListFormatter for serde_json::Value {
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
-    let path = PathBuf::try_from(&cli.evtx_file)?;
-
-    let parser = EvtxParser::from_path(path)?;
-
-    match cli.format {
-        OutputFormat::Json => {
-            let (record_ids, records) = if let Some(filter_id) = cli.id {
-                serde_json::Value::filter_by_id(parser, filter_id)
-            } else {
-                let min = cli.min.unwrap_or(u64::MIN);
-                let max = cli.max.unwrap_or(u64::MAX);
-                serde_json::Value::filter_by_range(parser, min, max)
-            };
-            serde_json::Value::display_results(record_ids, records, &cli);
-        }
-        OutputFormat::Xml => {
-            let (record_ids, records) = if let Some(filter_id) = cli.id {
-                String::filter_by_id(parser, filter_id)
-            } else {
-                let min = cli.min.unwrap_or(u64::MIN);
-                let max = cli.max.unwrap_or(u64::MAX);
-                String::filter_by_range(parser, min, max)
-            };
-            String::display_results(record_ids, records, &cli);
+    let sources = cli.input.sources([&cli.evtx_file])?;
+    let show_source = EvtxSource::need_tagging(&sources);
+    let formatter = cli.time.formatter(TimestampFormat::Rfc3339(SecondsFormat::Secs));
+    let message_catalogue = MessageCatalogue::with_user_files(&cli.message_catalogues)?;
+
+    for source in sources.iter() {
+        let parser = source.open()?;
+        let source = show_source.then_some(source);
+
+        match cli.format {
+            OutputFormat::Json => {
+                let (record_ids, mut records) = if let Some(filter_id) = cli.id {
+                    serde_json::Value::filter_by_id(parser, filter_id)
+                } else {
+                    let min = cli.min.unwrap_or(u64::MIN);
+                    let max = cli.max.unwrap_or(u64::MAX);
+                    serde_json::Value::filter_by_range(parser, min, max)
+                };
+                if cli.resolve_parameters {
+                    serde_json::Value::resolve_parameters_in(&mut records);
+                }
+                serde_json::Value::display_results(record_ids, records, &cli, &formatter, source);
+            }
+            OutputFormat::Xml => {
+                let (record_ids, mut records) = if let Some(filter_id) = cli.id {
+                    String::filter_by_id(parser, filter_id)
+                } else {
+                    let min = cli.min.unwrap_or(u64::MIN);
+                    let max = cli.max.unwrap_or(u64::MAX);
+                    String::filter_by_range(parser, min, max)
+                };
+                if cli.resolve_parameters {
+                    String::resolve_parameters_in(&mut records);
+                }
+                String::display_results(record_ids, records, &cli, &formatter, source);
+            }
+            OutputFormat::Message => {
+                let (record_ids, mut records) = if let Some(filter_id) = cli.id {
+                    serde_json::Value::filter_by_id(parser, filter_id)
+                } else {
+                    let min = cli.min.unwrap_or(u64::MIN);
+                    let max = cli.max.unwrap_or(u64::MAX);
+                    serde_json::Value::filter_by_range(parser, min, max)
+                };
+                if cli.resolve_parameters {
+                    serde_json::Value::resolve_parameters_in(&mut records);
+                }
+                let messages = records
+                    .into_iter()
+                    .map(|(id, record)| {
+                        let message = message_catalogue
+                            .render(&record)
+                            .unwrap_or_else(|| serde_json::Value::format(&record));
+                        let record = SerializedEvtxRecord {
+                            event_record_id: record.event_record_id,
+                            timestamp: record.timestamp,
+                            data: message,
+                        };
+                        (id, record)
+                    })
+                    .collect();
+                String::display_results(record_ids, messages, &cli, &formatter, source);
+            }
         }
     }
     Ok(())
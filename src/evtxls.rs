@@ -1,159 +1,682 @@
 use std::{
+    cell::RefCell,
+    collections::BTreeSet,
     io::{Read, Seek},
-    path::PathBuf,
+    thread,
+    time::Duration,
 };
 
 use anyhow::Result;
+use chrono::{DateTime, SecondsFormat, Utc};
 use colored::{control::SHOULD_COLORIZE, Colorize};
-use eventdata::EventId;
+use eventdata::{resolve_parameter_messages_in, EventId};
 use evtx::{EvtxParser, ParserSettings, SerializedEvtxRecord};
+use libevtx::{
+    AccountReference, EvtxSource, IdentityStore, MessageCatalogue, PartitionedWriter,
+    Pseudonymizer, RecordScript, TimestampFormat, TimestampFormatter,
+};
 
 use serde_json::Value;
 
 mod ls;
 use clap::Parser;
-use ls::{Cli, FilterBySystemField, HighlightedStringBuilder, SortOrder};
+use ls::{
+    contains_unsafe_characters, decode_codes, restore_escape_sequences, Browser, Cli,
+    ControlCharacterMode, EventDataField, FileUpdate, FilterBySystemField, FollowedFile,
+    HighlightedStringBuilder, HighlightingRule, RecordExporter, RecordSearch, SortOrder,
+    Statistics, StatsFormat, SystemFieldFilter,
+};
 
 struct EvtxLs {
     cli: Cli,
     hs_builder: HighlightedStringBuilder,
+    not_before: Option<DateTime<Utc>>,
+    not_after: Option<DateTime<Utc>>,
+    timestamp_formatter: TimestampFormatter,
+    sources: Vec<EvtxSource>,
+
+    /// width of the column which displays the source of a record, or `None`
+    /// if there is no need to display the source
+    source_width: Option<usize>,
+
+    /// ids of records whose event data contain control characters
+    records_with_control_chars: RefCell<BTreeSet<u64>>,
+
+    /// if the user wants to see messages instead of event data
+    message_catalogue: Option<MessageCatalogue>,
+
+    /// if the user specified search patterns, only matching records are displayed
+    search: Option<RecordSearch>,
+
+    /// if the user requested statistics, records are aggregated here instead of being displayed
+    stats: Option<RefCell<Statistics>>,
+
+    /// mappings between SIDs, account names and domains, if the user wants
+    /// to filter or display identities
+    identities: Option<RefCell<IdentityStore>>,
+    identity_filters: Vec<AccountReference>,
+
+    /// if the user requested a machine readable output format, records are
+    /// exported instead of being displayed
+    exporter: Option<RefCell<RecordExporter>>,
+
+    /// user supplied script which filters records and creates custom output
+    script: Option<RefCell<RecordScript>>,
+
+    /// if the user wants records to be written into separate files, text
+    /// lines are written here instead of being displayed
+    partitions: Option<RefCell<PartitionedWriter>>,
+
+    /// if the user wants to share the output, identifying values are
+    /// replaced after records have been filtered
+    pseudonymizer: Option<RefCell<Pseudonymizer>>,
 }
 
 impl EvtxLs {
-    fn new() -> Self {
+    fn new() -> Result<Self> {
         let cli = Cli::parse();
-        let hs_builder = HighlightedStringBuilder::new(cli.highlight.clone());
+        let mut rules = match cli.highlight_rules.as_ref() {
+            Some(path) => HighlightingRule::load_from(path)?,
+            None => HighlightingRule::default_rules(),
+        };
+        rules.extend(cli.highlight.iter().cloned().map(HighlightingRule::from));
+
+        let search = if cli.search_patterns.is_empty() {
+            None
+        } else {
+            let search = RecordSearch::new(
+                &cli.search_patterns,
+                cli.search_literal,
+                cli.search_ignore_case,
+                cli.search_fields.clone(),
+            )?;
+            rules.push(HighlightingRule::from(search.pattern().clone()));
+            Some(search)
+        };
+        let hs_builder = HighlightedStringBuilder::new(rules, cli.control_characters);
+
+        let not_before = cli.not_before.as_ref().map(|ts| cli.time.to_utc(ts)).transpose()?;
+        let not_after = cli.not_after.as_ref().map(|ts| cli.time.to_utc(ts)).transpose()?;
+
+        let timestamp_formatter = cli.time.formatter(match cli.delimiter {
+            None => TimestampFormat::Custom("%FT%T%.3f".to_owned()),
+            Some(_) => TimestampFormat::Rfc3339(SecondsFormat::AutoSi),
+        });
 
-        Self { cli, hs_builder }
+        let sources = cli.input.sources(&cli.evtx_files)?;
+        let source_width = if EvtxSource::need_tagging(&sources) {
+            sources.iter().map(|s| s.to_string().chars().count()).max()
+        } else {
+            None
+        };
+
+        let exporter = RecordExporter::new(&cli)?.map(RefCell::new);
+        let partitions = match (cli.partition.as_ref(), exporter.as_ref()) {
+            (Some(template), None) => Some(RefCell::new(PartitionedWriter::new(
+                template.clone(),
+                cli.time.timezone.clone(),
+            ))),
+            _ => None,
+        };
+
+        // Timesketch requires a message for every record
+        let message_catalogue = if cli.display_message || exporter.is_some() {
+            Some(MessageCatalogue::with_user_files(&cli.message_catalogues)?)
+        } else {
+            None
+        };
+
+        let stats = if cli.stats {
+            Some(RefCell::new(Statistics::new(
+                cli.group_by.clone(),
+                cli.control_characters,
+            )))
+        } else {
+            None
+        };
+
+        let identities = if cli.identities.is_empty() && !cli.show_identities {
+            None
+        } else {
+            Some(RefCell::new(IdentityStore::learn_from(&sources)?))
+        };
+        let identity_filters = cli
+            .identities
+            .iter()
+            .map(|i| AccountReference::from(&i[..]))
+            .collect();
+
+        let script = cli
+            .script
+            .as_ref()
+            .map(|path| RecordScript::load(path))
+            .transpose()?
+            .map(RefCell::new);
+
+        let pseudonymizer = cli.pseudonym.pseudonymizer()?.map(RefCell::new);
+
+        Ok(Self {
+            cli,
+            hs_builder,
+            not_before,
+            not_after,
+            timestamp_formatter,
+            sources,
+            source_width,
+            message_catalogue,
+            records_with_control_chars: RefCell::new(BTreeSet::new()),
+            search,
+            stats,
+            identities,
+            identity_filters,
+            exporter,
+            script,
+            partitions,
+            pseudonymizer,
+        })
     }
 
     fn run(self) -> Result<()> {
         let mut records = Vec::new();
+        let mut files = Vec::new();
 
-        for f_name in self.cli.evtx_files.iter() {
-            let path = PathBuf::try_from(&f_name)?;
+        for (index, source) in self.sources.iter().enumerate() {
+            let mut file = FollowedFile::new(source.clone());
 
             let settings = ParserSettings::default().num_threads(0);
-            let parser = EvtxParser::from_path(path)?.with_configuration(settings);
+            let parser = source.open()?.with_configuration(settings);
 
-            records.extend(self.read_records(parser)?);
+            records.extend(
+                self.read_records(parser, &mut file)?
+                    .into_iter()
+                    .map(|(record, matched_field)| (index, record, matched_field)),
+            );
+            files.push(file);
         }
 
         match self.cli.sort_order {
-            SortOrder::Storage => assert!(records.is_empty()),
+            SortOrder::Storage => (),
             SortOrder::RecordId => {
-                records.sort_by(|a, b| a.event_record_id.cmp(&b.event_record_id))
+                records.sort_by(|a, b| a.1.event_record_id.cmp(&b.1.event_record_id))
             }
-            SortOrder::Time => records.sort_by(|a, b| a.timestamp.cmp(&b.timestamp)),
+            SortOrder::Time => records.sort_by(|a, b| a.1.timestamp.cmp(&b.1.timestamp)),
+        }
+
+        if self.cli.interactive {
+            SHOULD_COLORIZE.set_override(true);
+            let browser = Browser::new(
+                &self.sources,
+                records
+                    .into_iter()
+                    .map(|(index, record, _)| (index, record))
+                    .collect(),
+                &self.hs_builder,
+                &self.timestamp_formatter,
+                self.cli.control_characters,
+                self.pseudonymizer.is_some()
+                    || self.cli.resolve_parameters
+                    || self.cli.decode_codes,
+            );
+            browser.run()?;
+            self.save_pseudonyms()?;
+            self.report_control_chars();
+            return Ok(());
         }
 
         if !records.is_empty() {
-            for record in records.into_iter() {
-                self.display_record(&record)?;
+            for (index, record, matched_field) in records.into_iter() {
+                self.display_record(&self.sources[index], &record, &matched_field)?;
+            }
+        }
+        if let Some(exporter) = self.exporter.as_ref() {
+            exporter.borrow_mut().flush()?;
+        }
+        if let Some(partitions) = self.partitions.as_ref() {
+            partitions.borrow_mut().flush()?;
+        }
+        self.save_pseudonyms()?;
+
+        if let Some(stats) = self.stats.as_ref() {
+            let formatter = self.cli.time.formatter(match self.cli.stats_format {
+                StatsFormat::Table => TimestampFormat::Custom("%FT%T%.3f".to_owned()),
+                _ => TimestampFormat::Rfc3339(SecondsFormat::AutoSi),
+            });
+            stats.borrow().display(self.cli.stats_format, &formatter)?;
+        }
+
+        if let Some(script) = self.script.as_ref() {
+            if let Some(output) = script.borrow_mut().finish()? {
+                println!("{output}");
             }
         }
 
+        self.report_control_chars();
+
+        if self.cli.follow {
+            self.follow(files)?;
+        }
         Ok(())
     }
 
-    fn read_records<T: Read + Seek>(&self, mut parser: EvtxParser<T>) -> Result<Vec<SerializedEvtxRecord<Value>>> {
-        if self.cli.display_colors {
+    /// polls the files for changes and displays new records, until the
+    /// program is terminated
+    fn follow(&self, mut files: Vec<FollowedFile>) -> Result<()> {
+        let poll_interval = Duration::from_millis(self.cli.poll_interval);
+        loop {
+            thread::sleep(poll_interval);
+            for file in files.iter_mut() {
+                if !file.has_changed() {
+                    continue;
+                }
+
+                // the file might be incomplete if it is just being copied,
+                // so we simply try again later
+                if let Err(why) = self.display_new_records(file) {
+                    eprintln!("WARNING: unable to read {}: {why}", file.source());
+                    file.invalidate();
+                }
+            }
+        }
+    }
+
+    fn display_new_records(&self, file: &mut FollowedFile) -> Result<()> {
+        let settings = ParserSettings::default().num_threads(0);
+        let mut parser = file.source().open()?.with_configuration(settings);
+
+        let mut records: Vec<_> = parser.records_json_value().filter_map(|r| r.ok()).collect();
+        records.iter_mut().for_each(|record| self.prepare_record(record));
+        records.sort_by_key(|r| r.event_record_id);
+
+        let first_record_id = records.first().map(|r| r.event_record_id);
+        let last_record_id = records.last().map(|r| r.event_record_id);
+        let seen_until = match file.update(first_record_id, last_record_id) {
+            FileUpdate::Appended { last_record_id } => last_record_id,
+            FileUpdate::Rewritten => {
+                eprintln!(
+                    "WARNING: {} has been replaced, displaying all of its records",
+                    file.source()
+                );
+                None
+            }
+
+            // the file is read again during the next poll
+            FileUpdate::Incomplete => return Ok(()),
+        };
+
+        for mut record in records
+            .into_iter()
+            .filter(|r| seen_until.is_none_or(|id| r.event_record_id > id))
+        {
+            if let Some(matched_field) = self.select(file.source(), &record)? {
+                self.pseudonymize(&mut record)?;
+                self.display_record(file.source(), &record, &matched_field)?;
+            }
+        }
+        if let Some(exporter) = self.exporter.as_ref() {
+            exporter.borrow_mut().flush()?;
+        }
+        if let Some(partitions) = self.partitions.as_ref() {
+            partitions.borrow_mut().flush()?;
+        }
+        self.save_pseudonyms()
+    }
+
+    fn report_control_chars(&self) {
+        const MAX_DISPLAYED_IDS: usize = 10;
+        let record_ids = self.records_with_control_chars.borrow();
+        if record_ids.is_empty() {
+            return;
+        }
+
+        let mut ids: Vec<_> = record_ids
+            .iter()
+            .take(MAX_DISPLAYED_IDS)
+            .map(|id| id.to_string())
+            .collect();
+        if record_ids.len() > MAX_DISPLAYED_IDS {
+            ids.push("...".to_owned());
+        }
+
+        let action = match self.cli.control_characters {
+            ControlCharacterMode::Raw => "have been displayed unchanged",
+            _ => "have been replaced",
+        };
+        eprintln!(
+            "WARNING: {} records contain control characters or bidirectional formatting characters, which {action}. Record ids: {}",
+            record_ids.len(),
+            ids.join(", ")
+        );
+    }
+
+    fn read_records<T: Read + Seek>(
+        &self,
+        mut parser: EvtxParser<T>,
+        file: &mut FollowedFile,
+    ) -> Result<Vec<(SerializedEvtxRecord<Value>, String)>> {
+        // partitions are written into files, which must not contain colors,
+        // even if stdout is a terminal
+        if self.cli.partition.is_some() {
+            SHOULD_COLORIZE.set_override(false);
+        } else if self.cli.display_colors {
             SHOULD_COLORIZE.set_override(true);
         }
 
         let mut records = Vec::new();
+        let mut first_record_id = None;
+        let mut last_record_id = None;
 
         for result in parser.records_json_value() {
             match result {
                 Err(_) => (),
-                Ok(record) => {
-                    if let Some(not_before) = self.cli.not_before.as_ref() {
-                        if &record.timestamp < not_before {
-                            continue;
-                        }
-                    }
-
-                    if let Some(not_after) = self.cli.not_after.as_ref() {
-                        if &record.timestamp < not_after {
-                            continue;
-                        }
-                    }
+                Ok(mut record) => {
+                    self.prepare_record(&mut record);
+                    let id = record.event_record_id;
+                    first_record_id = Some(first_record_id.map_or(id, |first: u64| first.min(id)));
+                    last_record_id = Some(last_record_id.map_or(id, |last: u64| last.max(id)));
 
-                    if !self.cli.included_event_ids.is_empty() {
-                        let event_id = EventId::try_from(&record)?.into();
-                        if !self.cli.included_event_ids.contains(&event_id) {
-                            continue;
-                        }
-                    }
+                    let Some(matched_field) = self.select(file.source(), &record)? else {
+                        continue;
+                    };
+                    self.pseudonymize(&mut record)?;
 
-                    if !self.cli.excluded_event_ids.is_empty() {
-                        let event_id = EventId::try_from(&record)?.into();
-                        if self.cli.excluded_event_ids.contains(&event_id) {
-                            continue;
-                        }
+                    if let Some(stats) = self.stats.as_ref() {
+                        stats.borrow_mut().add(&record)?;
+                        continue;
                     }
 
-                    if matches!(self.cli.sort_order, SortOrder::Storage) {
-                        self.display_record(&record)?
+                    if matches!(self.cli.sort_order, SortOrder::Storage) && !self.cli.interactive {
+                        self.display_record(file.source(), &record, &matched_field)?
                     } else {
-                        records.push(record);
+                        records.push((record, matched_field));
                     }
                 }
             }
         }
 
+        file.update(first_record_id, last_record_id);
         Ok(records)
     }
 
-    fn display_record(&self, record: &SerializedEvtxRecord<Value>) -> Result<()> {
-        let system_fields = if self.cli.hide_base_fields {
-            "".to_owned()
+    /// applies all modifications of record data which have been requested
+    /// by the user
+    fn prepare_record(&self, record: &mut SerializedEvtxRecord<Value>) {
+        if self.cli.resolve_parameters {
+            resolve_parameter_messages_in(&mut record.data);
+        }
+        if self.cli.decode_codes {
+            decode_codes(record);
+        }
+
+        // records which are appended in follow mode might contain new accounts
+        if self.cli.follow {
+            if let Some(identities) = self.identities.as_ref() {
+                identities.borrow_mut().learn_record(record);
+            }
+        }
+    }
+
+    /// replaces identifying values, if the user requested this. Filters
+    /// must be applied before, because they refer to the original values
+    fn pseudonymize(&self, record: &mut SerializedEvtxRecord<Value>) -> Result<()> {
+        match self.pseudonymizer.as_ref() {
+            Some(pseudonymizer) => pseudonymizer.borrow_mut().pseudonymize_record(record),
+            None => Ok(()),
+        }
+    }
+
+    fn save_pseudonyms(&self) -> Result<()> {
+        match self.pseudonymizer.as_ref() {
+            Some(pseudonymizer) => pseudonymizer.borrow().save_mapping(),
+            None => Ok(()),
+        }
+    }
+
+    /// checks if a record matches all filters specified by the user. If it
+    /// does, the field which matched the search patterns is returned (or an
+    /// empty string, if no search patterns were specified), because it must
+    /// be determined before the record is pseudonymized
+    fn select(
+        &self,
+        source: &EvtxSource,
+        record: &SerializedEvtxRecord<Value>,
+    ) -> Result<Option<String>> {
+        if let Some(not_before) = self.not_before.as_ref() {
+            if &record.timestamp < not_before {
+                return Ok(None);
+            }
+        }
+
+        if let Some(not_after) = self.not_after.as_ref() {
+            if &record.timestamp > not_after {
+                return Ok(None);
+            }
+        }
+
+        if !self.cli.included_event_ids.is_empty() {
+            let event_id = EventId::try_from(record)?.into();
+            if !self.cli.included_event_ids.contains(&event_id) {
+                return Ok(None);
+            }
+        }
+
+        if !self.cli.excluded_event_ids.is_empty() {
+            let event_id = EventId::try_from(record)?.into();
+            if self.cli.excluded_event_ids.contains(&event_id) {
+                return Ok(None);
+            }
+        }
+
+        if !self.cli.system_field_filters.is_empty()
+            && !SystemFieldFilter::matches_all(&self.cli.system_field_filters, record)?
+        {
+            return Ok(None);
+        }
+
+        let matched_field = match self.search.as_ref() {
+            None => String::new(),
+            Some(search) => match search.find_match(record) {
+                None => return Ok(None),
+                Some(matched_field) => matched_field,
+            },
+        };
+
+        if !self.identity_filters.is_empty() {
+            if let Some(identities) = self.identities.as_ref() {
+                if !identities.borrow().record_matches(&self.identity_filters, record) {
+                    return Ok(None);
+                }
+            }
+        }
+
+        if let Some(script) = self.script.as_ref() {
+            if !script.borrow_mut().filter(source, record)? {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(matched_field))
+    }
+
+    fn display_record(
+        &self,
+        source: &EvtxSource,
+        record: &SerializedEvtxRecord<Value>,
+        matched_field: &str,
+    ) -> Result<()> {
+        if let Some(script) = self.script.as_ref().filter(|s| s.borrow().has_map()) {
+            if let Some(output) = script.borrow_mut().map(source, record)? {
+                self.print_line(source, record, &output)?;
+            }
+            return Ok(());
+        }
+
+        if let Some(exporter) = self.exporter.as_ref() {
+            let message = self
+                .message_catalogue
+                .as_ref()
+                .and_then(|catalogue| catalogue.render(record))
+                .map(|message| message.split_whitespace().collect::<Vec<_>>().join(" "));
+            return exporter.borrow_mut().export(source, record, message);
+        }
+
+        let mut line_parts: Vec<String> = if self.cli.hide_base_fields {
+            Vec::new()
         } else {
             let system_fields = <SerializedEvtxRecord<Value> as FilterBySystemField>::filter_fields(
                 record,
                 self.cli.display_system_fields.as_ref()
             )?;
 
-            let line_parts: Vec<String> = if self.cli.delimiter.is_none() {
+            if self.cli.delimiter.is_none() {
                 system_fields
                     .iter()
                     .map(|f| f.value_with_padding())
                     .collect()
             } else {
                 system_fields.iter().map(|f| f.to_string()).collect()
-            };
-            if line_parts.is_empty() {
-                "".to_owned()
-            } else {
-                format!(
-                    "{}{}",
-                    line_parts.join(&self.cli.delimiter.unwrap_or(' ').to_string()),
-                    &self.cli.delimiter.unwrap_or(' ')
-                )
             }
         };
 
-        let event_data = self
-            .format_custom_data(record, "UserData")
-            .or_else(|| self.format_custom_data(record, "EventData"))
-            .unwrap_or_else(|| "".to_owned())
-            .replace("\\u001b", "\u{001b}");
+        if let Some(width) = self.source_width {
+            line_parts.insert(
+                0,
+                match self.cli.delimiter {
+                    None => format!("{:<width$}", source.to_string()),
+                    Some(_) => source.to_string(),
+                },
+            );
+        }
+
+        if self.cli.show_match {
+            line_parts.push(match self.cli.delimiter {
+                None => format!("{matched_field:<30}"),
+                Some(_) => matched_field.to_owned(),
+            });
+        }
+
+        if self.cli.show_identities {
+            let identities = self
+                .identities
+                .as_ref()
+                .map(|identities| {
+                    identities
+                        .borrow()
+                        .identities_of(record)
+                        .iter()
+                        .map(|i| i.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .unwrap_or_default();
+            let identities = self.cli.control_characters.sanitize(&identities).into_owned();
+            line_parts.push(match self.cli.delimiter {
+                None => format!("{identities:<40}"),
+                Some(_) => identities,
+            });
+        }
+
+        line_parts.extend(
+            self.cli
+                .event_data_fields
+                .iter()
+                .map(|field| self.format_event_data_field(record, field)),
+        );
+
+        let system_fields = if line_parts.is_empty() {
+            "".to_owned()
+        } else {
+            format!(
+                "{}{}",
+                line_parts.join(&self.cli.delimiter.unwrap_or(' ').to_string()),
+                &self.cli.delimiter.unwrap_or(' ')
+            )
+        };
+
+        let message = self
+            .message_catalogue
+            .as_ref()
+            .and_then(|catalogue| catalogue.render(record));
+
+        let event_data = if self.cli.hide_event_data {
+            "".to_owned()
+        } else if let Some(message) = message {
+            let event = &record.data["Event"];
+            if contains_unsafe_characters(&event["EventData"])
+                || contains_unsafe_characters(&event["UserData"])
+            {
+                self.records_with_control_chars
+                    .borrow_mut()
+                    .insert(record.event_record_id);
+            }
+
+            // messages span multiple lines, but we display one record per line
+            let message = message.split_whitespace().collect::<Vec<_>>().join(" ");
+            self.cli.control_characters.sanitize(&message).into_owned()
+        } else {
+            let event_data = self
+                .format_custom_data(record, "UserData")
+                .or_else(|| self.format_custom_data(record, "EventData"))
+                .unwrap_or_else(|| "".to_owned());
+            restore_escape_sequences(&event_data)
+        };
 
+        let timestamp = self.timestamp_formatter.format(&record.timestamp);
         let output = match self.cli.delimiter {
-            None => format!(
-                "{} {system_fields}{event_data}",
-                record.timestamp.format("%FT%T%.3f")
-            ),
-            Some(d) => format!(
-                "{}{d}{system_fields}{event_data}",
-                record.timestamp.to_rfc3339()
-            ),
+            None => format!("{timestamp} {system_fields}{event_data}"),
+            Some(d) => format!("{timestamp}{d}{system_fields}{event_data}"),
         }
         .normal();
 
-        println!("{output}");
+        self.print_line(source, record, &output.to_string())
+    }
 
-        Ok(())
+    /// displays a line which describes `record`, or writes it into the file
+    /// of the record's partition
+    fn print_line(
+        &self,
+        source: &EvtxSource,
+        record: &SerializedEvtxRecord<Value>,
+        line: &str,
+    ) -> Result<()> {
+        match self.partitions.as_ref() {
+            Some(partitions) => partitions.borrow_mut().write_line(source, record, None, line),
+            None => {
+                println!("{line}");
+                Ok(())
+            }
+        }
+    }
+
+    fn format_event_data_field(
+        &self,
+        record: &SerializedEvtxRecord<Value>,
+        field: &EventDataField,
+    ) -> String {
+        let text = match field.value_of(record) {
+            None => "".to_owned(),
+            Some(value) => {
+                if contains_unsafe_characters(value) {
+                    self.records_with_control_chars
+                        .borrow_mut()
+                        .insert(record.event_record_id);
+                }
+                field.text_of(record).unwrap_or_default()
+            }
+        };
+
+        match self.cli.delimiter {
+            None => {
+                let value = self
+                    .hs_builder
+                    .build_truncated(&text, Some(field.leaf_name()), field.width());
+                let padding = field.width().saturating_sub(value.display_width());
+                format!("{value}{}", " ".repeat(padding))
+            }
+            Some(_) => self
+                .hs_builder
+                .build_from(&text, Some(field.leaf_name()))
+                .to_string(),
+        }
     }
 
     fn format_custom_data(
@@ -168,7 +691,14 @@ impl EvtxLs {
             None => None,
             Some(custom_data) => match custom_data {
                 Value::Null => Some("".to_owned()),
-                v => Some(self.hs_builder.highlight_data(v).to_string())
+                v => {
+                    if contains_unsafe_characters(v) {
+                        self.records_with_control_chars
+                            .borrow_mut()
+                            .insert(record.event_record_id);
+                    }
+                    Some(self.hs_builder.highlight_data(v).to_string())
+                }
             },
         }
     }
@@ -176,5 +706,5 @@ impl EvtxLs {
 
 fn main() -> Result<()> {
     sigpipe::reset();
-    EvtxLs::new().run()
+    EvtxLs::new()?.run()
 }
@@ -1,18 +1,19 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::collections::HashMap;
 
 use anyhow::Result;
 use chrono::Duration;
 use clap::Parser;
 use colored_json::to_colored_json_auto;
-use evtx::{EvtxParser, SerializedEvtxRecord};
-use libevtx::{EventId, Range};
+use evtx::SerializedEvtxRecord;
+use libevtx::{EventId, EvtxSource, InputArgs, Range, TimeArgs, TimestampFormat};
 use term_table::{row::Row, table_cell::TableCell};
 
 /// Find time skews in an evtx file
 #[derive(Parser)]
 #[clap(author,version,name=env!("CARGO_BIN_NAME"))]
 struct Cli {
-    /// name of the evtx file to scan
+    /// name of the evtx file to scan. If this is a directory or a ZIP archive,
+    /// all evtx files contained in it are scanned separately
     evtx_file: String,
 
     /// display also the contents of the records befor and after a time skew
@@ -22,16 +23,33 @@ struct Cli {
     /// negative tolerance limit (in seconds): time skews to the past below this limit will be ignored
     #[clap(short = 'N', long, default_value_t = 5)]
     negative_tolerance: u32,
+
+    #[clap(flatten)]
+    input: InputArgs,
+
+    #[clap(flatten)]
+    time: TimeArgs,
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let sources = cli.input.sources([&cli.evtx_file])?;
+    let show_source = EvtxSource::need_tagging(&sources);
+
+    for source in sources.iter() {
+        if show_source {
+            println!("==> {source} <==");
+        }
+        scan(source, &cli)?;
+    }
+    Ok(())
+}
+
+fn scan(source: &EvtxSource, cli: &Cli) -> Result<()> {
     let mut record_ids: Vec<EventId> = Vec::new();
     let mut records: HashMap<EventId, SerializedEvtxRecord<serde_json::Value>> = HashMap::new();
 
-    let path = PathBuf::try_from(&cli.evtx_file)?;
-
-    let mut parser = EvtxParser::from_path(path)?;
+    let mut parser = source.open()?;
     for record in parser.records_json_value() {
         match record {
             Err(_) => (),
@@ -64,7 +82,7 @@ fn main() -> Result<()> {
         ranges.push(range)
     }
     ranges.sort();
-    print_ranges(&ranges, &records, &cli);
+    print_ranges(&ranges, &records, cli);
     Ok(())
 }
 
@@ -74,6 +92,7 @@ fn print_ranges(
     cli: &Cli,
 ) {
     let allowed_bias = Duration::seconds(cli.negative_tolerance.into());
+    let formatter = cli.time.formatter(TimestampFormat::Custom("%FT%T".to_owned()));
     if cli.show_records {
         for range in ranges.iter() {
             let mut table = term_table::Table::new();
@@ -86,12 +105,12 @@ fn print_ranges(
 
             table.add_row(Row::new(vec![
                 TableCell::new_with_alignment(
-                    range.begin().timestamp().format("%FT%T"),
+                    formatter.format(range.begin().timestamp()),
                     1,
                     term_table::table_cell::Alignment::Center,
                 ),
                 TableCell::new_with_alignment(
-                    range.end().timestamp().format("%FT%T"),
+                    formatter.format(range.end().timestamp()),
                     1,
                     term_table::table_cell::Alignment::Center,
                 ),
@@ -123,12 +142,12 @@ fn print_ranges(
                             TableCell::new(format!(
                                 "last event {} occurred at {}",
                                 event.event_record_id(),
-                                event.timestamp().format("%FT%T")
+                                formatter.format(event.timestamp())
                             )),
                             TableCell::new(format!(
                                 "current event {} occurred at {}",
                                 current_event.event_record_id(),
-                                current_event.timestamp().format("%FT%T")
+                                formatter.format(current_event.timestamp())
                             )),
                         ]));
 
@@ -147,7 +166,7 @@ fn print_ranges(
         }
     } else {
         for range in ranges.iter() {
-            println!("RANGE: {}", range);
+            println!("RANGE: {}", range.format(&formatter));
             println!("  {} events", range.len());
 
             let mut last_event: Option<&EventId> = None;
@@ -158,12 +177,12 @@ fn print_ranges(
                         println!(
                             "    last event            {} occurred at {},",
                             event.event_record_id(),
-                            event.timestamp().format("%FT%T")
+                            formatter.format(event.timestamp())
                         );
                         println!(
                             "    but the current event {} occurred at {}",
                             current_event.event_record_id(),
-                            current_event.timestamp().format("%FT%T")
+                            formatter.format(current_event.timestamp())
                         );
 
                         let duration = *current_event.timestamp() - *event.timestamp();
@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+
+use evtx::SerializedEvtxRecord;
+use serde_json::Value;
+
+/// returns all fields of `EventData` and `UserData` as a flat list of
+/// scalar values. The names of nested fields are their dotted paths relative
+/// to `EventData` or `UserData` (e.g. `EventXML.Param1`), and elements of
+/// arrays are named by their index (e.g. `Data.0`). Attributes and texts of
+/// elements (`#attributes` and `#text`) don't add to the path, and XML
+/// namespace declarations are omitted.
+pub fn flatten_custom_data(record: &SerializedEvtxRecord<Value>) -> BTreeMap<String, Value> {
+    let mut fields = BTreeMap::new();
+    let event = &record.data["Event"];
+    for data in ["EventData", "UserData"] {
+        if let Some(data) = event.get(data) {
+            flatten_into(data, String::new(), &mut fields);
+        }
+    }
+    fields
+}
+
+/// returns all children of `value` as a flat list of scalar values, named
+/// like the fields returned by [`flatten_custom_data`]
+pub fn flatten_fields(value: &Value) -> BTreeMap<String, Value> {
+    let mut fields = BTreeMap::new();
+    flatten_into(value, String::new(), &mut fields);
+    fields
+}
+
+fn flatten_into(value: &Value, path: String, fields: &mut BTreeMap<String, Value>) {
+    let child_path = |name: &str| {
+        if path.is_empty() {
+            name.to_owned()
+        } else {
+            format!("{path}.{name}")
+        }
+    };
+
+    match value {
+        Value::Object(children) => {
+            for (name, child) in children {
+                if name == "xmlns" {
+                    continue;
+                } else if name == "#attributes" || name == "#text" {
+                    flatten_into(child, path.clone(), fields);
+                } else {
+                    flatten_into(child, child_path(name), fields);
+                }
+            }
+        }
+        Value::Array(elements) => {
+            for (index, element) in elements.iter().enumerate() {
+                flatten_into(element, child_path(&index.to_string()), fields);
+            }
+        }
+        Value::Null => (),
+        value => {
+            if !path.is_empty() {
+                fields.insert(path, value.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use std::fmt::Display;
+
+use evtx::SerializedEvtxRecord;
+use serde::Serialize;
+use serde_json::Value;
+
+/// fields of `EventData` which reference an account, as triples of the
+/// fields which contain the SID, the account name and the domain name
+const ACCOUNT_FIELDS: &[(&str, &str, &str)] = &[
+    ("SubjectUserSid", "SubjectUserName", "SubjectDomainName"),
+    ("TargetUserSid", "TargetUserName", "TargetDomainName"),
+    ("TargetSid", "TargetUserName", "TargetDomainName"),
+    ("MemberSid", "", ""),
+];
+
+/// fields which contain an account name in the form `DOMAIN\name`, such as
+/// the `User` field of Sysmon events
+const QUALIFIED_NAME_FIELDS: &[&str] = &["User"];
+
+/// a reference to an account, as it is found in an event. Every part of
+/// the reference might be missing
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountReference {
+    pub sid: Option<String>,
+    pub name: Option<String>,
+    pub domain: Option<String>,
+}
+
+impl AccountReference {
+    pub fn new(sid: Option<&str>, name: Option<&str>, domain: Option<&str>) -> Self {
+        let mut me = Self {
+            sid: sid.and_then(normalize).map(|s| s.to_uppercase()),
+            name: None,
+            domain: domain.and_then(normalize).map(str::to_owned),
+        };
+
+        if let Some(name) = name.and_then(normalize) {
+            let qualified = Self::from(name);
+            me.name = qualified.name;
+            me.sid = me.sid.or(qualified.sid);
+            me.domain = qualified.domain.or(me.domain);
+        }
+        me
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sid.is_none() && self.name.is_none()
+    }
+
+    /// all references to accounts which can be found in `record`
+    pub fn all_in(record: &SerializedEvtxRecord<Value>) -> Vec<Self> {
+        let event = &record.data["Event"];
+        let mut references = Vec::new();
+
+        if let Some(event_data) = event["EventData"].as_object() {
+            let field = |name: &str| event_data.get(name).and_then(Value::as_str);
+            for (sid, name, domain) in ACCOUNT_FIELDS {
+                if event_data.contains_key(*sid) || event_data.contains_key(*name) {
+                    references.push(Self::new(field(sid), field(name), field(domain)));
+                }
+            }
+            for name in QUALIFIED_NAME_FIELDS {
+                if let Some(name) = field(name) {
+                    references.push(Self::new(None, Some(name), None));
+                }
+            }
+        }
+
+        // events of the TerminalServices providers store the user in UserData
+        if let Some(user) = event["UserData"]["EventXML"]["User"].as_str() {
+            references.push(Self::new(None, Some(user), None));
+        }
+
+        if let Some(sid) = event["System"]["Security"]["#attributes"]["UserID"].as_str() {
+            references.push(Self::new(Some(sid), None, None));
+        }
+
+        references.retain(|r| !r.is_empty());
+        references.dedup();
+        references
+    }
+}
+
+/// parses an account reference, which is either a SID, `DOMAIN\name`,
+/// `name@domain` or a simple account name
+impl From<&str> for AccountReference {
+    fn from(value: &str) -> Self {
+        let value = value.trim();
+        if is_sid(value) {
+            Self {
+                sid: Some(value.to_uppercase()),
+                ..Default::default()
+            }
+        } else if let Some((domain, name)) = value.split_once('\\') {
+            Self {
+                sid: None,
+                name: normalize(name).map(str::to_owned),
+                domain: normalize(domain).map(str::to_owned),
+            }
+        } else if let Some((name, domain)) = value.rsplit_once('@') {
+            Self {
+                sid: None,
+                name: normalize(name).map(str::to_owned),
+                domain: normalize(domain).map(str::to_owned),
+            }
+        } else {
+            Self {
+                sid: None,
+                name: normalize(value).map(str::to_owned),
+                domain: None,
+            }
+        }
+    }
+}
+
+/// the canonical representation of an account, as it has been resolved by
+/// an [`crate::IdentityStore`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Identity {
+    domain: Option<String>,
+    name: String,
+    sid: Option<String>,
+}
+
+impl Identity {
+    pub fn new(sid: Option<String>, domain: Option<String>, name: String) -> Self {
+        Self { domain, name, sid }
+    }
+
+    pub fn sid(&self) -> Option<&str> {
+        self.sid.as_deref()
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn domain(&self) -> Option<&str> {
+        self.domain.as_deref()
+    }
+}
+
+impl Display for Identity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.domain.as_ref() {
+            Some(domain) => write!(f, "{domain}\\{}", self.name),
+            None => self.name.fmt(f),
+        }
+    }
+}
+
+impl Serialize for Identity {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+const NT_AUTHORITY: Option<&str> = Some("NT AUTHORITY");
+const BUILTIN: Option<&str> = Some("BUILTIN");
+
+/// SIDs which are the same on all Windows systems, with their domain and
+/// account name
+const WELL_KNOWN_SIDS: &[(&str, Option<&str>, &str)] = &[
+    ("S-1-0-0", None, "NULL SID"),
+    ("S-1-1-0", None, "Everyone"),
+    ("S-1-2-0", None, "LOCAL"),
+    ("S-1-3-0", None, "CREATOR OWNER"),
+    ("S-1-5-2", NT_AUTHORITY, "NETWORK"),
+    ("S-1-5-3", NT_AUTHORITY, "BATCH"),
+    ("S-1-5-4", NT_AUTHORITY, "INTERACTIVE"),
+    ("S-1-5-6", NT_AUTHORITY, "SERVICE"),
+    ("S-1-5-7", NT_AUTHORITY, "ANONYMOUS LOGON"),
+    ("S-1-5-9", NT_AUTHORITY, "ENTERPRISE DOMAIN CONTROLLERS"),
+    ("S-1-5-11", NT_AUTHORITY, "Authenticated Users"),
+    ("S-1-5-13", NT_AUTHORITY, "TERMINAL SERVER USER"),
+    ("S-1-5-14", NT_AUTHORITY, "REMOTE INTERACTIVE LOGON"),
+    ("S-1-5-18", NT_AUTHORITY, "SYSTEM"),
+    ("S-1-5-19", NT_AUTHORITY, "LOCAL SERVICE"),
+    ("S-1-5-20", NT_AUTHORITY, "NETWORK SERVICE"),
+    ("S-1-5-32-544", BUILTIN, "Administrators"),
+    ("S-1-5-32-545", BUILTIN, "Users"),
+    ("S-1-5-32-546", BUILTIN, "Guests"),
+    ("S-1-5-32-547", BUILTIN, "Power Users"),
+    ("S-1-5-32-551", BUILTIN, "Backup Operators"),
+    ("S-1-5-32-555", BUILTIN, "Remote Desktop Users"),
+    ("S-1-5-32-562", BUILTIN, "Distributed COM Users"),
+    ("S-1-5-32-580", BUILTIN, "Remote Management Users"),
+];
+
+/// returns the domain and the name of a well-known SID
+pub fn well_known_sid(sid: &str) -> Option<(Option<&'static str>, &'static str)> {
+    WELL_KNOWN_SIDS
+        .iter()
+        .find(|(s, _, _)| *s == sid)
+        .map(|(_, domain, name)| (*domain, *name))
+}
+
+/// returns the SID of a well-known account, such as `NT AUTHORITY\SYSTEM`
+pub fn well_known_account(name: &str, domain: Option<&str>) -> Option<&'static str> {
+    WELL_KNOWN_SIDS
+        .iter()
+        .find(|(_, d, n)| {
+            n.eq_ignore_ascii_case(name)
+                && match (d, domain) {
+                    (Some(lhs), Some(rhs)) => lhs.eq_ignore_ascii_case(rhs),
+                    (None, Some(_)) => false,
+                    (_, None) => true,
+                }
+        })
+        .map(|(sid, _, _)| *sid)
+}
+
+/// returns the name of an account whose relative id is the same in all
+/// domains, such as the builtin `Administrator` account
+pub fn well_known_rid(sid: &str) -> Option<&'static str> {
+    if !sid.starts_with("S-1-5-21-") {
+        return None;
+    }
+    let name = match sid.rsplit('-').next()? {
+        "500" => "Administrator",
+        "501" => "Guest",
+        "502" => "krbtgt",
+        "503" => "DefaultAccount",
+        "512" => "Domain Admins",
+        "513" => "Domain Users",
+        "514" => "Domain Guests",
+        "515" => "Domain Computers",
+        "516" => "Domain Controllers",
+        "518" => "Schema Admins",
+        "519" => "Enterprise Admins",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// returns the SID of the domain which contains the account `sid`, if this
+/// is a domain account
+pub fn domain_sid(sid: &str) -> Option<&str> {
+    if !sid.starts_with("S-1-5-21-") {
+        return None;
+    }
+    sid.rsplit_once('-').map(|(domain, _)| domain)
+}
+
+fn is_sid(value: &str) -> bool {
+    value.len() > 4
+        && value[..4].eq_ignore_ascii_case("S-1-")
+        && value[4..].chars().all(|c| c.is_ascii_digit() || c == '-')
+}
+
+/// Windows uses `-` for missing values
+fn normalize(value: &str) -> Option<&str> {
+    let value = value.trim();
+    if value.is_empty() || value == "-" {
+        None
+    } else {
+        Some(value)
+    }
+}
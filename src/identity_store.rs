@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+
+use evtx::SerializedEvtxRecord;
+use serde_json::Value;
+
+use crate::{
+    domain_sid, well_known_account, well_known_rid, well_known_sid, AccountReference, EvtxSource,
+    Identity,
+};
+
+/// the different names of one single domain
+#[derive(Default, Debug)]
+struct Domain {
+    sid: Option<String>,
+    netbios_name: Option<String>,
+    dns_name: Option<String>,
+}
+
+impl Domain {
+    fn has_name(&self, name: &str) -> bool {
+        self.netbios_name
+            .iter()
+            .chain(self.dns_name.iter())
+            .any(|n| domain_names_match(n, name))
+    }
+
+    fn add_name(&mut self, name: &str) {
+        let slot = if name.contains('.') {
+            &mut self.dns_name
+        } else {
+            &mut self.netbios_name
+        };
+        if slot.is_none() {
+            *slot = Some(name.to_owned());
+        }
+    }
+
+    fn merge(&mut self, other: Domain) {
+        self.sid = self.sid.take().or(other.sid);
+        self.netbios_name = self.netbios_name.take().or(other.netbios_name);
+        self.dns_name = self.dns_name.take().or(other.dns_name);
+    }
+
+    /// NetBIOS names are preferred, because Windows uses them in most events
+    fn canonical_name(&self) -> Option<&str> {
+        self.netbios_name.as_deref().or(self.dns_name.as_deref())
+    }
+}
+
+/// learns which SIDs, account names and domain names belong together, and
+/// resolves account references found in events to canonical identities
+#[derive(Default, Debug)]
+pub struct IdentityStore {
+    /// domain and account name by SID
+    accounts: HashMap<String, (Option<String>, String)>,
+
+    domains: Vec<Domain>,
+}
+
+impl IdentityStore {
+    /// creates a store which knows all identities referenced in `sources`.
+    /// Records which cannot be read are ignored
+    pub fn learn_from(sources: &[EvtxSource]) -> anyhow::Result<Self> {
+        let mut store = Self::default();
+        for source in sources {
+            for record in source.open()?.records_json_value().filter_map(|r| r.ok()) {
+                store.learn_record(&record);
+            }
+        }
+        Ok(store)
+    }
+
+    pub fn learn_record(&mut self, record: &SerializedEvtxRecord<Value>) {
+        for reference in AccountReference::all_in(record) {
+            self.learn(&reference);
+        }
+    }
+
+    pub fn learn(&mut self, reference: &AccountReference) {
+        if let Some(sid) = reference.sid.as_ref() {
+            if well_known_sid(sid).is_some() {
+                return;
+            }
+        }
+
+        if let Some(domain) = reference.domain.as_ref() {
+            self.learn_domain(domain, reference.sid.as_deref().and_then(domain_sid));
+        }
+
+        if let (Some(sid), Some(name)) = (reference.sid.as_ref(), reference.name.as_ref()) {
+            let account = self
+                .accounts
+                .entry(sid.clone())
+                .or_insert_with(|| (None, name.clone()));
+            if account.1.eq_ignore_ascii_case(name) && account.0.is_none() {
+                account.0 = reference.domain.clone();
+            }
+        }
+    }
+
+    fn learn_domain(&mut self, name: &str, sid: Option<&str>) {
+        let mut matching = self
+            .domains
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| match (d.sid.as_deref(), sid) {
+                (Some(known_sid), Some(sid)) if known_sid == sid => true,
+                (Some(_), Some(_)) => false,
+                _ => d.has_name(name),
+            })
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+
+        let mut domain = match matching.first() {
+            None => Domain::default(),
+            Some(_) => {
+                // more than one entry might match, if we learned the names
+                // of a domain before we learned its SID
+                let mut domain = Domain::default();
+                while let Some(index) = matching.pop() {
+                    domain.merge(self.domains.remove(index));
+                }
+                domain
+            }
+        };
+        if domain.sid.is_none() {
+            domain.sid = sid.map(str::to_owned);
+        }
+        domain.add_name(name);
+        self.domains.push(domain);
+    }
+
+    /// returns the name which should be used for a domain. This is the
+    /// NetBIOS name if it is known, and the name itself otherwise
+    pub fn canonical_domain(&self, name: &str) -> String {
+        self.domains
+            .iter()
+            .find(|d| d.has_name(name))
+            .and_then(Domain::canonical_name)
+            .unwrap_or(name)
+            .to_owned()
+    }
+
+    pub fn same_domain(&self, lhs: &str, rhs: &str) -> bool {
+        domain_names_match(lhs, rhs)
+            || self
+                .canonical_domain(lhs)
+                .eq_ignore_ascii_case(&self.canonical_domain(rhs))
+    }
+
+    /// returns the canonical identity for an account reference, or `None`
+    /// if the reference is empty
+    pub fn resolve(&self, reference: &AccountReference) -> Option<Identity> {
+        if let Some(sid) = reference.sid.as_ref() {
+            if let Some((domain, name)) = well_known_sid(sid) {
+                return Some(Identity::new(
+                    Some(sid.clone()),
+                    domain.map(str::to_owned),
+                    name.to_owned(),
+                ));
+            }
+            if let Some((domain, name)) = self.accounts.get(sid) {
+                let domain = domain.as_ref().or(reference.domain.as_ref());
+                return Some(Identity::new(
+                    Some(sid.clone()),
+                    domain.map(|d| self.canonical_domain(d)),
+                    name.clone(),
+                ));
+            }
+        }
+
+        let domain = reference.domain.as_deref().map(|d| self.canonical_domain(d));
+        let name = match reference.name.as_ref() {
+            Some(name) => name.clone(),
+            None => {
+                let sid = reference.sid.as_ref()?;
+                let name = well_known_rid(sid).map(str::to_owned).unwrap_or_else(|| sid.clone());
+                let domain = domain.or_else(|| self.domain_of(sid));
+                return Some(Identity::new(Some(sid.clone()), domain, name));
+            }
+        };
+
+        if reference.sid.is_none() {
+            if let Some(sid) = well_known_account(&name, domain.as_deref()) {
+                return self.resolve(&AccountReference::new(Some(sid), None, None));
+            }
+        }
+
+        let sid = reference
+            .sid
+            .clone()
+            .or_else(|| self.find_sid(&name, domain.as_deref()));
+        match sid.as_ref().and_then(|sid| self.accounts.get(sid)) {
+            Some((known_domain, known_name)) => {
+                let domain = known_domain.as_deref().map(|d| self.canonical_domain(d)).or(domain);
+                Some(Identity::new(sid, domain, known_name.clone()))
+            }
+            None => Some(Identity::new(sid, domain, name)),
+        }
+    }
+
+    /// returns the canonical name of the domain which contains the account
+    /// `sid`, if this domain is known
+    fn domain_of(&self, sid: &str) -> Option<String> {
+        let domain_sid = domain_sid(sid)?;
+        self.domains
+            .iter()
+            .find(|d| d.sid.as_deref() == Some(domain_sid))
+            .and_then(Domain::canonical_name)
+            .map(str::to_owned)
+    }
+
+    /// finds the SID of an account. If no domain is specified, the account
+    /// name must be unique
+    fn find_sid(&self, name: &str, domain: Option<&str>) -> Option<String> {
+        let mut candidates = self
+            .accounts
+            .iter()
+            .filter(|(_, (d, n))| {
+                n.eq_ignore_ascii_case(name)
+                    && match (d.as_deref(), domain) {
+                        (Some(lhs), Some(rhs)) => self.same_domain(lhs, rhs),
+                        (None, Some(_)) => false,
+                        (_, None) => true,
+                    }
+            })
+            .map(|(sid, _)| sid);
+        match (candidates.next(), candidates.next()) {
+            (Some(sid), None) => Some(sid.clone()),
+            _ => None,
+        }
+    }
+
+    /// all identities which are referenced in `record`
+    pub fn identities_of(&self, record: &SerializedEvtxRecord<Value>) -> Vec<Identity> {
+        let mut identities = Vec::new();
+        for identity in AccountReference::all_in(record)
+            .iter()
+            .filter_map(|r| self.resolve(r))
+        {
+            if !identities.contains(&identity) {
+                identities.push(identity);
+            }
+        }
+        identities
+    }
+
+    /// checks if `identity` is the same account as `pattern`, which may be
+    /// incomplete, i.e. have no SID or no domain
+    pub fn matches(&self, pattern: &Identity, identity: &Identity) -> bool {
+        if let (Some(lhs), Some(rhs)) = (pattern.sid(), identity.sid()) {
+            return lhs == rhs;
+        }
+
+        pattern.name().eq_ignore_ascii_case(identity.name())
+            && match (pattern.domain(), identity.domain()) {
+                (Some(lhs), Some(rhs)) => self.same_domain(lhs, rhs),
+                _ => true,
+            }
+    }
+
+    /// checks if any of the identities referenced in `record` matches any of
+    /// the `patterns`
+    pub fn record_matches(
+        &self,
+        patterns: &[AccountReference],
+        record: &SerializedEvtxRecord<Value>,
+    ) -> bool {
+        let identities = self.identities_of(record);
+        patterns
+            .iter()
+            .filter_map(|pattern| self.resolve(pattern))
+            .any(|pattern| identities.iter().any(|i| self.matches(&pattern, i)))
+    }
+}
+
+/// compares two domain names, where one of them might be the DNS name and
+/// the other one the NetBIOS name, which might be truncated to 15 characters
+pub fn domain_names_match(lhs: &str, rhs: &str) -> bool {
+    let lhs = lhs.to_lowercase();
+    let rhs = rhs.to_lowercase();
+    if lhs == rhs {
+        return true;
+    }
+
+    let (dns_name, netbios_name) = match (lhs.contains('.'), rhs.contains('.')) {
+        (true, false) => (lhs, rhs),
+        (false, true) => (rhs, lhs),
+        (false, false) => {
+            return (lhs.len() > 15 && rhs.len() == 15 && lhs.starts_with(&rhs))
+                || (rhs.len() > 15 && lhs.len() == 15 && rhs.starts_with(&lhs))
+        }
+        (true, true) => return false,
+    };
+    let first_label = dns_name.split('.').next().unwrap_or_default();
+    first_label == netbios_name
+        || (first_label.len() > 15 && netbios_name.len() == 15 && first_label.starts_with(&netbios_name))
+}
@@ -0,0 +1,133 @@
+use std::{
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context};
+use clap::Args;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use walkdir::WalkDir;
+
+use crate::{evtx_source::member_file_name, EvtxSource};
+
+const DEFAULT_INCLUDE_PATTERN: &str = "*.evtx";
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// command line options which control which evtx files are read from
+/// directories and ZIP archives
+#[derive(Args, Clone)]
+pub struct InputArgs {
+    /// when reading directories or ZIP archives, read only files whose name
+    /// matches this glob pattern (e.g. '*Security*.evtx'). This option can be
+    /// specified multiple times. If it is omitted, all '*.evtx' files are read
+    #[clap(long("include-files"), global = true)]
+    pub include_files: Vec<String>,
+
+    /// when reading directories or ZIP archives, skip files whose name matches
+    /// this glob pattern. This option can be specified multiple times
+    #[clap(long("exclude-files"), global = true)]
+    pub exclude_files: Vec<String>,
+}
+
+impl InputArgs {
+    /// finds all evtx files specified by `paths`. A path can be an evtx file,
+    /// a directory (which is searched recursively) or a ZIP archive. Files
+    /// which are specified explicitly are always read, regardless of
+    /// '--include-files' and '--exclude-files'
+    pub fn sources<P: AsRef<Path>>(
+        &self,
+        paths: impl IntoIterator<Item = P>,
+    ) -> anyhow::Result<Vec<EvtxSource>> {
+        let include = if self.include_files.is_empty() {
+            build_globset(&[DEFAULT_INCLUDE_PATTERN.to_owned()])?
+        } else {
+            build_globset(&self.include_files)?
+        };
+        let exclude = build_globset(&self.exclude_files)?;
+        let is_selected = |name: &str| include.is_match(name) && !exclude.is_match(name);
+
+        let mut sources = Vec::new();
+        for path in paths {
+            let path = path.as_ref();
+            if path.is_dir() {
+                for entry in WalkDir::new(path).sort_by_file_name() {
+                    let entry = entry?;
+                    if !entry.file_type().is_file() {
+                        continue;
+                    }
+                    // to avoid opening every file of a collection, archives
+                    // inside of directories are recognized by their extension
+                    let name = entry.file_name().to_string_lossy();
+                    if name.to_lowercase().ends_with(".zip") {
+                        sources.extend(zip_members(entry.path(), is_selected)?);
+                    } else if is_selected(&name) {
+                        sources.push(EvtxSource::File(entry.path().to_owned()));
+                    }
+                }
+            } else if path.is_file() {
+                if is_zip_file(path)? {
+                    sources.extend(zip_members(path, is_selected)?);
+                } else {
+                    sources.push(EvtxSource::File(path.to_owned()));
+                }
+            } else {
+                bail!(
+                    "unable to read {}: no such file or directory",
+                    path.display()
+                );
+            }
+        }
+        Ok(sources)
+    }
+}
+
+fn build_globset(patterns: &[String]) -> anyhow::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(
+            GlobBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .with_context(|| format!("invalid glob pattern: '{pattern}'"))?,
+        );
+    }
+    Ok(builder.build()?)
+}
+
+fn is_zip_file(path: &Path) -> anyhow::Result<bool> {
+    let mut magic = [0; 4];
+    let mut file =
+        File::open(path).with_context(|| format!("unable to open {}", path.display()))?;
+    match file.read_exact(&mut magic) {
+        Ok(()) => Ok(magic == ZIP_MAGIC),
+        Err(_) => Ok(false),
+    }
+}
+
+fn zip_members(
+    archive: &Path,
+    is_selected: impl Fn(&str) -> bool,
+) -> anyhow::Result<Vec<EvtxSource>> {
+    let file =
+        File::open(archive).with_context(|| format!("unable to open {}", archive.display()))?;
+    let zip = zip::ZipArchive::new(file)
+        .with_context(|| format!("invalid ZIP archive: {}", archive.display()))?;
+
+    let mut members: Vec<_> = zip
+        .file_names()
+        .filter(|name| !name.ends_with('/'))
+        .filter(|name| is_selected(member_file_name(name)))
+        .map(|name| name.to_owned())
+        .collect();
+    members.sort();
+
+    let archive = PathBuf::from(archive);
+    Ok(members
+        .into_iter()
+        .map(|member| EvtxSource::ZipMember {
+            archive: archive.clone(),
+            member,
+        })
+        .collect())
+}
@@ -4,3 +4,66 @@ pub use event_id::*;
 
 mod range;
 pub use range::*;
+
+mod timezone_spec;
+pub use timezone_spec::*;
+
+mod timestamp_format;
+pub use timestamp_format::*;
+
+mod rfc3339_datetime;
+pub use rfc3339_datetime::*;
+
+mod time_args;
+pub use time_args::*;
+
+mod evtx_source;
+pub use evtx_source::*;
+
+mod input_args;
+pub use input_args::*;
+
+mod message_catalogue;
+pub use message_catalogue::*;
+
+mod identity;
+pub use identity::*;
+
+mod identity_store;
+pub use identity_store::*;
+
+mod flattened_fields;
+pub use flattened_fields::*;
+
+mod timesketch;
+pub use timesketch::*;
+
+mod ecs;
+pub use ecs::*;
+
+mod bulk_ingest;
+pub use bulk_ingest::*;
+
+mod sqlite_export;
+pub use sqlite_export::*;
+
+mod parquet_export;
+pub use parquet_export::*;
+
+mod siem_formats;
+pub use siem_formats::*;
+
+mod syslog;
+pub use syslog::*;
+
+mod record_script;
+pub use record_script::*;
+
+mod partitioned_writer;
+pub use partitioned_writer::*;
+
+mod pseudonymizer;
+pub use pseudonymizer::*;
+
+mod pseudonym_args;
+pub use pseudonym_args::*;
\ No newline at end of file
@@ -0,0 +1,46 @@
+use std::str::FromStr;
+
+use evtx::SerializedEvtxRecord;
+use serde_json::Value;
+
+use crate::ls::{RecordSearch, SystemFieldFilter};
+
+/// a filter which is entered interactively. It consists of terms separated
+/// by whitespace, all of which must match. Terms of the form `<FIELD>=<VALUE>`
+/// compare system fields (like `--where` does, e.g. `event-id=4624` or
+/// `provider=Microsoft-Windows-Security-Auditing`), all other terms are
+/// searched case insensitive in all fields
+#[derive(Default)]
+pub(crate) struct BrowserFilter {
+    system_fields: Vec<SystemFieldFilter>,
+    searches: Vec<RecordSearch>,
+}
+
+impl From<&str> for BrowserFilter {
+    fn from(s: &str) -> Self {
+        let mut filter = Self::default();
+        for term in s.split_whitespace() {
+            match SystemFieldFilter::from_str(term) {
+                Ok(system_field) => filter.system_fields.push(system_field),
+                Err(_) => filter.searches.push(
+                    RecordSearch::new(&[term.to_owned()], true, true, Vec::new())
+                        .expect("literal patterns must always be valid"),
+                ),
+            }
+        }
+        filter
+    }
+}
+
+impl BrowserFilter {
+    pub fn matches(&self, record: &SerializedEvtxRecord<Value>) -> bool {
+        if !self.system_fields.is_empty()
+            && !SystemFieldFilter::matches_all(&self.system_fields, record).unwrap_or(false)
+        {
+            return false;
+        }
+        self.searches
+            .iter()
+            .all(|search| search.find_match(record).is_some())
+    }
+}
@@ -0,0 +1,377 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs::File,
+    io::{BufWriter, Write},
+    str::FromStr,
+};
+
+use anyhow::Result;
+use evtx::SerializedEvtxRecord;
+use libevtx::{EvtxSource, Rfc3339Datetime, TimeZoneSpec, TimestampFormatter};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    DefaultTerminal,
+};
+use serde_json::{json, Value};
+
+use super::{restore_escape_sequences, ControlCharacterMode, HighlightedStringBuilder};
+
+mod browser_filter;
+use browser_filter::*;
+
+mod ui;
+
+mod xml_view;
+use xml_view::record_to_xml;
+
+const DEFAULT_EXPORT_FILE: &str = "bookmarks.jsonl";
+
+/// interactive terminal browser, which displays a list of records and the
+/// contents of the selected record
+pub(crate) struct Browser<'a> {
+    sources: &'a [EvtxSource],
+    records: Vec<(usize, SerializedEvtxRecord<Value>)>,
+    hs_builder: &'a HighlightedStringBuilder,
+    timestamp_formatter: &'a TimestampFormatter,
+    timezone: TimeZoneSpec,
+    control_characters: ControlCharacterMode,
+
+    /// indices of the records which pass the current filter
+    visible: Vec<usize>,
+
+    /// selected entry of `visible`
+    selected: usize,
+
+    /// first entry of `visible` which is displayed in the list
+    offset: usize,
+
+    /// number of records which fit into the list
+    page_size: usize,
+
+    detail_scroll: u16,
+    detail_view: DetailView,
+
+    /// XML representations of all records, which are read when the XML
+    /// view is requested for the first time
+    xml_records: Option<HashMap<(usize, u64), String>>,
+
+    /// the record data differ from the original files (e.g. because they
+    /// have been pseudonymized), so the XML view must be created from them
+    xml_from_json: bool,
+
+    filter: String,
+    only_bookmarks: bool,
+    bookmarks: BTreeSet<usize>,
+
+    input: Option<Input>,
+    status: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum DetailView {
+    Json,
+    Xml,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum InputKind {
+    Filter,
+    Jump,
+    Export,
+}
+
+/// content of the input line, while the user is entering some text
+struct Input {
+    kind: InputKind,
+    text: String,
+
+    /// the filter which was active before the user started editing it
+    previous_filter: String,
+}
+
+impl<'a> Browser<'a> {
+    pub fn new(
+        sources: &'a [EvtxSource],
+        records: Vec<(usize, SerializedEvtxRecord<Value>)>,
+        hs_builder: &'a HighlightedStringBuilder,
+        timestamp_formatter: &'a TimestampFormatter,
+        control_characters: ControlCharacterMode,
+        xml_from_json: bool,
+    ) -> Self {
+        let visible = (0..records.len()).collect();
+        Self {
+            sources,
+            records,
+            hs_builder,
+            timestamp_formatter,
+            timezone: timestamp_formatter.timezone().clone(),
+            control_characters,
+            visible,
+            selected: 0,
+            offset: 0,
+            page_size: 1,
+            detail_scroll: 0,
+            detail_view: DetailView::Json,
+            xml_records: None,
+            xml_from_json,
+            filter: String::new(),
+            only_bookmarks: false,
+            bookmarks: BTreeSet::new(),
+            input: None,
+            status: String::new(),
+        }
+    }
+
+    pub fn run(mut self) -> Result<()> {
+        let mut terminal = ratatui::init();
+        let result = self.event_loop(&mut terminal);
+        ratatui::restore();
+        result
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| ui::draw(frame, self))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                let quit = if self.input.is_some() {
+                    self.handle_input_key(key);
+                    false
+                } else {
+                    self.handle_key(key)
+                };
+                if quit {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// handles a key while the list has the focus. Returns `true` if the
+    /// browser should be closed
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        self.status.clear();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Down | KeyCode::Char('j') => self.select(self.selected.saturating_add(1)),
+            KeyCode::Up | KeyCode::Char('k') => self.select(self.selected.saturating_sub(1)),
+            KeyCode::PageDown => self.select(self.selected.saturating_add(self.page_size)),
+            KeyCode::PageUp => self.select(self.selected.saturating_sub(self.page_size)),
+            KeyCode::Home | KeyCode::Char('g') => self.select(0),
+            KeyCode::End | KeyCode::Char('G') => self.select(usize::MAX),
+            KeyCode::Char('J') => self.detail_scroll = self.detail_scroll.saturating_add(1),
+            KeyCode::Char('K') => self.detail_scroll = self.detail_scroll.saturating_sub(1),
+            KeyCode::Char(' ') | KeyCode::Char('b') => self.toggle_bookmark(),
+            KeyCode::Char('B') => {
+                self.only_bookmarks = !self.only_bookmarks;
+                self.apply_filter();
+            }
+            KeyCode::Char('x') => self.toggle_detail_view(),
+            KeyCode::Char('/') => self.start_input(InputKind::Filter, self.filter.clone()),
+            KeyCode::Char(':') => self.start_input(InputKind::Jump, String::new()),
+            KeyCode::Char('e') => {
+                self.start_input(InputKind::Export, DEFAULT_EXPORT_FILE.to_owned())
+            }
+            _ => (),
+        }
+        false
+    }
+
+    fn handle_input_key(&mut self, key: KeyEvent) {
+        let input = self.input.as_mut().expect("no active input");
+        match key.code {
+            KeyCode::Esc => {
+                if input.kind == InputKind::Filter {
+                    self.filter = input.previous_filter.clone();
+                    self.apply_filter();
+                }
+                self.input = None;
+            }
+            KeyCode::Enter => {
+                let input = self.input.take().expect("no active input");
+                match input.kind {
+                    InputKind::Filter => (),
+                    InputKind::Jump => self.jump_to(input.text.trim()),
+                    InputKind::Export => self.export_bookmarks(input.text.trim()),
+                }
+            }
+            KeyCode::Backspace => {
+                input.text.pop();
+                self.input_changed();
+            }
+            KeyCode::Char(c) => {
+                input.text.push(c);
+                self.input_changed();
+            }
+            _ => (),
+        }
+    }
+
+    fn start_input(&mut self, kind: InputKind, text: String) {
+        self.input = Some(Input {
+            kind,
+            text,
+            previous_filter: self.filter.clone(),
+        });
+    }
+
+    /// filters are applied while the user is typing
+    fn input_changed(&mut self) {
+        if let Some(input) = self.input.as_ref() {
+            if input.kind == InputKind::Filter {
+                self.filter = input.text.clone();
+                self.apply_filter();
+            }
+        }
+    }
+
+    fn apply_filter(&mut self) {
+        let selected_record = self.visible.get(self.selected).copied();
+        let filter = BrowserFilter::from(&self.filter[..]);
+        self.visible = self
+            .records
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !self.only_bookmarks || self.bookmarks.contains(index))
+            .filter(|(_, (_, record))| filter.matches(record))
+            .map(|(index, _)| index)
+            .collect();
+
+        // try to keep the selected record selected
+        let selected = selected_record
+            .and_then(|record| self.visible.iter().position(|index| *index == record))
+            .unwrap_or(0);
+        self.select(selected);
+    }
+
+    fn select(&mut self, index: usize) {
+        let index = index.min(self.visible.len().saturating_sub(1));
+        if index != self.selected {
+            self.detail_scroll = 0;
+        }
+        self.selected = index;
+    }
+
+    fn selected_record(&self) -> Option<(usize, &SerializedEvtxRecord<Value>)> {
+        self.visible
+            .get(self.selected)
+            .map(|index| (*index, &self.records[*index].1))
+    }
+
+    fn toggle_bookmark(&mut self) {
+        if let Some(index) = self.visible.get(self.selected).copied() {
+            if !self.bookmarks.remove(&index) {
+                self.bookmarks.insert(index);
+            }
+            self.select(self.selected.saturating_add(1));
+        }
+    }
+
+    fn toggle_detail_view(&mut self) {
+        self.detail_view = match self.detail_view {
+            DetailView::Json => DetailView::Xml,
+            DetailView::Xml => DetailView::Json,
+        };
+        if self.detail_view == DetailView::Xml && self.xml_records.is_none() && !self.xml_from_json
+        {
+            match self.read_xml_records() {
+                Ok(xml_records) => self.xml_records = Some(xml_records),
+                Err(why) => {
+                    self.status = format!("unable to read XML records: {why}");
+                    self.detail_view = DetailView::Json;
+                }
+            }
+        }
+        self.detail_scroll = 0;
+    }
+
+    fn read_xml_records(&self) -> Result<HashMap<(usize, u64), String>> {
+        let mut xml_records = HashMap::new();
+        for (index, source) in self.sources.iter().enumerate() {
+            for record in source.open()?.records().filter_map(|r| r.ok()) {
+                xml_records.insert((index, record.event_record_id), record.data);
+            }
+        }
+        Ok(xml_records)
+    }
+
+    /// jumps to the record with the specified record id, or to the first
+    /// record which has been created at or after the specified timestamp
+    fn jump_to(&mut self, target: &str) {
+        let position = if let Ok(record_id) = u64::from_str(target) {
+            self.visible
+                .iter()
+                .position(|index| self.records[*index].1.event_record_id == record_id)
+        } else {
+            match Rfc3339Datetime::from_str(target).and_then(|ts| ts.to_utc(&self.timezone)) {
+                Err(why) => {
+                    self.status = format!("invalid record id or timestamp '{target}': {why}");
+                    return;
+                }
+                Ok(timestamp) => self
+                    .visible
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, index)| self.records[**index].1.timestamp >= timestamp)
+                    .min_by_key(|(_, index)| self.records[**index].1.timestamp)
+                    .map(|(position, _)| position),
+            }
+        };
+
+        match position {
+            Some(position) => self.select(position),
+            None => self.status = format!("no matching record found for '{target}'"),
+        }
+    }
+
+    fn export_bookmarks(&mut self, filename: &str) {
+        self.status = match self.write_bookmarks(filename) {
+            Ok(()) => format!("exported {} records to {filename}", self.bookmarks.len()),
+            Err(why) => format!("unable to export records to {filename}: {why}"),
+        };
+    }
+
+    /// writes all bookmarked records as JSON lines
+    fn write_bookmarks(&self, filename: &str) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(filename)?);
+        for index in self.bookmarks.iter() {
+            let (source, record) = &self.records[*index];
+            let line = json!({
+                "source": self.sources[*source].to_string(),
+                "event_record_id": record.event_record_id,
+                "timestamp": self.timestamp_formatter.format(&record.timestamp),
+                "data": record.data,
+            });
+            serde_json::to_writer(&mut writer, &line)?;
+            writeln!(writer)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// the contents of the selected record, including ANSI color codes
+    fn detail_text(&self) -> String {
+        match self.selected_record() {
+            None => String::new(),
+            Some((index, record)) => match self.detail_view {
+                DetailView::Json => {
+                    let data = self.hs_builder.highlight_data(&record.data);
+                    let json = serde_json::to_string_pretty(&data).unwrap_or_default();
+                    restore_escape_sequences(&json)
+                }
+                DetailView::Xml if self.xml_from_json => self
+                    .control_characters
+                    .sanitize(&record_to_xml(&record.data))
+                    .into_owned(),
+                DetailView::Xml => self
+                    .xml_records
+                    .as_ref()
+                    .and_then(|xml| xml.get(&(self.records[index].0, record.event_record_id)))
+                    .map(|xml| self.control_characters.sanitize(xml).into_owned())
+                    .unwrap_or_default(),
+            },
+        }
+    }
+}
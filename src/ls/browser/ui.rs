@@ -0,0 +1,162 @@
+use ansi_to_tui::IntoText;
+use evtx::SerializedEvtxRecord;
+use libevtx::EvtxSource;
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Text},
+    widgets::{Block, Paragraph, Row, Table, TableState, Wrap},
+    Frame,
+};
+use serde_json::Value;
+
+use super::{Browser, DetailView, InputKind};
+use crate::ls::{FilterBySystemField, SystemField};
+
+const HELP: &str = "q:quit  j/k:move  J/K:scroll  /:filter  ':':jump  b:bookmark  B:only bookmarks  e:export  x:JSON/XML";
+
+pub(super) fn draw(frame: &mut Frame, browser: &mut Browser) {
+    let [list_area, detail_area, status_area] = Layout::vertical([
+        Constraint::Percentage(40),
+        Constraint::Min(3),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    draw_list(frame, browser, list_area);
+    draw_detail(frame, browser, detail_area);
+    draw_status(frame, browser, status_area);
+}
+
+fn draw_list(frame: &mut Frame, browser: &mut Browser, area: Rect) {
+    // borders and header
+    browser.page_size = usize::from(area.height.saturating_sub(3)).max(1);
+    if browser.selected < browser.offset {
+        browser.offset = browser.selected;
+    } else if browser.selected >= browser.offset + browser.page_size {
+        browser.offset = browser.selected + 1 - browser.page_size;
+    }
+
+    let show_source = EvtxSource::need_tagging(browser.sources);
+    let rows: Vec<_> = browser
+        .visible
+        .iter()
+        .skip(browser.offset)
+        .take(browser.page_size)
+        .map(|index| {
+            let (source, record) = &browser.records[*index];
+            let mut cells = vec![
+                if browser.bookmarks.contains(index) {
+                    "*".to_owned()
+                } else {
+                    " ".to_owned()
+                },
+                browser.timestamp_formatter.format(&record.timestamp),
+                record.event_record_id.to_string(),
+                system_field(browser, record, SystemField::EventId),
+                system_field(browser, record, SystemField::Provider),
+            ];
+            if show_source {
+                cells.push(browser.sources[*source].to_string());
+            }
+            Row::new(cells)
+        })
+        .collect();
+
+    let mut header = vec!["", "timestamp", "record id", "event id", "provider"];
+    let mut widths = vec![
+        Constraint::Length(1),
+        Constraint::Length(
+            browser
+                .timestamp_formatter
+                .format(&chrono::DateTime::UNIX_EPOCH)
+                .len()
+                .try_into()
+                .unwrap_or(u16::MAX),
+        ),
+        Constraint::Length(10),
+        Constraint::Length(8),
+        Constraint::Fill(1),
+    ];
+    if show_source {
+        header.push("source");
+        widths.push(Constraint::Fill(1));
+    }
+
+    let title = format!(
+        " {} of {} records{} ",
+        browser.visible.len(),
+        browser.records.len(),
+        if browser.filter.is_empty() {
+            "".to_owned()
+        } else {
+            format!(", filter: '{}'", browser.filter)
+        }
+    );
+
+    let table = Table::new(rows, widths)
+        .header(Row::new(header).style(Style::new().add_modifier(Modifier::BOLD)))
+        .block(Block::bordered().title(title))
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+
+    let mut state = TableState::default();
+    if !browser.visible.is_empty() {
+        state.select(Some(browser.selected - browser.offset));
+    }
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+fn system_field(
+    browser: &Browser,
+    record: &SerializedEvtxRecord<Value>,
+    field: SystemField,
+) -> String {
+    <SerializedEvtxRecord<Value> as FilterBySystemField>::filter_fields(record, &[field])
+        .map(|fields| {
+            browser
+                .control_characters
+                .sanitize(fields[0].to_string().trim())
+                .into_owned()
+        })
+        .unwrap_or_default()
+}
+
+fn draw_detail(frame: &mut Frame, browser: &Browser, area: Rect) {
+    let title = match browser.detail_view {
+        DetailView::Json => " JSON ",
+        DetailView::Xml => " XML ",
+    };
+    let text = browser.detail_text();
+    let text = text.into_text().unwrap_or_else(|_| Text::raw(text.clone()));
+    let detail = Paragraph::new(text)
+        .block(Block::bordered().title(title))
+        .wrap(Wrap { trim: false })
+        .scroll((browser.detail_scroll, 0));
+    frame.render_widget(detail, area);
+}
+
+fn draw_status(frame: &mut Frame, browser: &Browser, area: Rect) {
+    let line = match browser.input.as_ref() {
+        Some(input) => {
+            let prompt = match input.kind {
+                InputKind::Filter => "filter (e.g. 'event-id=4624 provider=... text'): ",
+                InputKind::Jump => "jump to record id or timestamp: ",
+                InputKind::Export => "export bookmarks to: ",
+            };
+            frame.set_cursor_position((
+                area.x
+                    .saturating_add((prompt.len() + input.text.chars().count()) as u16),
+                area.y,
+            ));
+            Line::raw(format!("{prompt}{}", input.text))
+        }
+        None => {
+            if browser.status.is_empty() {
+                Line::raw(HELP)
+            } else {
+                Line::raw(browser.status.clone())
+            }
+        }
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
@@ -0,0 +1,102 @@
+use std::fmt::Write;
+
+use serde_json::Value;
+
+const ATTRIBUTES: &str = "#attributes";
+const TEXT: &str = "#text";
+
+/// children of `EventData` which are no named `Data` elements
+const UNNAMED_EVENT_DATA: [&str; 2] = ["Data", "Binary"];
+
+/// converts the JSON representation of a record back into XML. This is used
+/// if the record data have been modified (e.g. pseudonymized), so that the
+/// XML view displays the same contents as the JSON view. The named `Data`
+/// elements of `EventData`, which are represented as fields, are restored
+pub(super) fn record_to_xml(data: &Value) -> String {
+    let mut xml = String::new();
+    match data.as_object() {
+        Some(elements) => {
+            for (name, value) in elements {
+                write_element(&mut xml, name, value, 0, false);
+            }
+        }
+        None => xml.push_str(&escape(&text_of(data))),
+    }
+    xml
+}
+
+fn write_element(xml: &mut String, name: &str, value: &Value, depth: usize, is_data: bool) {
+    let indent = "  ".repeat(depth);
+    let (tag, mut attributes) = if is_data {
+        ("Data", format!(" Name=\"{}\"", escape(name)))
+    } else {
+        (name, String::new())
+    };
+
+    match value {
+        Value::Array(values) => {
+            for value in values {
+                write_element(xml, name, value, depth, is_data);
+            }
+        }
+        // unnamed `Data` elements are represented as an array of texts
+        Value::Object(object)
+            if object.len() == 1 && object.get(TEXT).is_some_and(Value::is_array) =>
+        {
+            write_element(xml, name, &object[TEXT], depth, is_data);
+        }
+        Value::Object(object) => {
+            if let Some(Value::Object(values)) = object.get(ATTRIBUTES) {
+                for (name, value) in values {
+                    let _ = write!(attributes, " {name}=\"{}\"", escape(&text_of(value)));
+                }
+            }
+            let children: Vec<_> = object
+                .iter()
+                .filter(|(name, _)| *name != ATTRIBUTES && *name != TEXT)
+                .collect();
+            let text = object.get(TEXT).map(text_of).unwrap_or_default();
+
+            if children.is_empty() && text.is_empty() {
+                let _ = writeln!(xml, "{indent}<{tag}{attributes}/>");
+            } else if children.is_empty() {
+                let _ = writeln!(xml, "{indent}<{tag}{attributes}>{}</{tag}>", escape(&text));
+            } else {
+                let _ = writeln!(xml, "{indent}<{tag}{attributes}>");
+                if !text.is_empty() {
+                    let _ = writeln!(xml, "{indent}  {}", escape(&text));
+                }
+                let has_data = tag == "EventData";
+                for (name, value) in children {
+                    let is_data = has_data && !UNNAMED_EVENT_DATA.contains(&name.as_str());
+                    write_element(xml, name, value, depth + 1, is_data);
+                }
+                let _ = writeln!(xml, "{indent}</{tag}>");
+            }
+        }
+        Value::Null => {
+            let _ = writeln!(xml, "{indent}<{tag}{attributes}/>");
+        }
+        value => {
+            let text = escape(&text_of(value));
+            let _ = writeln!(xml, "{indent}<{tag}{attributes}>{text}</{tag}>");
+        }
+    }
+}
+
+fn text_of(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(values) => values.iter().map(text_of).collect::<Vec<_>>().join(", "),
+        value => value.to_string(),
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
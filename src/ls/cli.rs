@@ -1,6 +1,15 @@
+use std::path::PathBuf;
+
 use clap::{Parser, ValueEnum};
 
-use super::{Rfc3339Datetime, SystemField};
+use libevtx::{
+    InputArgs, PartitionTemplate, PseudonymArgs, SyslogTarget, DEFAULT_INDEX_PATTERN,
+    Rfc3339Datetime, TimeArgs,
+};
+use super::{
+    ControlCharacterMode, EventDataField, GroupByKey, OutputFormat, StatsFormat, SystemField,
+    SystemFieldFilter,
+};
 use regex::Regex;
 
 #[derive(ValueEnum, Clone)]
@@ -19,13 +28,81 @@ pub(crate) enum SortOrder {
 #[derive(Parser)]
 #[clap(author,version,about,long_about=None)]
 pub(crate) struct Cli {
-    /// Name of the evtx files to read from
+    /// Name of the evtx files to read from. Directories are searched
+    /// recursively, and evtx files inside of ZIP archives are read without
+    /// extracting them
     pub(crate) evtx_files: Vec<String>,
 
     /// use this delimiter instead of generating fixed space columns
     #[clap(short('d'), long("delimiter"))]
     pub(crate) delimiter: Option<char>,
 
+    /// write records in this format instead of displaying them as text
+    #[clap(
+        short('O'),
+        long("output-format"),
+        value_enum,
+        default_value_t=OutputFormat::Text,
+        conflicts_with_all=["stats", "interactive"])]
+    pub(crate) output_format: OutputFormat,
+
+    /// send records to the '_bulk' API of Elasticsearch or OpenSearch at
+    /// this URL (e.g. 'http://localhost:9200') instead of displaying them.
+    /// Records are converted to the Elastic Common Schema
+    #[clap(long("ingest"), conflicts_with_all=["output_format", "stats", "interactive"])]
+    pub(crate) ingest_url: Option<String>,
+
+    /// write records into this SQLite database instead of displaying them.
+    /// If the database already exists, new records are appended to it
+    #[clap(
+        long("sqlite"),
+        conflicts_with_all=["output_format", "ingest_url", "stats", "interactive"])]
+    pub(crate) sqlite_file: Option<PathBuf>,
+
+    /// write records into this Apache Parquet file instead of displaying them
+    #[clap(
+        long("parquet"),
+        conflicts_with_all=["output_format", "ingest_url", "sqlite_file", "stats", "interactive", "follow"])]
+    pub(crate) parquet_file: Option<PathBuf>,
+
+    /// write only records with this event id into the Parquet file, and store
+    /// every field of EventData or UserData in a separate column instead of
+    /// storing all fields in one map column
+    #[clap(long("parquet-event-id"), requires="parquet_file")]
+    pub(crate) parquet_event_id: Option<u16>,
+
+    /// send records as RFC 5424 syslog messages to '-' (stdout),
+    /// 'udp://<host>:<port>' or 'tcp://<host>:<port>'. The message is formatted
+    /// as specified by '--output-format', which must be 'cef', 'leef' or 'ecs'
+    #[clap(
+        long("syslog"),
+        conflicts_with_all=["ingest_url", "sqlite_file", "parquet_file", "stats", "interactive"])]
+    pub(crate) syslog_target: Option<SyslogTarget>,
+
+    /// write records into separate files instead of displaying them. The
+    /// names of the files are given by this template, e.g.
+    /// '{computer}/{channel}/{date}.jsonl'. Supported placeholders are
+    /// '{computer}', '{channel}', '{provider}', '{event_id}', '{date}',
+    /// '{year}', '{month}', '{day}', '{hour}' and '{evtx_file}'
+    #[clap(
+        long("partition"),
+        conflicts_with_all=["ingest_url", "sqlite_file", "parquet_file", "syslog_target", "stats", "interactive"])]
+    pub(crate) partition: Option<PartitionTemplate>,
+
+    /// name of the index which records are sent to. '{host}' and '{channel}'
+    /// are replaced by the computer name and the channel of the record
+    #[clap(long("index"), default_value=DEFAULT_INDEX_PATTERN, requires="ingest_url")]
+    pub(crate) index_pattern: String,
+
+    /// number of records which are sent in one single request
+    #[clap(long("bulk-size"), default_value_t=500, requires="ingest_url")]
+    pub(crate) bulk_size: usize,
+
+    /// how often a request is repeated if the cluster is not available or
+    /// overloaded. The delay between two attempts is doubled every time
+    #[clap(long("max-retries"), default_value_t=5, requires="ingest_url")]
+    pub(crate) max_retries: u32,
+
     /// List events with only the specified event ids, separated by ','
     #[clap(
         short('i'),
@@ -46,21 +123,84 @@ pub(crate) struct Cli {
     pub(crate) excluded_event_ids: Vec<u16>,
 
 
+    /// show only events where a system field has a specific value, specified
+    /// as '<FIELD>=<VALUE>' (e.g. 'channel=Security'). Values are compared case
+    /// insensitive. This option can be specified multiple times; if a field is
+    /// used in multiple filters, at least one of them must match
+    #[clap(short('w'), long("where"))]
+    pub(crate) system_field_filters: Vec<SystemFieldFilter>,
+
+    /// show only events where this regular expression matches any field. This
+    /// option can be specified multiple times; events are shown if at least one
+    /// of the patterns matches
+    #[clap(short('g'), long("grep"))]
+    pub(crate) search_patterns: Vec<String>,
+
+    /// interpret the patterns given with '--grep' as literal strings instead
+    /// of regular expressions
+    #[clap(short('L'), long("literal"), requires="search_patterns")]
+    pub(crate) search_literal: bool,
+
+    /// ignore case distinctions in the patterns given with '--grep'
+    #[clap(short('I'), long("ignore-case"), requires="search_patterns")]
+    pub(crate) search_ignore_case: bool,
+
+    /// search only in the specified fields, given by name (e.g. 'TargetUserName')
+    /// or by path (e.g. 'EventData.TargetUserName'). Using the name of an element
+    /// searches all of its children (e.g. 'EventData'). Multiple values must be
+    /// separated by ','
+    #[clap(
+        long("grep-fields"),
+        use_value_delimiter = true,
+        value_delimiter = ',',
+        requires = "search_patterns"
+    )]
+    pub(crate) search_fields: Vec<String>,
+
+    /// display the path of the field which matched the '--grep' pattern as
+    /// an additional column
+    #[clap(long("show-match"), requires="search_patterns")]
+    pub(crate) show_match: bool,
+
+    /// display only records which reference this account. The account can be
+    /// specified as SID, 'DOMAIN\name', 'name@domain' or as plain name.
+    /// SIDs, account names and NetBIOS and DNS domain names are matched
+    /// using the mappings found in all input files
+    #[clap(short('U'), long("identity"))]
+    pub(crate) identities: Vec<String>,
+
+    /// display the canonical names of all accounts referenced by a record as
+    /// an additional column. This cannot be combined with pseudonymization
+    #[clap(long("show-identities"), conflicts_with("key_file"))]
+    pub(crate) show_identities: bool,
+
     /// highlight interesting content using colors
     #[clap(short('c'), long("colors"))]
     pub(crate) display_colors: bool,
 
-    /// hide events older than the specified date (hint: use RFC 3339 syntax)
+    /// hide events older than the specified date (hint: use RFC 3339 syntax).
+    /// Dates without an offset are interpreted in the time zone given by '--timezone'
     #[clap(short('f'), long("from"))]
     pub(crate) not_before: Option<Rfc3339Datetime>,
 
-    /// hide events newer than the specified date (hint: use RFC 3339 syntax)
+    /// hide events newer than the specified date (hint: use RFC 3339 syntax).
+    /// Dates without an offset are interpreted in the time zone given by '--timezone'
     #[clap(short('t'), long("to"))]
     pub(crate) not_after: Option<Rfc3339Datetime>,
 
-    /// highlight event data based on this regular expression
+    /// highlight event data based on this regular expression. This option can
+    /// be specified multiple times
     #[clap(short('r'), long("regex"))]
-    pub(crate) highlight: Option<Regex>,
+    pub(crate) highlight: Vec<Regex>,
+
+    /// read highlighting rules from this YAML file instead of using the default rules
+    #[clap(long("highlight-rules"))]
+    pub(crate) highlight_rules: Option<PathBuf>,
+
+    /// how to display control characters and bidirectional formatting
+    /// characters found in event data
+    #[clap(long("control-chars"), value_enum, default_value_t=ControlCharacterMode::Escape)]
+    pub(crate) control_characters: ControlCharacterMode,
 
     /// sort order
     #[clap(short('s'), long("sort"), value_enum, default_value_t=SortOrder::Storage)]
@@ -81,6 +221,90 @@ pub(crate) struct Cli {
     /// specifying '--base-fields' without any values (which is not allowed, that's why there is this flag)
     #[clap(short('B'), long("hide-base-fields"), default_value_t=false)]
     pub (crate) hide_base_fields: bool,
+
+    /// display fields of EventData or UserData as separate columns. Nested fields
+    /// can be specified using a dotted path (e.g. 'EventXML.Param1'). The column
+    /// width can be appended after a colon (e.g. 'TargetUserName:30'); longer
+    /// values are truncated.
+    /// Multiple values must be separated by ','
+    #[clap(
+        short('F'),
+        long("fields"),
+        use_value_delimiter=true,
+        value_delimiter=',')]
+    pub (crate) event_data_fields: Vec<EventDataField>,
+
+    /// don't list records, but display how often a group of records occurs,
+    /// together with the time when it has been seen first and last
+    #[clap(long("stats"))]
+    pub (crate) stats: bool,
+
+    /// fields by which records are grouped in '--stats' mode. Use either names
+    /// of system fields (see '--base-fields') or names of EventData fields.
+    /// Multiple values must be separated by ','
+    #[clap(
+        long("group-by"),
+        use_value_delimiter=true,
+        value_delimiter=',',
+        default_value="provider,event-id",
+        requires="stats")]
+    pub (crate) group_by: Vec<GroupByKey>,
+
+    /// output format of '--stats'
+    #[clap(long("stats-format"), value_enum, default_value_t=StatsFormat::Table, requires="stats")]
+    pub (crate) stats_format: StatsFormat,
+
+    /// after all records have been displayed, keep watching the files and
+    /// display records as they are added. Files which are replaced by a
+    /// different log are displayed again from the beginning
+    #[clap(long("follow"), conflicts_with="stats")]
+    pub (crate) follow: bool,
+
+    /// in '--follow' mode, check for changes every this many milliseconds
+    #[clap(long("poll-interval"), default_value_t=1000, requires="follow")]
+    pub (crate) poll_interval: u64,
+
+    /// browse the records in an interactive terminal user interface
+    #[clap(long("interactive"), conflicts_with_all=["stats", "follow"])]
+    pub (crate) interactive: bool,
+
+    /// replace references to parameter messages (such as '%%1936') by their text
+    #[clap(short('P'), long("resolve-parameters"))]
+    pub (crate) resolve_parameters: bool,
+
+    /// display the meaning of logon types, status codes (such as
+    /// '0xC000006A') and RDP disconnect reasons next to their values
+    #[clap(short('D'), long("decode"))]
+    pub (crate) decode_codes: bool,
+
+    /// display the human readable message of events instead of the contents of
+    /// EventData or UserData, if there is a message template for the event
+    #[clap(short('M'), long("message"))]
+    pub (crate) display_message: bool,
+
+    /// read additional message templates from this YAML file. This option can
+    /// be specified multiple times
+    #[clap(long("message-catalogue"), requires="display_message")]
+    pub (crate) message_catalogues: Vec<PathBuf>,
+
+    /// don't display the contents of EventData or UserData
+    #[clap(short('E'), long("hide-event-data"), default_value_t=false)]
+    pub (crate) hide_event_data: bool,
+
+    /// run this Rhai script for every record. Its function `filter(record)`
+    /// selects records, the output of `map(record)` replaces the display of
+    /// records, and the output of `finish()` is displayed at the end
+    #[clap(long("script"), conflicts_with="interactive")]
+    pub (crate) script: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub(crate) input: InputArgs,
+
+    #[clap(flatten)]
+    pub(crate) time: TimeArgs,
+
+    #[clap(flatten)]
+    pub(crate) pseudonym: PseudonymArgs,
 }
 
 
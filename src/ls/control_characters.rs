@@ -0,0 +1,103 @@
+use std::borrow::Cow;
+
+use clap::ValueEnum;
+use serde_json::Value;
+
+/// specifies how control characters and bidirectional formatting characters
+/// in event data are displayed. Such characters might be used to inject
+/// terminal escape sequences or to disguise the real content of a value.
+#[derive(ValueEnum, Clone, Copy, Default)]
+pub(crate) enum ControlCharacterMode {
+    /// replace those characters by their code point, e.g. '<U+001B>'
+    #[default]
+    Escape,
+
+    /// replace those characters by a visible symbol, e.g. '␛'
+    Mark,
+
+    /// display event data as it is. Be aware that this allows event data to
+    /// send escape sequences to your terminal
+    Raw,
+}
+
+impl ControlCharacterMode {
+    pub fn sanitize<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        if matches!(self, Self::Raw) || !s.chars().any(is_unsafe) {
+            return Cow::Borrowed(s);
+        }
+
+        let mut result = String::with_capacity(s.len());
+        for c in s.chars() {
+            if !is_unsafe(c) {
+                result.push(c);
+            } else {
+                match self {
+                    Self::Escape => result.push_str(&format!("<U+{:04X}>", c as u32)),
+                    Self::Mark => result.push(control_picture(c)),
+                    Self::Raw => unreachable!(),
+                }
+            }
+        }
+        Cow::Owned(result)
+    }
+}
+
+/// tests if any string in `value` contains control characters or
+/// bidirectional formatting characters
+pub(crate) fn contains_unsafe_characters(value: &Value) -> bool {
+    match value {
+        Value::String(s) => s.chars().any(is_unsafe),
+        Value::Array(a) => a.iter().any(contains_unsafe_characters),
+        Value::Object(o) => o
+            .iter()
+            .any(|(k, v)| k.chars().any(is_unsafe) || contains_unsafe_characters(v)),
+        _ => false,
+    }
+}
+
+/// JSON serialization escapes the ESC characters of our own color codes
+/// (as well as any other control character). This function reverts this for
+/// ESC, but leaves escaped backslashes intact, so that the literal text
+/// `\u001b` in event data does not become an escape sequence.
+pub(crate) fn restore_escape_sequences(json: &str) -> String {
+    let mut result = String::with_capacity(json.len());
+    let mut remaining = json;
+    while let Some(pos) = remaining.find('\\') {
+        result.push_str(&remaining[..pos]);
+        let escaped = &remaining[pos..];
+        if let Some(rest) = escaped.strip_prefix("\\u001b") {
+            result.push('\u{001b}');
+            remaining = rest;
+        } else {
+            // keep the backslash and the escaped character
+            let len = escaped[1..].chars().next().map(char::len_utf8).unwrap_or(0);
+            result.push_str(&escaped[..1 + len]);
+            remaining = &escaped[1 + len..];
+        }
+    }
+    result.push_str(remaining);
+    result
+}
+
+/// line breaks and tabs are escaped during JSON serialization, so they are
+/// not considered to be unsafe
+fn is_unsafe(c: char) -> bool {
+    (c.is_control() && !matches!(c, '\t' | '\n' | '\r')) || is_bidi_control(c)
+}
+
+fn is_bidi_control(c: char) -> bool {
+    matches!(c,
+        '\u{061C}' |                // ARABIC LETTER MARK
+        '\u{200E}' | '\u{200F}' |   // LEFT-TO-RIGHT MARK, RIGHT-TO-LEFT MARK
+        '\u{202A}'..='\u{202E}' |   // embeddings and overrides
+        '\u{2066}'..='\u{2069}'     // isolates
+    )
+}
+
+fn control_picture(c: char) -> char {
+    match c as u32 {
+        code @ 0x00..=0x1f => char::from_u32(0x2400 + code).unwrap(),
+        0x7f => '\u{2421}',
+        _ => '\u{FFFD}',
+    }
+}
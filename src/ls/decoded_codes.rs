@@ -0,0 +1,44 @@
+use eventdata::{DisconnectReason, EventId, LogonType, NtStatus, ProviderName};
+use evtx::SerializedEvtxRecord;
+use serde_json::Value;
+
+/// events of the Security log whose fields `Status` and `SubStatus` contain
+/// `NTSTATUS` codes. Other events use these names for other codes, e.g.
+/// Kerberos result codes
+const NT_STATUS_EVENTS: &[u16] = &[4625, 4776, 4777];
+
+/// replaces the numeric codes in a record (logon types, `NTSTATUS` codes and
+/// RDP disconnect reasons) by a string which contains both the original
+/// value and its meaning, e.g. `10` becomes `"10 (RemoteInteractive)"`
+pub(crate) fn decode_codes(record: &mut SerializedEvtxRecord<Value>) {
+    let logon_type = LogonType::try_from(&*record).ok();
+    let has_nt_status = ProviderName::try_from(&*record)
+        .is_ok_and(|p| p.value() == "Microsoft-Windows-Security-Auditing")
+        && EventId::try_from(&*record).is_ok_and(|id| NT_STATUS_EVENTS.contains(&id.value()));
+    let (status, sub_status) = if has_nt_status {
+        (
+            NtStatus::from_event_data(record, "Status").ok(),
+            NtStatus::from_event_data(record, "SubStatus").ok(),
+        )
+    } else {
+        (None, None)
+    };
+    let disconnect_reason = DisconnectReason::try_from(&*record).ok();
+
+    let event = &mut record.data["Event"];
+    if let Some(event_data) = event.get_mut("EventData").and_then(Value::as_object_mut) {
+        if let Some(logon_type) = logon_type {
+            event_data.insert("LogonType".into(), logon_type.to_string().into());
+        }
+        if let Some(status) = status {
+            event_data.insert("Status".into(), status.to_string().into());
+        }
+        if let Some(sub_status) = sub_status {
+            event_data.insert("SubStatus".into(), sub_status.to_string().into());
+        }
+    }
+
+    if let Some(reason) = disconnect_reason {
+        event["UserData"]["EventXML"]["Reason"] = reason.to_string().into();
+    }
+}
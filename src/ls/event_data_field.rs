@@ -0,0 +1,87 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::bail;
+use evtx::SerializedEvtxRecord;
+use serde_json::Value;
+
+const DEFAULT_FIELD_WIDTH: usize = 20;
+
+/// a field from `EventData` or `UserData` which should be displayed as a
+/// separate column. Nested fields can be specified using a dotted path, such
+/// as `EventXML.Param1`. Optionally, the column width can be appended after
+/// a colon, such as `TargetUserName:30`. Longer values are truncated
+#[derive(Clone)]
+pub(crate) struct EventDataField {
+    name: String,
+    path: Vec<String>,
+    width: usize,
+}
+
+impl FromStr for EventDataField {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, width) = match s.rsplit_once(':') {
+            Some((name, width)) => (name, width.parse()?),
+            None => (s, DEFAULT_FIELD_WIDTH),
+        };
+
+        if name.is_empty() || name.split('.').any(|p| p.is_empty()) {
+            bail!("invalid field name: '{name}'");
+        }
+
+        Ok(Self {
+            name: name.to_owned(),
+            path: name.split('.').map(|p| p.to_owned()).collect(),
+            width,
+        })
+    }
+}
+
+impl Display for EventDataField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.name.fmt(f)
+    }
+}
+
+impl EventDataField {
+    /// the last part of the field path, which is the name of the XML element
+    pub fn leaf_name(&self) -> &str {
+        self.path.last().unwrap()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// searches the field in `EventData`, `UserData` and the child element
+    /// of `UserData` (typically `EventXML`). If none of them contains the
+    /// field, the path is treated as being relative to `Event`.
+    pub fn value_of<'a>(&self, record: &'a SerializedEvtxRecord<Value>) -> Option<&'a Value> {
+        let event = &record.data["Event"];
+        let mut candidates = vec![&event["EventData"], &event["UserData"]];
+        if let Some(user_data) = event["UserData"].as_object() {
+            candidates.extend(user_data.values());
+        }
+        candidates.push(event);
+
+        candidates
+            .into_iter()
+            .filter_map(|base| self.path.iter().try_fold(base, |v, p| v.get(p)))
+            .find(|v| !v.is_null())
+    }
+
+    /// returns the field value as plain text, or `None` if the record does
+    /// not contain this field
+    pub fn text_of(&self, record: &SerializedEvtxRecord<Value>) -> Option<String> {
+        self.value_of(record).map(|value| match value {
+            Value::String(s) => s.to_owned(),
+            Value::Object(o) => match o.get("#text") {
+                Some(Value::String(s)) => s.to_owned(),
+                Some(v) => v.to_string(),
+                None => value.to_string(),
+            },
+            v => v.to_string(),
+        })
+    }
+}
@@ -0,0 +1,150 @@
+use std::{fs, path::Path, time::SystemTime};
+
+use libevtx::EvtxSource;
+
+/// an evtx file which is watched for new records in `--follow` mode
+pub(crate) struct FollowedFile {
+    source: EvtxSource,
+    fingerprint: Option<FileFingerprint>,
+    first_record_id: Option<u64>,
+    last_record_id: Option<u64>,
+
+    /// the fingerprint of the file when it has been found to contain fewer
+    /// records than before. If the file does not change until the next poll,
+    /// it has really been replaced; otherwise it was still being copied
+    shrunk: Option<FileFingerprint>,
+}
+
+/// cheap indicator whether a file has been changed. Both the size and the
+/// modification time change if the file is replaced by a newer copy.
+#[derive(PartialEq, Eq, Clone, Copy)]
+struct FileFingerprint {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+/// describes how the contents of a file have changed since it has been read
+/// the last time
+pub(crate) enum FileUpdate {
+    /// new records have been added to the file (and old records might have
+    /// been overwritten). Only records with an id greater than
+    /// `last_record_id` have not been seen before
+    Appended { last_record_id: Option<u64> },
+
+    /// the file has been replaced by a different log, e.g. because the log
+    /// has been cleared. All records have to be considered as new
+    Rewritten,
+
+    /// the file contains fewer records than before, but starts with the same
+    /// record. It is probably still being copied, so it must be read again
+    /// during the next poll
+    Incomplete,
+}
+
+impl FollowedFile {
+    pub fn new(source: EvtxSource) -> Self {
+        let fingerprint = FileFingerprint::of(source.container());
+        Self {
+            source,
+            fingerprint,
+            first_record_id: None,
+            last_record_id: None,
+            shrunk: None,
+        }
+    }
+
+    pub fn source(&self) -> &EvtxSource {
+        &self.source
+    }
+
+    /// checks if the file (or the archive which contains it) has been modified
+    /// since the last call. A file which cannot be accessed (e.g. because it
+    /// is just being copied) is regarded as unchanged.
+    pub fn has_changed(&mut self) -> bool {
+        match FileFingerprint::of(self.source.container()) {
+            None => false,
+            Some(fingerprint) => {
+                if self.fingerprint == Some(fingerprint) {
+                    false
+                } else {
+                    self.fingerprint = Some(fingerprint);
+                    true
+                }
+            }
+        }
+    }
+
+    /// forgets the state of the file, so that it will be read again during
+    /// the next poll. This is used if reading the file failed.
+    pub fn invalidate(&mut self) {
+        self.fingerprint = None;
+    }
+
+    /// stores the range of record ids found while reading the file, and
+    /// tells which of the records are new
+    pub fn update(
+        &mut self,
+        first_record_id: Option<u64>,
+        last_record_id: Option<u64>,
+    ) -> FileUpdate {
+        // event record ids are strictly increasing. Old records might be
+        // overwritten in a circular log, so the first id may increase as well,
+        // but the last id will never decrease, and the first id will only
+        // change if old records have been overwritten
+        let first_changed = match (self.first_record_id, first_record_id) {
+            (Some(old), Some(new)) => {
+                new < old || (new > old && shrunk(self.last_record_id, last_record_id))
+            }
+            _ => false,
+        };
+
+        let update = if first_changed {
+            FileUpdate::Rewritten
+        } else if shrunk(self.last_record_id, last_record_id) {
+            // a file which is being copied can be read successfully, but
+            // misses the records at its end. Only if the file doesn't change
+            // until the next poll, it has really been replaced by a shorter
+            // log, e.g. because a log which never wrapped has been cleared
+            if self.fingerprint.is_some() && self.shrunk == self.fingerprint {
+                FileUpdate::Rewritten
+            } else {
+                self.shrunk = self.fingerprint;
+                self.invalidate();
+                return FileUpdate::Incomplete;
+            }
+        } else {
+            FileUpdate::Appended {
+                last_record_id: self.last_record_id,
+            }
+        };
+        self.shrunk = None;
+
+        if matches!(update, FileUpdate::Rewritten) {
+            self.first_record_id = first_record_id;
+            self.last_record_id = last_record_id;
+        } else {
+            self.first_record_id = first_record_id.or(self.first_record_id);
+            self.last_record_id = last_record_id.or(self.last_record_id);
+        }
+        update
+    }
+}
+
+/// tests if a file contains fewer records than when it was read before
+fn shrunk(old_last_record_id: Option<u64>, new_last_record_id: Option<u64>) -> bool {
+    match (old_last_record_id, new_last_record_id) {
+        (Some(old), Some(new)) => new < old,
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
+impl FileFingerprint {
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
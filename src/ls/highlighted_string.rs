@@ -1,33 +1,41 @@
-use std::{net::Ipv4Addr, str::FromStr, collections::HashMap};
+use std::{collections::HashMap, fmt::Display};
 
 use colored::{ColoredString, Colorize};
-use lazy_regex::regex;
-use regex::Regex;
+use eventdata::truncate;
 use serde::Serialize;
 use serde_json::{Value, json};
 
+use super::{ControlCharacterMode, HighlightingRule};
+
 pub (crate) struct HighlightedStringBuilder {
-    custom_regex: Option<Regex>
+    rules: Vec<HighlightingRule>,
+    control_characters: ControlCharacterMode,
 }
 
 impl HighlightedStringBuilder {
-    pub fn new(regex: Option<Regex>) -> Self {
+    pub fn new(rules: Vec<HighlightingRule>, control_characters: ControlCharacterMode) -> Self {
         Self {
-            custom_regex: regex
+            rules,
+            control_characters
         }
     }
 
     pub fn highlight_data(&self, data: &Value) -> Value {
+        self.highlight_field(data, None)
+    }
+
+    fn highlight_field(&self, data: &Value, field: Option<&str>) -> Value {
         match data {
-            Value::String(s) => json!(self.build_from(&s[..])),
+            Value::String(s) => json!(self.build_from(&s[..], field)),
             Value::Array(o) => {
-                let s: Vec<_> = o.iter().map(|d| self.highlight_data(d)).collect();
+                let s: Vec<_> = o.iter().map(|d| self.highlight_field(d, field)).collect();
                 json!(s)
             }
             Value::Object(o) => {
                 let s: HashMap<_, _> = o.iter()
                     .map(|(k,v)| (
-                        k, json!(self.highlight_data(v))
+                        self.control_characters.sanitize(k),
+                        json!(self.highlight_field(v, Some(k)))
                     )).collect();
                 json!(s)
             },
@@ -36,49 +44,20 @@ impl HighlightedStringBuilder {
         }
     }
 
-    pub fn build_from(&self, s: &str) -> HighlightedString {
-        let ip_regex = regex!(r"\b(?:(?:25[0-5]|2[0-4][0-9]|[01]?[0-9][0-9]?)\.){3}(?:25[0-5]|2[0-4][0-9]|[01]?[0-9][0-9]?)\b");
-        let file_regex = regex!(r"[a-zA-Z]:\\\\?(?:[^\\]+\\\\?)+");
-        if ip_regex.is_match(s) {
-            for c in ip_regex.captures_iter(s) {
-                for m in c.iter().flatten() {
-                    let ip_addr = match Ipv4Addr::from_str(m.as_str()) {
-                        Ok(addr) => addr,
-                        Err(_) => {
-                            log::warn!("invalid IP address: {}, don't highlighting it", m.as_str());
-                            continue;
-                        }
-                    };
-                    
-                    if ip_addr.is_link_local() || ip_addr.is_loopback() || ip_addr.is_unspecified() {
-                        continue;
-                    }
-                    if ip_addr.is_private() {
-                        return s.bright_purple().into();
-                    }
-    
-                    //if ip_addr.is_global() {
-                        return s.red().on_bright_yellow().into();
-                    //}
-                }
-            }
-        }
-    
-        if file_regex.is_match(s) {
-            return s.bright_green().into()
-        }
-    
-        if s.to_lowercase().contains("admin") {
-            return s.bright_yellow().on_red().into()
-        }
+    pub fn build_from(&self, s: &str, field: Option<&str>) -> HighlightedString {
+        self.build_truncated(s, field, usize::MAX)
+    }
 
-        if let Some(regex) = self.custom_regex.as_ref() {
-            if regex.is_match(s) {
-                return s.blue().on_bright_white().into()
-            }
+    /// like [`Self::build_from`], but the displayed text is cut at `width`
+    /// characters, where the last character is replaced by an ellipsis.
+    /// Highlighting rules are matched against the complete string
+    pub fn build_truncated(&self, s: &str, field: Option<&str>, width: usize) -> HighlightedString {
+        let sanitized = self.control_characters.sanitize(s);
+        let rendered = truncate(&sanitized, width);
+        match self.rules.iter().find(|rule| rule.matches(s, field)) {
+            Some(rule) => rule.apply(&rendered).into(),
+            None => rendered.bright_blue().into()
         }
-    
-        s.bright_blue().into()
     }
 }
 
@@ -86,6 +65,19 @@ pub (crate) struct HighlightedString {
     cstring: ColoredString
 }
 
+impl HighlightedString {
+    /// the number of characters which are displayed, not counting any color codes
+    pub fn display_width(&self) -> usize {
+        self.cstring.chars().count()
+    }
+}
+
+impl Display for HighlightedString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.cstring.fmt(f)
+    }
+}
+
 impl From<ColoredString> for HighlightedString {
     fn from(cstring: ColoredString) -> Self {
         Self {
@@ -101,4 +93,4 @@ impl Serialize for HighlightedString {
         let s = self.cstring.to_string();
         serializer.serialize_str(&s)
     }
-}
\ No newline at end of file
+}
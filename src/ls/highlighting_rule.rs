@@ -0,0 +1,268 @@
+use std::{
+    fs::File,
+    net::{Ipv4Addr, Ipv6Addr},
+    path::Path,
+    str::FromStr,
+};
+
+use anyhow::Context;
+use colored::{Color, ColoredString, Colorize};
+use lazy_regex::regex;
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+
+static DEFAULT_RULES: &str = include_str!("default_highlighting_rules.yaml");
+
+/// a single highlighting rule. Rules are evaluated in the order they are
+/// specified, the first matching rule determines how a value is displayed.
+#[derive(Deserialize)]
+pub(crate) struct HighlightingRule {
+    #[serde(flatten)]
+    matcher: RuleMatcher,
+
+    /// names of the fields this rule applies to. If this is empty, the rule
+    /// applies to all fields
+    #[serde(default)]
+    fields: Vec<String>,
+
+    #[serde(flatten)]
+    style: HighlightStyle,
+
+    /// optional text which is displayed next to a matching value
+    label: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub(crate) enum RuleMatcher {
+    /// matches if the value matches a regular expression
+    Regex {
+        #[serde(deserialize_with = "deserialize_regex")]
+        pattern: Regex,
+    },
+
+    /// matches if the value contains any of the keywords
+    Keywords {
+        keywords: Vec<String>,
+
+        #[serde(default)]
+        case_sensitive: bool,
+    },
+
+    /// matches if the value contains an IPv4 address. Link-local, loopback
+    /// and unspecified addresses are ignored.
+    Ipv4 {
+        #[serde(default)]
+        scope: IpScope,
+    },
+
+    /// matches if the value contains an IPv6 address. Link-local, loopback
+    /// and unspecified addresses are ignored.
+    Ipv6 {
+        #[serde(default)]
+        scope: IpScope,
+    },
+
+    /// matches if the value contains a security identifier, such as `S-1-5-18`
+    Sid,
+
+    /// matches if the value contains an URL
+    Url,
+
+    /// matches if the value contains a MD5, SHA1 or SHA256 hash value
+    Hash,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum IpScope {
+    Private,
+    Public,
+
+    #[default]
+    Any,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TextStyle {
+    Bold,
+    Dimmed,
+    Italic,
+    Underline,
+    Blink,
+    Reversed,
+    Strikethrough,
+}
+
+#[derive(Deserialize, Default)]
+pub(crate) struct HighlightStyle {
+    #[serde(default, deserialize_with = "deserialize_color")]
+    color: Option<Color>,
+
+    #[serde(default, deserialize_with = "deserialize_color")]
+    background: Option<Color>,
+
+    #[serde(default)]
+    styles: Vec<TextStyle>,
+}
+
+impl HighlightingRule {
+    /// the rules which are used if the user does not specify a rules file
+    pub fn default_rules() -> Vec<Self> {
+        serde_yaml::from_str(DEFAULT_RULES).expect("invalid default highlighting rules")
+    }
+
+    /// reads an ordered list of rules from a YAML file
+    pub fn load_from(path: &Path) -> anyhow::Result<Vec<Self>> {
+        let file = File::open(path)
+            .with_context(|| format!("unable to open rules file {}", path.display()))?;
+        serde_yaml::from_reader(file)
+            .with_context(|| format!("invalid rules file {}", path.display()))
+    }
+
+    /// tests if this rule applies to the value `s`, which has been found in
+    /// the field named `field`
+    pub fn matches(&self, s: &str, field: Option<&str>) -> bool {
+        if !self.fields.is_empty() {
+            match field {
+                None => return false,
+                Some(field) => {
+                    if !self.fields.iter().any(|f| f.eq_ignore_ascii_case(field)) {
+                        return false;
+                    }
+                }
+            }
+        }
+        self.matcher.matches(s)
+    }
+
+    pub fn apply(&self, s: &str) -> ColoredString {
+        match self.label.as_ref() {
+            Some(label) => self.style.apply(&format!("{s} [{label}]")),
+            None => self.style.apply(s),
+        }
+    }
+}
+
+/// a user supplied regular expression, as specified using `--regex`
+impl From<Regex> for HighlightingRule {
+    fn from(pattern: Regex) -> Self {
+        Self {
+            matcher: RuleMatcher::Regex { pattern },
+            fields: Vec::new(),
+            style: HighlightStyle {
+                color: Some(Color::Blue),
+                background: Some(Color::BrightWhite),
+                styles: Vec::new(),
+            },
+            label: None,
+        }
+    }
+}
+
+impl RuleMatcher {
+    fn matches(&self, s: &str) -> bool {
+        match self {
+            Self::Regex { pattern } => pattern.is_match(s),
+            Self::Keywords {
+                keywords,
+                case_sensitive,
+            } => {
+                if *case_sensitive {
+                    keywords.iter().any(|k| s.contains(k))
+                } else {
+                    let s = s.to_lowercase();
+                    keywords.iter().any(|k| s.contains(&k.to_lowercase()))
+                }
+            }
+            Self::Ipv4 { scope } => {
+                let ip_regex = regex!(r"\b(?:(?:25[0-5]|2[0-4][0-9]|[01]?[0-9][0-9]?)\.){3}(?:25[0-5]|2[0-4][0-9]|[01]?[0-9][0-9]?)\b");
+                ip_regex
+                    .find_iter(s)
+                    .filter_map(|m| match Ipv4Addr::from_str(m.as_str()) {
+                        Ok(addr) => Some(addr),
+                        Err(_) => {
+                            log::warn!("invalid IP address: {}, don't highlighting it", m.as_str());
+                            None
+                        }
+                    })
+                    .filter(|addr| {
+                        !(addr.is_link_local() || addr.is_loopback() || addr.is_unspecified())
+                    })
+                    .any(|addr| scope.contains(addr.is_private()))
+            }
+            Self::Ipv6 { scope } => {
+                let ip_regex = regex!(r"(?i)[0-9a-f]{0,4}(?::[0-9a-f]{0,4}){2,7}");
+                ip_regex
+                    .find_iter(s)
+                    .filter_map(|m| Ipv6Addr::from_str(m.as_str()).ok())
+                    .filter(|addr| {
+                        let is_link_local = (addr.segments()[0] & 0xffc0) == 0xfe80;
+                        !(is_link_local || addr.is_loopback() || addr.is_unspecified())
+                    })
+                    .any(|addr| {
+                        let is_unique_local = (addr.segments()[0] & 0xfe00) == 0xfc00;
+                        scope.contains(is_unique_local)
+                    })
+            }
+            Self::Sid => regex!(r"\bS-1-\d+(?:-\d+)+\b").is_match(s),
+            Self::Url => regex!(r#"(?i)\b(?:https?|ftp|file|smb|ldaps?)://[^\s"'<>]+"#).is_match(s),
+            Self::Hash => {
+                regex!(r"(?i)\b(?:[0-9a-f]{32}|[0-9a-f]{40}|[0-9a-f]{64})\b").is_match(s)
+            }
+        }
+    }
+}
+
+impl IpScope {
+    fn contains(&self, is_private: bool) -> bool {
+        match self {
+            Self::Private => is_private,
+            Self::Public => !is_private,
+            Self::Any => true,
+        }
+    }
+}
+
+impl HighlightStyle {
+    fn apply(&self, s: &str) -> ColoredString {
+        let mut cstring = ColoredString::from(s);
+        if let Some(color) = self.color {
+            cstring = cstring.color(color);
+        }
+        if let Some(color) = self.background {
+            cstring = cstring.on_color(color);
+        }
+        for style in self.styles.iter() {
+            cstring = match style {
+                TextStyle::Bold => cstring.bold(),
+                TextStyle::Dimmed => cstring.dimmed(),
+                TextStyle::Italic => cstring.italic(),
+                TextStyle::Underline => cstring.underline(),
+                TextStyle::Blink => cstring.blink(),
+                TextStyle::Reversed => cstring.reversed(),
+                TextStyle::Strikethrough => cstring.strikethrough(),
+            };
+        }
+        cstring
+    }
+}
+
+fn deserialize_regex<'de, D>(deserializer: D) -> Result<Regex, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let pattern = String::deserialize(deserializer)?;
+    Regex::new(&pattern).map_err(serde::de::Error::custom)
+}
+
+fn deserialize_color<'de, D>(deserializer: D) -> Result<Option<Color>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let color = String::deserialize(deserializer)?;
+    Color::from_str(&color.replace('_', " "))
+        .map(Some)
+        .map_err(|_| serde::de::Error::custom(format!("invalid color: '{color}'")))
+}
@@ -1,12 +1,36 @@
+mod browser;
+pub (crate) use browser::Browser;
+
 mod cli;
 pub (crate) use cli::*;
 
-mod rfc3339_datetime;
-pub (crate) use rfc3339_datetime::*;
-
 mod highlighted_string;
 pub (crate) use highlighted_string::*;
 
+mod highlighting_rule;
+pub (crate) use highlighting_rule::*;
+
+mod control_characters;
+pub (crate) use control_characters::*;
+
+mod followed_file;
+pub (crate) use followed_file::*;
+
+mod decoded_codes;
+pub (crate) use decoded_codes::*;
+
+mod event_data_field;
+pub (crate) use event_data_field::*;
+
+mod output_format;
+pub (crate) use output_format::*;
+
+mod record_search;
+pub (crate) use record_search::*;
+
+mod stats;
+pub (crate) use stats::*;
+
 mod system_field;
 pub (crate) use system_field::*;
 
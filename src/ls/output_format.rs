@@ -0,0 +1,192 @@
+use std::io::{stdout, Stdout, Write};
+
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+use evtx::SerializedEvtxRecord;
+use libevtx::{
+    cef_line, ecs_document, leef_line, BulkIngester, EvtxSource, ParquetWriter, PartitionedWriter,
+    SqliteWriter, SyslogWriter, TimesketchEvent, TimesketchFormat, TimesketchWriter,
+};
+use serde_json::Value;
+
+use super::Cli;
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    /// one line of text per record
+    Text,
+
+    /// JSON lines which can be imported into Timesketch
+    TimesketchJsonl,
+
+    /// CSV which can be imported into Timesketch. Because the header must
+    /// contain all fields, records are written after all files have been read
+    TimesketchCsv,
+
+    /// JSON lines which follow the Elastic Common Schema, using the field
+    /// names of Winlogbeat
+    Ecs,
+
+    /// ArcSight Common Event Format
+    Cef,
+
+    /// IBM Log Event Extended Format
+    Leef,
+}
+
+/// where records are written to, which are exported one per line
+pub(crate) enum LineOutput {
+    Stdout(Stdout),
+    Syslog(Box<SyslogWriter>),
+    Partitioned(Box<PartitionedWriter>),
+}
+
+/// writes records in a machine readable format, instead of displaying them
+pub(crate) enum RecordExporter {
+    Timesketch(Box<TimesketchWriter<Stdout>>),
+    Lines(OutputFormat, LineOutput),
+    Ingest(Box<BulkIngester>),
+    Sqlite(Box<SqliteWriter>),
+    Parquet(Box<ParquetWriter>),
+}
+
+impl RecordExporter {
+    /// returns `None` if records should be displayed as text
+    pub fn new(cli: &Cli) -> Result<Option<Self>> {
+        if let Some(url) = cli.ingest_url.as_ref() {
+            return Ok(Some(Self::Ingest(Box::new(
+                BulkIngester::new(url, &cli.index_pattern)
+                    .with_batch_size(cli.bulk_size)
+                    .with_max_retries(cli.max_retries),
+            ))));
+        }
+        if let Some(path) = cli.sqlite_file.as_ref() {
+            return Ok(Some(Self::Sqlite(Box::new(SqliteWriter::open(path)?))));
+        }
+        if let Some(path) = cli.parquet_file.as_ref() {
+            return Ok(Some(Self::Parquet(Box::new(ParquetWriter::create(
+                path,
+                cli.parquet_event_id,
+            )?))));
+        }
+
+        if let Some(target) = cli.syslog_target.as_ref() {
+            if !matches!(
+                cli.output_format,
+                OutputFormat::Cef | OutputFormat::Leef | OutputFormat::Ecs
+            ) {
+                bail!("'--syslog' requires one of the output formats 'cef', 'leef' or 'ecs'");
+            }
+            return Ok(Some(Self::Lines(
+                cli.output_format,
+                LineOutput::Syslog(Box::new(SyslogWriter::connect(target)?)),
+            )));
+        }
+
+        if let Some(template) = cli.partition.as_ref() {
+            return Ok(match cli.output_format {
+                // text is written by evtxls itself
+                OutputFormat::Text => None,
+                OutputFormat::TimesketchCsv => {
+                    bail!("'--partition' cannot be used with the output format 'timesketch-csv'")
+                }
+                _ => Some(Self::Lines(
+                    cli.output_format,
+                    LineOutput::Partitioned(Box::new(PartitionedWriter::new(
+                        template.clone(),
+                        cli.time.timezone.clone(),
+                    ))),
+                )),
+            });
+        }
+
+        Ok(match cli.output_format {
+            OutputFormat::Text => None,
+            OutputFormat::TimesketchJsonl => Some(Self::Timesketch(Box::new(TimesketchWriter::new(
+                stdout(),
+                TimesketchFormat::Jsonl,
+            )))),
+            OutputFormat::TimesketchCsv => Some(Self::Timesketch(Box::new(TimesketchWriter::new(
+                stdout(),
+                TimesketchFormat::Csv,
+            )))),
+            OutputFormat::Ecs | OutputFormat::Cef | OutputFormat::Leef => Some(Self::Lines(
+                cli.output_format,
+                LineOutput::Stdout(stdout()),
+            )),
+        })
+    }
+
+    /// exports one record. `message` is the human readable message of the
+    /// record, if it is known
+    pub fn export(
+        &mut self,
+        source: &EvtxSource,
+        record: &SerializedEvtxRecord<Value>,
+        message: Option<String>,
+    ) -> Result<()> {
+        match self {
+            Self::Timesketch(writer) => {
+                writer.write(TimesketchEvent::from_record(record, Some(source), message))
+            }
+            Self::Lines(format, output) => {
+                let line = match format {
+                    OutputFormat::Cef => cef_line(record, Some(source), message.as_deref()),
+                    OutputFormat::Leef => leef_line(record, Some(source), message.as_deref()),
+                    OutputFormat::TimesketchJsonl | OutputFormat::TimesketchCsv => {
+                        TimesketchEvent::from_record(record, Some(source), message)
+                            .to_json()
+                            .to_string()
+                    }
+                    _ => {
+                        let mut document = ecs_document(record, Some(source));
+                        if let Some(message) = message {
+                            document["message"] = message.into();
+                        }
+                        document.to_string()
+                    }
+                };
+                match output {
+                    LineOutput::Stdout(stdout) => Ok(writeln!(stdout, "{line}")?),
+                    LineOutput::Syslog(writer) => writer.send(record, &line),
+                    LineOutput::Partitioned(writer) => {
+                        writer.write_line(source, record, None, &line)
+                    }
+                }
+            }
+            Self::Ingest(ingester) => ingester.add(source, record, message),
+            Self::Sqlite(writer) => writer.write(source, record, message),
+            Self::Parquet(writer) => writer.write(source, record, message),
+        }
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        match self {
+            Self::Timesketch(writer) => writer.flush(),
+            Self::Lines(_, LineOutput::Stdout(stdout)) => Ok(stdout.flush()?),
+            Self::Lines(_, LineOutput::Syslog(writer)) => writer.flush(),
+            Self::Lines(_, LineOutput::Partitioned(writer)) => writer.flush(),
+            Self::Ingest(ingester) => {
+                ingester.flush()?;
+                eprintln!("{}", ingester.summary());
+                Ok(())
+            }
+            Self::Sqlite(writer) => {
+                writer.flush()?;
+                eprintln!(
+                    "{} records have been added to the database, {} records were already stored",
+                    writer.inserted(),
+                    writer.skipped()
+                );
+                Ok(())
+            }
+
+            // Parquet files cannot be extended, so this is only called once
+            Self::Parquet(writer) => {
+                writer.finish()?;
+                eprintln!("{} records have been written", writer.written());
+                Ok(())
+            }
+        }
+    }
+}
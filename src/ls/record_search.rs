@@ -0,0 +1,101 @@
+use evtx::SerializedEvtxRecord;
+use regex::{Regex, RegexBuilder};
+use serde_json::Value;
+
+/// filters records by searching for a pattern in their fields, like `grep`
+/// would do with the text representation of a record
+pub(crate) struct RecordSearch {
+    pattern: Regex,
+    fields: Vec<String>,
+}
+
+impl RecordSearch {
+    /// creates a search which matches if any of `patterns` matches. If
+    /// `literal` is set, the patterns are not interpreted as regular
+    /// expressions. If `fields` is not empty, only fields with one of these
+    /// names are searched; a field can be specified by its name (such as
+    /// `TargetUserName`) or by its path (such as `EventData.TargetUserName`).
+    /// Specifying an element name searches all of its children and attributes
+    /// (e.g. `EventData` or `Provider`)
+    pub fn new(
+        patterns: &[String],
+        literal: bool,
+        ignore_case: bool,
+        fields: Vec<String>,
+    ) -> anyhow::Result<Self> {
+        let alternatives: Vec<_> = patterns
+            .iter()
+            .map(|p| {
+                if literal {
+                    regex::escape(p)
+                } else {
+                    format!("(?:{p})")
+                }
+            })
+            .collect();
+
+        let pattern = RegexBuilder::new(&alternatives.join("|"))
+            .case_insensitive(ignore_case)
+            .build()?;
+        Ok(Self { pattern, fields })
+    }
+
+    pub fn pattern(&self) -> &Regex {
+        &self.pattern
+    }
+
+    /// returns the path of the first field of the record which matches the
+    /// search pattern, or `None` if no field matches
+    pub fn find_match(&self, record: &SerializedEvtxRecord<Value>) -> Option<String> {
+        let mut path = Vec::new();
+        record
+            .data
+            .get("Event")
+            .and_then(|event| self.find_in(event, &mut path))
+    }
+
+    fn find_in<'v>(&self, value: &'v Value, path: &mut Vec<&'v str>) -> Option<String> {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter() {
+                    // attributes and text nodes are regarded as part of
+                    // their containing element
+                    let is_pseudo_key = key == "#attributes" || key == "#text";
+                    if !is_pseudo_key {
+                        path.push(key);
+                    }
+                    let result = self.find_in(value, path);
+                    if !is_pseudo_key {
+                        path.pop();
+                    }
+                    if result.is_some() {
+                        return result;
+                    }
+                }
+                None
+            }
+            Value::Array(values) => values.iter().find_map(|v| self.find_in(v, path)),
+            Value::Null => None,
+            Value::String(s) => self.match_field(s, path),
+            v => self.match_field(&v.to_string(), path),
+        }
+    }
+
+    fn match_field(&self, s: &str, path: &[&str]) -> Option<String> {
+        if !self.fields.is_empty() {
+            let full_path = path.join(".");
+            if !self.fields.iter().any(|f| {
+                f.eq_ignore_ascii_case(&full_path)
+                    || path.iter().any(|element| f.eq_ignore_ascii_case(element))
+            }) {
+                return None;
+            }
+        }
+
+        if self.pattern.is_match(s) {
+            Some(path.join("."))
+        } else {
+            None
+        }
+    }
+}
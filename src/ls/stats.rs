@@ -0,0 +1,216 @@
+use std::{collections::HashMap, fmt::Display, io::stdout, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use evtx::SerializedEvtxRecord;
+use libevtx::TimestampFormatter;
+use serde_json::{json, Map, Value};
+
+use super::{ControlCharacterMode, EventDataField, FilterBySystemField, SystemField};
+
+/// a criterion by which records are grouped. This can either be a system
+/// field (such as `event-id`) or a field from `EventData` or `UserData`
+#[derive(Clone)]
+pub(crate) enum GroupByKey {
+    System(SystemField),
+    EventData(EventDataField),
+}
+
+impl FromStr for GroupByKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match SystemField::from_str(s, true) {
+            Ok(field) => Ok(Self::System(field)),
+            Err(_) => Ok(Self::EventData(EventDataField::from_str(s)?)),
+        }
+    }
+}
+
+impl Display for GroupByKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::System(field) => field
+                .to_possible_value()
+                .expect("no skipped values")
+                .get_name()
+                .fmt(f),
+            Self::EventData(field) => field.fmt(f),
+        }
+    }
+}
+
+impl GroupByKey {
+    fn value_of(&self, record: &SerializedEvtxRecord<Value>) -> anyhow::Result<String> {
+        match self {
+            Self::System(field) => {
+                let fields = <SerializedEvtxRecord<Value> as FilterBySystemField>::filter_fields(
+                    record,
+                    &[*field],
+                )?;
+                Ok(fields[0].to_string())
+            }
+            Self::EventData(field) => Ok(field.text_of(record).unwrap_or_default()),
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub(crate) enum StatsFormat {
+    /// display a table with fixed width columns
+    Table,
+
+    /// comma separated values
+    Csv,
+
+    /// one JSON array with one object per group
+    Json,
+}
+
+struct GroupStats {
+    count: usize,
+    first_seen: DateTime<Utc>,
+    last_seen: DateTime<Utc>,
+}
+
+/// counts records per group, and stores when a group has been seen first and last
+pub(crate) struct Statistics {
+    keys: Vec<GroupByKey>,
+    control_characters: ControlCharacterMode,
+    groups: HashMap<Vec<String>, GroupStats>,
+}
+
+impl Statistics {
+    pub fn new(keys: Vec<GroupByKey>, control_characters: ControlCharacterMode) -> Self {
+        Self {
+            keys,
+            control_characters,
+            groups: HashMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, record: &SerializedEvtxRecord<Value>) -> anyhow::Result<()> {
+        let mut group = Vec::with_capacity(self.keys.len());
+        for key in self.keys.iter() {
+            let value = key.value_of(record)?;
+            group.push(self.control_characters.sanitize(&value).into_owned());
+        }
+
+        self.groups
+            .entry(group)
+            .and_modify(|stats| {
+                stats.count += 1;
+                stats.first_seen = stats.first_seen.min(record.timestamp);
+                stats.last_seen = stats.last_seen.max(record.timestamp);
+            })
+            .or_insert(GroupStats {
+                count: 1,
+                first_seen: record.timestamp,
+                last_seen: record.timestamp,
+            });
+        Ok(())
+    }
+
+    /// all groups, with the most frequent group first
+    fn sorted_groups(&self) -> Vec<(&Vec<String>, &GroupStats)> {
+        let mut groups: Vec<_> = self.groups.iter().collect();
+        groups.sort_by(|a, b| {
+            b.1.count
+                .cmp(&a.1.count)
+                .then_with(|| a.1.first_seen.cmp(&b.1.first_seen))
+        });
+        groups
+    }
+
+    fn header(&self) -> Vec<String> {
+        let mut header: Vec<_> = self.keys.iter().map(|k| k.to_string()).collect();
+        header.extend(["count", "first_seen", "last_seen"].map(String::from));
+        header
+    }
+
+    fn rows(&self, formatter: &TimestampFormatter) -> Vec<Vec<String>> {
+        self.sorted_groups()
+            .into_iter()
+            .map(|(group, stats)| {
+                let mut row = group.clone();
+                row.push(stats.count.to_string());
+                row.push(formatter.format(&stats.first_seen));
+                row.push(formatter.format(&stats.last_seen));
+                row
+            })
+            .collect()
+    }
+
+    pub fn display(&self, format: StatsFormat, formatter: &TimestampFormatter) -> anyhow::Result<()> {
+        match format {
+            StatsFormat::Table => self.display_table(formatter),
+            StatsFormat::Csv => self.display_csv(formatter)?,
+            StatsFormat::Json => self.display_json(formatter)?,
+        }
+        Ok(())
+    }
+
+    fn display_table(&self, formatter: &TimestampFormatter) {
+        let header = self.header();
+        let rows = self.rows(formatter);
+        let widths: Vec<_> = header
+            .iter()
+            .enumerate()
+            .map(|(idx, h)| {
+                rows.iter()
+                    .map(|r| r[idx].chars().count())
+                    .chain(std::iter::once(h.chars().count()))
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        let count_column = self.keys.len();
+        for row in std::iter::once(&header).chain(rows.iter()) {
+            let line: Vec<_> = row
+                .iter()
+                .zip(widths.iter())
+                .enumerate()
+                .map(|(idx, (value, width))| {
+                    if idx == count_column {
+                        format!("{value:>width$}")
+                    } else {
+                        format!("{value:width$}")
+                    }
+                })
+                .collect();
+            println!("{}", line.join("  ").trim_end());
+        }
+    }
+
+    fn display_csv(&self, formatter: &TimestampFormatter) -> anyhow::Result<()> {
+        let mut csv_writer = csv::Writer::from_writer(stdout());
+        csv_writer.write_record(self.header())?;
+        for row in self.rows(formatter) {
+            csv_writer.write_record(row)?;
+        }
+        csv_writer.flush()?;
+        Ok(())
+    }
+
+    fn display_json(&self, formatter: &TimestampFormatter) -> anyhow::Result<()> {
+        let groups: Vec<_> = self
+            .sorted_groups()
+            .into_iter()
+            .map(|(group, stats)| {
+                let mut object: Map<_, _> = self
+                    .keys
+                    .iter()
+                    .zip(group.iter())
+                    .map(|(k, v)| (k.to_string(), json!(v)))
+                    .collect();
+                object.insert("count".to_owned(), json!(stats.count));
+                object.insert("first_seen".to_owned(), json!(formatter.format(&stats.first_seen)));
+                object.insert("last_seen".to_owned(), json!(formatter.format(&stats.last_seen)));
+                Value::Object(object)
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&groups)?);
+        Ok(())
+    }
+}
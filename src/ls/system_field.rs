@@ -1,11 +1,17 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
+use anyhow::anyhow;
 use clap::ValueEnum;
-use eventdata::{EvtxFieldView, EventId, EventRecordId, ActivityId, RelatedActivityId, ProcessId, PROCESS_ID_MAX_LENGTH};
+use eventdata::{
+    EvtxFieldView, EventId, EventRecordId, ActivityId, RelatedActivityId, ProcessId, PROCESS_ID_MAX_LENGTH,
+    ProviderName, PROVIDER_NAME_MAX_LENGTH, Channel, CHANNEL_MAX_LENGTH, Computer, COMPUTER_MAX_LENGTH,
+    Level, LEVEL_MAX_LENGTH, Task, TASK_MAX_LENGTH, Opcode, OPCODE_MAX_LENGTH, Keywords, KEYWORDS_MAX_LENGTH,
+    ThreadId, THREAD_ID_MAX_LENGTH, Version, VERSION_MAX_LENGTH, UserId, USER_ID_MAX_LENGTH,
+};
 use evtx::SerializedEvtxRecord;
 use serde_json::Value;
 
-#[derive(ValueEnum, Clone)]
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
 #[allow(clippy::enum_variant_names)]
 pub (crate) enum SystemField {
     /// The identifier that the provider used to identify the event
@@ -21,7 +27,37 @@ pub (crate) enum SystemField {
     RelatedActivityId,
 
     /// The ID of the process that created the event
-    ProcessId
+    ProcessId,
+
+    /// The name of the event provider
+    Provider,
+
+    /// The name of the channel to which the event was logged
+    Channel,
+
+    /// The name of the computer on which the event occurred
+    Computer,
+
+    /// The severity level of the event
+    Level,
+
+    /// The task or category of the event
+    Task,
+
+    /// The opcode of the event
+    Opcode,
+
+    /// A bitmask of the keywords defined for the event
+    Keywords,
+
+    /// The ID of the thread that created the event
+    ThreadId,
+
+    /// The version of the event definition
+    Version,
+
+    /// The security identifier of the user in whose context the event was logged
+    UserId,
 }
 
 pub (crate) trait FilterBySystemField {
@@ -43,6 +79,16 @@ impl FilterBySystemField for SerializedEvtxRecord<Value> {
                         _ => result.push(Box::new(EmptyField::with_size(PROCESS_ID_MAX_LENGTH)))    
                     }
                 }
+                SystemField::Provider => result.push(optional_field::<ProviderName>(record, PROVIDER_NAME_MAX_LENGTH)),
+                SystemField::Channel => result.push(optional_field::<Channel>(record, CHANNEL_MAX_LENGTH)),
+                SystemField::Computer => result.push(optional_field::<Computer>(record, COMPUTER_MAX_LENGTH)),
+                SystemField::Level => result.push(optional_field::<Level>(record, LEVEL_MAX_LENGTH)),
+                SystemField::Task => result.push(optional_field::<Task>(record, TASK_MAX_LENGTH)),
+                SystemField::Opcode => result.push(optional_field::<Opcode>(record, OPCODE_MAX_LENGTH)),
+                SystemField::Keywords => result.push(optional_field::<Keywords>(record, KEYWORDS_MAX_LENGTH)),
+                SystemField::ThreadId => result.push(optional_field::<ThreadId>(record, THREAD_ID_MAX_LENGTH)),
+                SystemField::Version => result.push(optional_field::<Version>(record, VERSION_MAX_LENGTH)),
+                SystemField::UserId => result.push(optional_field::<UserId>(record, USER_ID_MAX_LENGTH)),
             }
         }
 
@@ -50,6 +96,59 @@ impl FilterBySystemField for SerializedEvtxRecord<Value> {
     }
 }
 
+/// not all events contain all system fields, so we display an empty field
+/// if some value is missing
+fn optional_field<'a, F>(record: &'a SerializedEvtxRecord<Value>, size: usize) -> Box<dyn EvtxFieldView + 'a>
+where
+    F: EvtxFieldView + TryFrom<&'a SerializedEvtxRecord<Value>> + 'a,
+{
+    match F::try_from(record) {
+        Ok(f) => Box::new(f),
+        _ => Box::new(EmptyField::with_size(size)),
+    }
+}
+
+/// a filter which hides all events where a system field does not have the
+/// expected value. It is specified as `<FIELD>=<VALUE>`, e.g. `channel=Security`
+#[derive(Clone)]
+pub (crate) struct SystemFieldFilter {
+    field: SystemField,
+    value: String,
+}
+
+impl FromStr for SystemFieldFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, value) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow!("invalid filter '{s}', expected '<FIELD>=<VALUE>'"))?;
+        let field = SystemField::from_str(field.trim(), true)
+            .map_err(|why| anyhow!("invalid field name in filter '{s}': {why}"))?;
+        Ok(Self {
+            field,
+            value: value.trim().to_owned(),
+        })
+    }
+}
+
+impl SystemFieldFilter {
+    /// tests if `record` passes all filters. If there are multiple filters
+    /// for the same field, at least one of them must match.
+    pub fn matches_all(filters: &[Self], record: &SerializedEvtxRecord<Value>) -> anyhow::Result<bool> {
+        for filter in filters.iter() {
+            let field_filters: Vec<_> = filters.iter().filter(|f| f.field == filter.field).collect();
+            let field = <SerializedEvtxRecord<Value> as FilterBySystemField>::filter_fields(record, &[filter.field])?;
+            let value = field[0].to_string();
+            let value = value.trim();
+            if !field_filters.iter().any(|f| f.value.eq_ignore_ascii_case(value)) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
 struct EmptyField {
     size: usize
 }
@@ -63,10 +162,16 @@ impl EmptyField {
 }
 
 impl Display for EmptyField {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", " ".repeat(self.size))
+    fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Ok(())
     }
 }
 impl EvtxFieldView for EmptyField {
+    fn maximum_display_length(&self) -> usize {
+        self.size
+    }
 
+    fn value_with_padding(&self) -> String {
+        " ".repeat(self.size)
+    }
 }
@@ -0,0 +1,179 @@
+use std::{fs::File, path::Path};
+
+use anyhow::Context;
+use eventdata::{ProviderName, Version};
+use evtx::SerializedEvtxRecord;
+use serde::Deserialize;
+use serde_json::Value;
+
+static BUNDLED_TEMPLATES: &str = include_str!("message_catalogue.yaml");
+
+/// a template for the human readable message of an event, as Windows
+/// displays it in the event viewer
+#[derive(Deserialize, Clone)]
+pub struct MessageTemplate {
+    provider: String,
+    event_id: u16,
+
+    /// if this is `None`, the template applies to all versions of the event
+    #[serde(default)]
+    version: Option<u8>,
+
+    message: String,
+}
+
+/// collection of message templates, keyed by provider, event id and version
+#[derive(Clone)]
+pub struct MessageCatalogue {
+    /// the templates of every file, starting with the file which takes
+    /// precedence. The bundled templates are always last
+    layers: Vec<Vec<MessageTemplate>>,
+}
+
+impl Default for MessageCatalogue {
+    fn default() -> Self {
+        Self::bundled()
+    }
+}
+
+impl MessageCatalogue {
+    /// the catalogue which is part of this crate, which contains templates for
+    /// the most common events of the Security and System logs, Sysmon,
+    /// Terminal Services and PowerShell
+    pub fn bundled() -> Self {
+        Self {
+            layers: vec![serde_yaml::from_str(BUNDLED_TEMPLATES)
+                .expect("invalid bundled message catalogue")],
+        }
+    }
+
+    /// creates the bundled catalogue, extended by the templates found in
+    /// `files`. Templates from files which are specified later take precedence
+    pub fn with_user_files<P: AsRef<Path>>(files: &[P]) -> anyhow::Result<Self> {
+        let mut catalogue = Self::bundled();
+        for file in files {
+            catalogue.load_from(file.as_ref())?;
+        }
+        Ok(catalogue)
+    }
+
+    /// adds the templates from a YAML file. They take precedence over all
+    /// templates which already are in the catalogue, even if those match the
+    /// version of an event and the new templates don't
+    pub fn load_from(&mut self, path: &Path) -> anyhow::Result<()> {
+        let file = File::open(path)
+            .with_context(|| format!("unable to open message catalogue {}", path.display()))?;
+        let templates: Vec<MessageTemplate> = serde_yaml::from_reader(file)
+            .with_context(|| format!("invalid message catalogue {}", path.display()))?;
+        self.layers.insert(0, templates);
+        Ok(())
+    }
+
+    pub fn find(&self, provider: &str, event_id: u16, version: Option<u8>) -> Option<&MessageTemplate> {
+        self.layers.iter().find_map(|templates| {
+            let mut candidates = templates.iter().filter(|t| {
+                t.event_id == event_id && t.provider.eq_ignore_ascii_case(provider)
+            });
+            let exact_match = candidates
+                .clone()
+                .find(|t| t.version.is_some() && t.version == version);
+            exact_match.or_else(|| candidates.find(|t| t.version.is_none()))
+        })
+    }
+
+    /// renders the message of `record`, or returns `None` if there is no
+    /// template for this event
+    pub fn render(&self, record: &SerializedEvtxRecord<Value>) -> Option<String> {
+        let provider = ProviderName::try_from(record).ok()?;
+        let event_id = eventdata::EventId::try_from(record).ok()?;
+        let version = Version::try_from(record).ok().map(u8::from);
+        self.find(provider.value(), event_id.value(), version)
+            .map(|template| template.render(record))
+    }
+}
+
+impl MessageTemplate {
+    pub fn render(&self, record: &SerializedEvtxRecord<Value>) -> String {
+        let mut result = String::with_capacity(self.message.len());
+        let mut chars = self.message.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    result.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    result.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    let mut is_terminated = false;
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            is_terminated = true;
+                            break;
+                        }
+                        name.push(c);
+                    }
+                    if is_terminated {
+                        result.push_str(&insertion_string(record, &name).unwrap_or_default());
+                    } else {
+                        result.push('{');
+                        result.push_str(&name);
+                    }
+                }
+                c => result.push(c),
+            }
+        }
+        result
+    }
+}
+
+/// finds the value of an insertion string, which is either a field name or
+/// the (1-based) position of the value in `EventData`
+fn insertion_string(record: &SerializedEvtxRecord<Value>, name: &str) -> Option<String> {
+    let event = record.data.get("Event")?;
+    let value = match name.parse::<usize>() {
+        Ok(position) => positional_data(event.get("EventData")?, position.checked_sub(1)?)?,
+        Err(_) => match event.get("EventData").and_then(|data| data.get(name)) {
+            Some(value) => value,
+
+            // events which use UserData store their fields in a child
+            // element, such as `EventXML` or `LogFileCleared`
+            None => event
+                .get("UserData")?
+                .as_object()?
+                .values()
+                .find_map(|child| child.get(name))?,
+        },
+    };
+    Some(value_to_string(value))
+}
+
+fn positional_data(event_data: &Value, index: usize) -> Option<&Value> {
+    let data = event_data.get("Data")?;
+    let data = data.get("#text").unwrap_or(data);
+    match data {
+        Value::Array(values) => values.get(index),
+        v if index == 0 => Some(v),
+        _ => None,
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.to_owned(),
+        Value::Array(values) => values
+            .iter()
+            .map(value_to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+        Value::Object(o) => match o.get("#text") {
+            Some(text) => value_to_string(text),
+            None => value.to_string(),
+        },
+        v => v.to_string(),
+    }
+}
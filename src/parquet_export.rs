@@ -0,0 +1,300 @@
+use std::{collections::BTreeMap, fs::File, path::Path, sync::Arc};
+
+use arrow_array::{
+    builder::{MapBuilder, StringBuilder},
+    ArrayRef, RecordBatch, StringArray, TimestampMicrosecondArray, UInt16Array, UInt64Array,
+    UInt8Array,
+};
+use arrow_schema::{Field, Schema};
+use eventdata::{
+    Channel, Computer, EventId, Keywords, Level, Opcode, ProcessId, ProviderName, Task, ThreadId,
+    UserId,
+};
+use evtx::SerializedEvtxRecord;
+use parquet::{
+    arrow::ArrowWriter,
+    basic::Compression,
+    file::properties::WriterProperties,
+};
+use serde_json::Value;
+
+use crate::{flatten_custom_data, EvtxSource};
+
+/// the number of records which are buffered before they are written as one
+/// row group
+const ROW_GROUP_SIZE: usize = 65_536;
+
+/// the names of the columns which contain system fields
+const SYSTEM_COLUMNS: &[&str] = &[
+    "timestamp",
+    "event_record_id",
+    "event_id",
+    "provider",
+    "channel",
+    "computer",
+    "level",
+    "task",
+    "opcode",
+    "keywords",
+    "process_id",
+    "thread_id",
+    "user_id",
+    "evtx_file",
+    "message",
+];
+
+/// the name of the map column which contains `EventData` or `UserData`
+const EVENT_DATA_COLUMN: &str = "event_data";
+
+/// the contents of one row
+struct Row {
+    timestamp: i64,
+    event_record_id: u64,
+    event_id: Option<u16>,
+    provider: Option<String>,
+    channel: Option<String>,
+    computer: Option<String>,
+    level: Option<u8>,
+    task: Option<u16>,
+    opcode: Option<u8>,
+    keywords: Option<String>,
+    process_id: Option<u64>,
+    thread_id: Option<u64>,
+    user_id: Option<String>,
+    evtx_file: String,
+    message: Option<String>,
+    fields: BTreeMap<String, Value>,
+}
+
+/// writes records into an Apache Parquet file.
+///
+/// System fields are stored as typed columns. The fields of `EventData` and
+/// `UserData` are either stored as a map of strings in the column
+/// `event_data`, or, if only records of one single event id are written,
+/// as one string column per field. In this case, the columns are taken from
+/// the records of the first row group; fields which are not found there, or
+/// which have the name of a system column, are omitted.
+///
+/// Records are buffered and written in row groups of 65536 records, so the
+/// memory usage does not depend on the number of records
+pub struct ParquetWriter {
+    file: Option<File>,
+    writer: Option<ArrowWriter<File>>,
+
+    /// write records of this event id only, with flattened fields
+    event_id: Option<u16>,
+    columns: Vec<String>,
+
+    rows: Vec<Row>,
+    written: usize,
+}
+
+impl ParquetWriter {
+    /// creates a new file. If `event_id` is given, only records of this
+    /// event id are written, and their fields are stored in separate columns
+    pub fn create(path: &Path, event_id: Option<u16>) -> anyhow::Result<Self> {
+        Ok(Self {
+            file: Some(File::create(path)?),
+            writer: None,
+            event_id,
+            columns: Vec::new(),
+            rows: Vec::new(),
+            written: 0,
+        })
+    }
+
+    pub fn write(
+        &mut self,
+        source: &EvtxSource,
+        record: &SerializedEvtxRecord<Value>,
+        message: Option<String>,
+    ) -> anyhow::Result<()> {
+        let event_id = EventId::try_from(record).ok().map(u16::from);
+        if self.event_id.is_some() && self.event_id != event_id {
+            return Ok(());
+        }
+
+        self.rows.push(Row {
+            timestamp: record.timestamp.timestamp_micros(),
+            event_record_id: record.event_record_id,
+            event_id,
+            provider: ProviderName::try_from(record).ok().map(|p| p.to_string()),
+            channel: Channel::try_from(record).ok().map(|c| c.to_string()),
+            computer: Computer::try_from(record).ok().map(|c| c.to_string()),
+            level: Level::try_from(record).ok().map(|l| l.value()),
+            task: Task::try_from(record).ok().map(|t| t.0),
+            opcode: Opcode::try_from(record).ok().map(|o| o.0),
+            keywords: Keywords::try_from(record).ok().map(|k| k.to_string()),
+            process_id: ProcessId::try_from(record).ok().map(|p| p.0),
+            thread_id: ThreadId::try_from(record).ok().map(|t| t.0),
+            user_id: UserId::try_from(record).ok().map(|u| u.to_string()),
+            evtx_file: source.to_string(),
+            message,
+            fields: flatten_custom_data(record),
+        });
+
+        if self.rows.len() >= ROW_GROUP_SIZE {
+            self.write_row_group()?;
+        }
+        Ok(())
+    }
+
+    /// writes all buffered records and closes the file. Records which are
+    /// written afterwards are ignored
+    pub fn finish(&mut self) -> anyhow::Result<()> {
+        if self.file.is_some() || !self.rows.is_empty() {
+            self.write_row_group()?;
+        }
+        if let Some(writer) = self.writer.take() {
+            writer.close()?;
+        }
+        Ok(())
+    }
+
+    /// the number of records which have been written
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    fn write_row_group(&mut self) -> anyhow::Result<()> {
+        let rows = std::mem::take(&mut self.rows);
+
+        if self.event_id.is_some() && self.writer.is_none() {
+            let mut columns: Vec<&String> = rows
+                .iter()
+                .flat_map(|row| row.fields.keys())
+                .filter(|c| !SYSTEM_COLUMNS.contains(&c.as_str()))
+                .collect();
+            columns.sort();
+            columns.dedup();
+            self.columns = columns.into_iter().cloned().collect();
+        }
+
+        let batch = self.record_batch(&rows)?;
+        let writer = match self.writer.as_mut() {
+            Some(writer) => writer,
+            None => {
+                let file = match self.file.take() {
+                    Some(file) => file,
+                    None => return Ok(()),
+                };
+                let properties = WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .set_max_row_group_size(ROW_GROUP_SIZE)
+                    .build();
+                self.writer.insert(ArrowWriter::try_new(
+                    file,
+                    batch.schema(),
+                    Some(properties),
+                )?)
+            }
+        };
+        writer.write(&batch)?;
+        writer.flush()?;
+        self.written += rows.len();
+        Ok(())
+    }
+
+    fn record_batch(&self, rows: &[Row]) -> anyhow::Result<RecordBatch> {
+        fn strings<'r>(rows: &'r [Row], f: impl Fn(&'r Row) -> Option<&'r str>) -> ArrayRef {
+            Arc::new(rows.iter().map(f).collect::<StringArray>())
+        }
+
+        let mut columns: Vec<(&str, ArrayRef, bool)> = vec![
+            (
+                "timestamp",
+                Arc::new(
+                    TimestampMicrosecondArray::from_iter_values(rows.iter().map(|r| r.timestamp))
+                        .with_timezone("UTC"),
+                ),
+                false,
+            ),
+            (
+                "event_record_id",
+                Arc::new(UInt64Array::from_iter_values(rows.iter().map(|r| r.event_record_id))),
+                false,
+            ),
+            (
+                "event_id",
+                Arc::new(rows.iter().map(|r| r.event_id).collect::<UInt16Array>()),
+                true,
+            ),
+            ("provider", strings(rows, |r| r.provider.as_deref()), true),
+            ("channel", strings(rows, |r| r.channel.as_deref()), true),
+            ("computer", strings(rows, |r| r.computer.as_deref()), true),
+            (
+                "level",
+                Arc::new(rows.iter().map(|r| r.level).collect::<UInt8Array>()),
+                true,
+            ),
+            (
+                "task",
+                Arc::new(rows.iter().map(|r| r.task).collect::<UInt16Array>()),
+                true,
+            ),
+            (
+                "opcode",
+                Arc::new(rows.iter().map(|r| r.opcode).collect::<UInt8Array>()),
+                true,
+            ),
+            ("keywords", strings(rows, |r| r.keywords.as_deref()), true),
+            (
+                "process_id",
+                Arc::new(rows.iter().map(|r| r.process_id).collect::<UInt64Array>()),
+                true,
+            ),
+            (
+                "thread_id",
+                Arc::new(rows.iter().map(|r| r.thread_id).collect::<UInt64Array>()),
+                true,
+            ),
+            ("user_id", strings(rows, |r| r.user_id.as_deref()), true),
+            ("evtx_file", strings(rows, |r| Some(&r.evtx_file)), false),
+            ("message", strings(rows, |r| r.message.as_deref()), true),
+        ];
+
+        let mut field_values = Vec::new();
+        if self.event_id.is_some() {
+            for column in self.columns.iter() {
+                let values: StringArray = rows
+                    .iter()
+                    .map(|r| r.fields.get(column).map(value_to_string))
+                    .collect();
+                field_values.push((column.as_str(), values));
+            }
+        } else {
+            let mut builder = MapBuilder::new(None, StringBuilder::new(), StringBuilder::new());
+            for row in rows {
+                for (name, value) in row.fields.iter() {
+                    builder.keys().append_value(name);
+                    builder.values().append_value(value_to_string(value));
+                }
+                builder.append(true)?;
+            }
+            columns.push((EVENT_DATA_COLUMN, Arc::new(builder.finish()), false));
+        }
+        for (name, values) in field_values {
+            columns.push((name, Arc::new(values), true));
+        }
+
+        let schema = Schema::new(
+            columns
+                .iter()
+                .map(|(name, array, nullable)| {
+                    Field::new(*name, array.data_type().clone(), *nullable)
+                })
+                .collect::<Vec<_>>(),
+        );
+        Ok(RecordBatch::try_new(
+            Arc::new(schema),
+            columns.into_iter().map(|(_, array, _)| array).collect(),
+        )?)
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    }
+}
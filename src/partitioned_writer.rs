@@ -0,0 +1,268 @@
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fmt::Display,
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail};
+use eventdata::{Channel, Computer, EventId, ProviderName};
+use evtx::SerializedEvtxRecord;
+use serde_json::Value;
+
+use crate::{EvtxSource, TimeZoneSpec};
+
+/// files are closed when this many files are open, so that we don't run out
+/// of file handles. They are reopened if more records are written into them
+const MAX_OPEN_FILES: usize = 128;
+
+/// a value of a record which can be used in a [`PartitionTemplate`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Placeholder {
+    Computer,
+    Channel,
+    Provider,
+    EventId,
+    Date,
+    Year,
+    Month,
+    Day,
+    Hour,
+    EvtxFile,
+}
+
+impl FromStr for Placeholder {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "computer" => Self::Computer,
+            "channel" => Self::Channel,
+            "provider" => Self::Provider,
+            "event_id" => Self::EventId,
+            "date" => Self::Date,
+            "year" => Self::Year,
+            "month" => Self::Month,
+            "day" => Self::Day,
+            "hour" => Self::Hour,
+            "evtx_file" => Self::EvtxFile,
+            _ => bail!(
+                "unknown placeholder '{{{s}}}', use one of {{computer}}, {{channel}}, \
+                {{provider}}, {{event_id}}, {{date}}, {{year}}, {{month}}, {{day}}, {{hour}} \
+                or {{evtx_file}}"
+            ),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Segment {
+    Literal(String),
+    Placeholder(Placeholder),
+}
+
+/// the name of the file a record is written to, such as
+/// `{computer}/{channel}/{date}.jsonl`. Placeholders are replaced by the
+/// values of the record, where characters which are not allowed in file
+/// names are replaced by `_`
+#[derive(Clone, Debug)]
+pub struct PartitionTemplate {
+    template: String,
+    segments: Vec<Segment>,
+}
+
+impl FromStr for PartitionTemplate {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = Vec::new();
+        let mut rest = s;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                segments.push(Segment::Literal(rest[..start].to_owned()));
+            }
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| anyhow!("missing '}}' in '{s}'"))?;
+            segments.push(Segment::Placeholder(rest[start + 1..start + end].parse()?));
+            rest = &rest[start + end + 1..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Literal(rest.to_owned()));
+        }
+
+        if !segments
+            .iter()
+            .any(|s| matches!(s, Segment::Placeholder(_)))
+        {
+            bail!(
+                "'{s}' contains no placeholder, so all records would be written into the same file"
+            );
+        }
+        Ok(Self {
+            template: s.to_owned(),
+            segments,
+        })
+    }
+}
+
+impl Display for PartitionTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.template.fmt(f)
+    }
+}
+
+impl PartitionTemplate {
+    /// the path of the file `record` should be written to. Dates and times
+    /// are given in `timezone`
+    pub fn path_of(
+        &self,
+        source: &EvtxSource,
+        record: &SerializedEvtxRecord<Value>,
+        timezone: &TimeZoneSpec,
+    ) -> PathBuf {
+        let timestamp = timezone.from_utc(&record.timestamp);
+        let mut path = String::new();
+        for segment in self.segments.iter() {
+            let value = match segment {
+                Segment::Literal(literal) => {
+                    path.push_str(literal);
+                    continue;
+                }
+                Segment::Placeholder(placeholder) => match placeholder {
+                    Placeholder::Computer => Computer::try_from(record).ok().map(|c| c.to_string()),
+                    Placeholder::Channel => Channel::try_from(record).ok().map(|c| c.to_string()),
+                    Placeholder::Provider => {
+                        ProviderName::try_from(record).ok().map(|p| p.to_string())
+                    }
+                    Placeholder::EventId => EventId::try_from(record).ok().map(|id| id.to_string()),
+                    Placeholder::Date => Some(timestamp.format("%Y-%m-%d").to_string()),
+                    Placeholder::Year => Some(timestamp.format("%Y").to_string()),
+                    Placeholder::Month => Some(timestamp.format("%m").to_string()),
+                    Placeholder::Day => Some(timestamp.format("%d").to_string()),
+                    Placeholder::Hour => Some(timestamp.format("%H").to_string()),
+                    Placeholder::EvtxFile => Some(source.file_name().into_owned()),
+                },
+            };
+            path.push_str(&sanitize(value.as_deref().unwrap_or_default()));
+        }
+        PathBuf::from(path)
+    }
+}
+
+/// values must not create additional directories, so path separators and
+/// other special characters are replaced
+fn sanitize(value: &str) -> String {
+    let value: String = value
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '@' | ' ') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if value.is_empty() || value.chars().all(|c| c == '.') {
+        "unknown".to_owned()
+    } else {
+        value
+    }
+}
+
+/// writes records into different files, whose names are given by a
+/// [`PartitionTemplate`]. Directories are created as needed. Files which
+/// already exist are overwritten
+pub struct PartitionedWriter {
+    template: PartitionTemplate,
+    timezone: TimeZoneSpec,
+    files: HashMap<PathBuf, BufWriter<File>>,
+
+    /// all files which have been created, so that they can be reopened
+    created: HashSet<PathBuf>,
+}
+
+impl PartitionedWriter {
+    pub fn new(template: PartitionTemplate, timezone: TimeZoneSpec) -> Self {
+        Self {
+            template,
+            timezone,
+            files: HashMap::new(),
+            created: HashSet::new(),
+        }
+    }
+
+    /// returns the file `record` should be written to, and `true` if the
+    /// file has just been created, so that a header can be written
+    pub fn file_for(
+        &mut self,
+        source: &EvtxSource,
+        record: &SerializedEvtxRecord<Value>,
+    ) -> anyhow::Result<(&mut BufWriter<File>, bool)> {
+        let path = self.template.path_of(source, record, &self.timezone);
+
+        if !self.files.contains_key(&path) && self.files.len() >= MAX_OPEN_FILES {
+            self.close_all()?;
+        }
+
+        let is_new = !self.created.contains(&path);
+        let file = match self.files.entry(path) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let path = entry.key();
+                if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    fs::create_dir_all(parent).map_err(|why| {
+                        anyhow!("unable to create directory {}: {why}", parent.display())
+                    })?;
+                }
+                let file = if is_new {
+                    File::create(path)
+                } else {
+                    OpenOptions::new().append(true).open(path)
+                }
+                .map_err(|why| anyhow!("unable to open {}: {why}", path.display()))?;
+                self.created.insert(path.clone());
+                entry.insert(BufWriter::new(file))
+            }
+        };
+        Ok((file, is_new))
+    }
+
+    /// writes one line into the file of `record`. `header` is written
+    /// before, if the file has just been created
+    pub fn write_line(
+        &mut self,
+        source: &EvtxSource,
+        record: &SerializedEvtxRecord<Value>,
+        header: Option<&str>,
+        line: &str,
+    ) -> anyhow::Result<()> {
+        let (file, is_new) = self.file_for(source, record)?;
+        if let Some(header) = header.filter(|_| is_new) {
+            writeln!(file, "{header}")?;
+        }
+        writeln!(file, "{line}")?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> anyhow::Result<()> {
+        for file in self.files.values_mut() {
+            file.flush()?;
+        }
+        Ok(())
+    }
+
+    /// the number of files which have been written
+    pub fn files_written(&self) -> usize {
+        self.created.len()
+    }
+
+    fn close_all(&mut self) -> anyhow::Result<()> {
+        self.flush()?;
+        self.files.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::Pseudonymizer;
+
+/// command line options which control the pseudonymization of user names,
+/// SIDs, host names, domains and IP addresses
+#[derive(Args, Clone)]
+pub struct PseudonymArgs {
+    /// replace user names, SIDs, host names, domains and IP addresses by
+    /// pseudonyms, which are derived from the secret key stored in this file
+    /// (at least 16 bytes). The same key always yields the same pseudonyms
+    #[clap(long("pseudonymize"), value_name("KEY_FILE"), global = true)]
+    pub key_file: Option<PathBuf>,
+
+    /// write the mapping between original values and their pseudonyms as CSV
+    /// into this file, so that pseudonyms can be resolved later
+    #[clap(long("pseudonym-map"), requires("key_file"), global = true)]
+    pub mapping_file: Option<PathBuf>,
+}
+
+impl PseudonymArgs {
+    /// returns `None` if the user does not want to pseudonymize records
+    pub fn pseudonymizer(&self) -> anyhow::Result<Option<Pseudonymizer>> {
+        let pseudonymizer = match self.key_file.as_ref() {
+            Some(path) => Pseudonymizer::from_key_file(path)?,
+            None => return Ok(None),
+        };
+        Ok(Some(match self.mapping_file.as_ref() {
+            Some(path) => pseudonymizer.with_mapping_file(path.clone()),
+            None => pseudonymizer,
+        }))
+    }
+}
@@ -0,0 +1,623 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    fs::File,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    path::{Path, PathBuf},
+};
+
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
+use anyhow::{anyhow, bail};
+use evtx::SerializedEvtxRecord;
+use hmac::{Hmac, Mac};
+use lazy_regex::regex;
+use serde_json::Value;
+use sha2::Sha256;
+
+/// keys which are shorter than this could be found by brute force, which
+/// would reveal the original values of all pseudonyms
+const MIN_KEY_LENGTH: usize = 16;
+
+/// the number of bytes of the HMAC which are used for a pseudonym
+const TOKEN_LENGTH: usize = 6;
+
+/// the number of rounds of the Feistel network which permutes SIDs and IP
+/// addresses. It must be even
+const FEISTEL_ROUNDS: u8 = 8;
+
+/// shorter names are not searched in free text, because they would be found
+/// in too many unrelated words
+const MIN_NAME_LENGTH: usize = 3;
+
+/// accounts which exist on every system, and which don't identify anybody
+const WELL_KNOWN_USERS: &[&str] = &[
+    "-",
+    "system",
+    "local service",
+    "network service",
+    "anonymous logon",
+    "anonymous",
+    "administrator",
+    "guest",
+    "defaultaccount",
+    "wdagutilityaccount",
+    "krbtgt",
+];
+
+const WELL_KNOWN_DOMAINS: &[&str] = &[
+    "-",
+    "nt authority",
+    "nt-autorität",
+    "autorite nt",
+    "builtin",
+    "workgroup",
+    "window manager",
+    "font driver host",
+    "nt service",
+    "nt virtual machine",
+    "iis apppool",
+];
+
+const WELL_KNOWN_HOSTS: &[&str] = &["-", "localhost"];
+
+/// the kind of a value which is replaced by a pseudonym
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum PseudonymKind {
+    User,
+    Domain,
+    Host,
+    Sid,
+    Ip,
+}
+
+impl Display for PseudonymKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::User => "user",
+            Self::Domain => "domain",
+            Self::Host => "host",
+            Self::Sid => "sid",
+            Self::Ip => "ip",
+        }
+        .fmt(f)
+    }
+}
+
+impl PseudonymKind {
+    fn prefix(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Domain => "dom",
+            Self::Host => "host",
+            Self::Sid => "sid",
+            Self::Ip => "ip",
+        }
+    }
+
+    /// guesses the kind of a value from the name of its field, e.g.
+    /// `TargetUserName` or `WorkstationName`. SIDs and IP addresses are
+    /// recognized by their values instead
+    fn of_field(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        if name.ends_with("username")
+            || name.ends_with("accountname")
+            || matches!(
+                name.as_str(),
+                "user" | "account" | "targetuser" | "userprincipalname" | "upn"
+            )
+        {
+            Some(Self::User)
+        } else if name.ends_with("domainname")
+            || matches!(name.as_str(), "domain" | "targetdomain" | "accountdomain")
+        {
+            Some(Self::Domain)
+        } else if name.ends_with("hostname")
+            || name.ends_with("computername")
+            || name.ends_with("workstationname")
+            || name.ends_with("servername")
+            || name.ends_with("machinename")
+            || matches!(
+                name.as_str(),
+                "computer" | "workstation" | "clientname" | "clientmachine"
+            )
+        {
+            Some(Self::Host)
+        } else {
+            None
+        }
+    }
+}
+
+/// replaces user names, SIDs, host names, domains and IP addresses by
+/// pseudonyms. Pseudonyms are derived from the original values using a
+/// keyed HMAC, so that the same value always gets the same pseudonym, as
+/// long as the same key is used. SIDs and IP addresses are permuted with a
+/// keyed Feistel network, so that two of them never get the same pseudonym.
+/// IP addresses are replaced by addresses of the same class (e.g. private
+/// addresses by private addresses).
+///
+/// All replacements are recorded, so that pseudonyms can be resolved by
+/// someone who has access to the mapping file.
+pub struct Pseudonymizer {
+    mac: Hmac<Sha256>,
+
+    /// maps normalized original values to the original value which has been
+    /// seen first, and to its pseudonym
+    mapping: BTreeMap<(PseudonymKind, String), (String, String)>,
+
+    /// maps the pseudonyms of names to the normalized names. Pseudonyms of
+    /// names are shortened hashes, so two names might get the same one
+    originals: HashMap<(PseudonymKind, String), String>,
+
+    /// the names of users, hosts and domains which have been replaced, with
+    /// their pseudonyms, and an automaton which finds them in free text. The
+    /// automaton is created again when new names have been found
+    names: Vec<(String, String)>,
+    names_automaton: Option<AhoCorasick>,
+    mapping_file: Option<PathBuf>,
+}
+
+impl Pseudonymizer {
+    pub fn new(key: &[u8]) -> anyhow::Result<Self> {
+        if key.len() < MIN_KEY_LENGTH {
+            bail!("the pseudonymization key must be at least {MIN_KEY_LENGTH} bytes long");
+        }
+        Ok(Self {
+            mac: Hmac::new_from_slice(key).map_err(|why| anyhow!("invalid key: {why}"))?,
+            mapping: BTreeMap::new(),
+            originals: HashMap::new(),
+            names: Vec::new(),
+            names_automaton: None,
+            mapping_file: None,
+        })
+    }
+
+    /// reads the key from a file. Trailing whitespace is ignored, so that
+    /// the key can be written with a text editor
+    pub fn from_key_file(path: &Path) -> anyhow::Result<Self> {
+        let key = std::fs::read(path)
+            .map_err(|why| anyhow!("unable to read {}: {why}", path.display()))?;
+        let length = key
+            .iter()
+            .rposition(|c| !c.is_ascii_whitespace())
+            .map_or(0, |p| p + 1);
+        Self::new(&key[..length])
+    }
+
+    /// the mapping of pseudonyms to original values is written into this
+    /// file by [`Pseudonymizer::save_mapping`]
+    pub fn with_mapping_file(mut self, path: PathBuf) -> Self {
+        self.mapping_file = Some(path);
+        self
+    }
+
+    /// writes all replacements which have been done until now as CSV into
+    /// the mapping file, if there is any. The file is overwritten
+    pub fn save_mapping(&self) -> anyhow::Result<()> {
+        let path = match self.mapping_file.as_ref() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let file = File::create(path)
+            .map_err(|why| anyhow!("unable to create {}: {why}", path.display()))?;
+        let mut writer = csv::Writer::from_writer(file);
+        writer.write_record(["type", "original", "pseudonym"])?;
+        for ((kind, _), (original, pseudonym)) in self.mapping.iter() {
+            writer.write_record([&kind.to_string(), original, pseudonym])?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// replaces all identifying values in the data of `record`. Names of
+    /// users, hosts and domains which have been found in fields of this or
+    /// of previous records are replaced in all other values, too
+    pub fn pseudonymize_record(
+        &mut self,
+        record: &mut SerializedEvtxRecord<Value>,
+    ) -> anyhow::Result<()> {
+        self.pseudonymize_data(None, &mut record.data)?;
+        self.replace_names_in(&mut record.data);
+        Ok(())
+    }
+
+    /// replaces all identifying values in `value` and its children. `name`
+    /// is the name of the element which contains `value`
+    fn pseudonymize_data(&mut self, name: Option<&str>, value: &mut Value) -> anyhow::Result<()> {
+        match value {
+            Value::Object(children) => {
+                for (child_name, child) in children.iter_mut() {
+                    let child_name = if child_name == "#text" || child_name == "#attributes" {
+                        name
+                    } else {
+                        Some(child_name.as_str())
+                    };
+                    self.pseudonymize_data(child_name, child)?;
+                }
+            }
+            Value::Array(elements) => {
+                for element in elements.iter_mut() {
+                    self.pseudonymize_data(name, element)?;
+                }
+            }
+            Value::String(text) => {
+                let pseudonymized = self.replace_field(name.unwrap_or_default(), text)?;
+                *text = pseudonymized;
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// replaces all known names in the strings of `value` and its children
+    fn replace_names_in(&mut self, value: &mut Value) {
+        match value {
+            Value::Object(children) => {
+                for child in children.values_mut() {
+                    self.replace_names_in(child);
+                }
+            }
+            Value::Array(elements) => {
+                for element in elements.iter_mut() {
+                    self.replace_names_in(element);
+                }
+            }
+            Value::String(text) => *text = self.replace_names(text),
+            _ => (),
+        }
+    }
+
+    /// replaces the value of a field. The kind of the value is determined
+    /// by the name of the field, which may be a dotted path (such as
+    /// `EventData.TargetUserName`). SIDs and IP addresses are replaced in
+    /// all fields, as well as all names which are already known
+    pub fn pseudonymize_field(&mut self, name: &str, value: &str) -> anyhow::Result<String> {
+        let value = self.replace_field(name, value)?;
+        Ok(self.replace_names(&value))
+    }
+
+    fn replace_field(&mut self, name: &str, value: &str) -> anyhow::Result<String> {
+        let name = name.rsplit('.').next().unwrap_or_default();
+        match PseudonymKind::of_field(name) {
+            Some(PseudonymKind::User) => self.user(value),
+            Some(PseudonymKind::Domain) => self.domain(value),
+            Some(PseudonymKind::Host) => self.host(value),
+            _ => Ok(self.text(value, !name.to_lowercase().contains("version"))),
+        }
+    }
+
+    /// replaces an account, which can be given as SID, as `DOMAIN\name`,
+    /// as `name@domain` or as plain name. This can be used to pseudonymize
+    /// filters which are applied to pseudonymized records
+    pub fn pseudonymize_account(&mut self, account: &str) -> anyhow::Result<String> {
+        if account.trim().to_uppercase().starts_with("S-1-") {
+            Ok(self.text(account, false))
+        } else {
+            self.user(account)
+        }
+    }
+
+    fn user(&mut self, value: &str) -> anyhow::Result<String> {
+        let value = value.trim();
+        if value.is_empty()
+            || is_well_known(WELL_KNOWN_USERS, value)
+            || regex!(r"(?i)^(DWM|UMFD)-\d+$").is_match(value)
+        {
+            Ok(value.to_owned())
+        } else if value.to_uppercase().starts_with("S-1-") {
+            Ok(self.text(value, false))
+        } else if let Some((domain, name)) = value.split_once('\\') {
+            Ok(format!("{}\\{}", self.domain(domain)?, self.user(name)?))
+        } else if let Some((name, domain)) = value.rsplit_once('@') {
+            Ok(format!("{}@{}", self.user(name)?, self.domain(domain)?))
+        } else if let Some(machine) = value.strip_suffix('$') {
+            // machine accounts are named after their host
+            Ok(format!("{}$", self.host(machine)?))
+        } else {
+            self.token(PseudonymKind::User, value)
+        }
+    }
+
+    fn domain(&mut self, value: &str) -> anyhow::Result<String> {
+        let value = value.trim();
+        if value.is_empty() || is_well_known(WELL_KNOWN_DOMAINS, value) {
+            Ok(value.to_owned())
+        } else {
+            self.token(PseudonymKind::Domain, value.trim_end_matches('.'))
+        }
+    }
+
+    fn host(&mut self, value: &str) -> anyhow::Result<String> {
+        let value = value.trim();
+        if let Some(host) = value.strip_prefix("\\\\") {
+            Ok(format!("\\\\{}", self.host(host)?))
+        } else if value.is_empty() || is_well_known(WELL_KNOWN_HOSTS, value) {
+            Ok(value.to_owned())
+        } else if let Ok(ip) = value.parse::<IpAddr>() {
+            Ok(self.ip(ip).to_string())
+        } else if let Some((host, domain)) = value.split_once('.') {
+            Ok(format!("{}.{}", self.host(host)?, self.domain(domain)?))
+        } else {
+            self.token(PseudonymKind::Host, value)
+        }
+    }
+
+    /// replaces SIDs and IP addresses in a text. IPv4 addresses are not
+    /// searched in texts which might contain version numbers
+    fn text(&mut self, value: &str, with_ipv4: bool) -> String {
+        match value.trim().parse::<IpAddr>() {
+            Ok(ip) if with_ipv4 || ip.is_ipv6() => return self.ip(ip).to_string(),
+            _ => (),
+        }
+
+        let value = regex!(r"\bS-1-(5-21|12-1)-(\d+)-(\d+)-(\d+)(-\d+)?\b")
+            .replace_all(value, |captures: &lazy_regex::Captures| {
+                let prefix = format!("S-1-{}", &captures[1]);
+                let mut sub_authorities = vec![&captures[2], &captures[3], &captures[4]];
+                let rid = captures.get(5).map_or("", |m| m.as_str());
+                if &captures[1] == "5-21" {
+                    // the RID is kept, because some RIDs (e.g. 500) have
+                    // a special meaning
+                    format!("{}{rid}", self.sid(&prefix, &sub_authorities))
+                } else {
+                    if let Some(rid) = rid.strip_prefix('-') {
+                        sub_authorities.push(rid);
+                    }
+                    self.sid(&prefix, &sub_authorities)
+                }
+            })
+            .into_owned();
+
+        if !with_ipv4 {
+            return value;
+        }
+        regex!(r"\b(?:\d{1,3}\.){3}\d{1,3}\b")
+            .replace_all(&value, |captures: &lazy_regex::Captures| {
+                let m = captures.get(0).unwrap();
+                let after = &value[m.end()..];
+                let is_part_of_version = value[..m.start()].ends_with('.')
+                    || (after.starts_with('.')
+                        && after[1..].starts_with(|c: char| c.is_ascii_digit()));
+                match m.as_str().parse::<Ipv4Addr>() {
+                    Ok(ip) if !is_part_of_version => self.ipv4(ip).to_string(),
+                    _ => m.as_str().to_owned(),
+                }
+            })
+            .into_owned()
+    }
+
+    /// replaces the sub authorities of a SID, which identify the account or
+    /// domain. Sub authorities which are no 32 bit numbers are kept
+    fn sid(&mut self, prefix: &str, sub_authorities: &[&str]) -> String {
+        let sid = format!("{prefix}-{}", sub_authorities.join("-"));
+        let Ok(numbers) = sub_authorities
+            .iter()
+            .map(|s| s.parse::<u32>())
+            .collect::<Result<Vec<_>, _>>()
+        else {
+            return sid;
+        };
+        self.pseudonym(PseudonymKind::Sid, &sid, &sid, |me| {
+            let value = numbers
+                .iter()
+                .fold(0u128, |value, number| value << 32 | u128::from(*number));
+            let bits = 32 * numbers.len() as u32;
+            let value = me.permute(PseudonymKind::Sid, value, bits);
+            let sub_authorities: Vec<_> = (0..numbers.len())
+                .rev()
+                .map(|index| ((value >> (32 * index)) as u32).to_string())
+                .collect();
+            format!("{prefix}-{}", sub_authorities.join("-"))
+        })
+    }
+
+    fn ip(&mut self, ip: IpAddr) -> IpAddr {
+        match ip {
+            IpAddr::V4(ip) => IpAddr::V4(self.ipv4(ip)),
+            IpAddr::V6(ip) => IpAddr::V6(self.ipv6(ip)),
+        }
+    }
+
+    /// replaces an IPv4 address by an address of the same range, whose
+    /// host bits are permuted. Addresses which don't identify a host (such
+    /// as `127.0.0.1`) are kept
+    fn ipv4(&mut self, ip: Ipv4Addr) -> Ipv4Addr {
+        if ip.is_loopback()
+            || ip.is_unspecified()
+            || ip.is_broadcast()
+            || ip.is_multicast()
+            || ip.is_documentation()
+        {
+            return ip;
+        }
+        let host_bits = match ip.octets() {
+            [10, ..] => 24,
+            [172, 16..=31, ..] => 20,
+            [192, 168, ..] | [169, 254, ..] => 16,
+            [100, 64..=127, ..] => 22,
+            _ if is_public_ipv4(&ip) => 32,
+            _ => return ip,
+        };
+        let key = ip.to_string();
+        self.pseudonym(PseudonymKind::Ip, &key, &key, |me| {
+            // public addresses are permuted until a public address is
+            // found (cycle walking), which terminates at the latest when
+            // the original address is reached again
+            let mut candidate = u32::from(ip);
+            loop {
+                candidate = me.permute(PseudonymKind::Ip, candidate.into(), host_bits) as u32;
+                if host_bits < 32 || is_public_ipv4(&candidate.into()) {
+                    return Ipv4Addr::from(candidate).to_string();
+                }
+            }
+        })
+        .parse()
+        .unwrap_or(ip)
+    }
+
+    /// replaces an IPv6 address by an address of the same class, whose
+    /// remaining bits are permuted. Link-local addresses keep their prefix
+    /// and only get another interface identifier
+    fn ipv6(&mut self, ip: Ipv6Addr) -> Ipv6Addr {
+        if let Some(ipv4) = ip.to_ipv4_mapped() {
+            return self.ipv4(ipv4).to_ipv6_mapped();
+        }
+        if ip.is_loopback() || ip.is_unspecified() || ip.is_multicast() {
+            return ip;
+        }
+        let key = ip.to_string();
+        let first = ip.segments()[0];
+        let bits = if first & 0xffc0 == 0xfe80 {
+            64
+        } else if first & 0xfe00 == 0xfc00 {
+            // unique local addresses
+            121
+        } else {
+            // the first three bits distinguish global unicast addresses
+            125
+        };
+        self.pseudonym(PseudonymKind::Ip, &key, &key, |me| {
+            Ipv6Addr::from(me.permute(PseudonymKind::Ip, ip.into(), bits)).to_string()
+        })
+        .parse()
+        .unwrap_or(ip)
+    }
+
+    /// returns the pseudonym of a name, such as `user-3f2a9c01d4e7`. Names
+    /// are case insensitive. Pseudonyms are shortened hashes, so two names
+    /// could get the same pseudonym, which is reported as error
+    fn token(&mut self, kind: PseudonymKind, value: &str) -> anyhow::Result<String> {
+        let key = value.to_lowercase();
+        if let Some((_, pseudonym)) = self.mapping.get(&(kind, key.clone())) {
+            return Ok(pseudonym.clone());
+        }
+        let token: String = self.hash(kind, &key)[..TOKEN_LENGTH]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        let pseudonym = format!("{}-{token}", kind.prefix());
+        if let Some(other) = self.originals.get(&(kind, pseudonym.clone())) {
+            bail!("'{other}' and '{value}' would get the same pseudonym {pseudonym}, please use another key");
+        }
+        self.originals
+            .insert((kind, pseudonym.clone()), key.clone());
+        Ok(self.pseudonym(kind, &key, value, |_| pseudonym))
+    }
+
+    /// replaces all known names of users, hosts and domains in a text, e.g.
+    /// in `C:\Users\alice\Desktop` or `\\DC01\share`. Names are only
+    /// replaced if they are not part of a longer word
+    fn replace_names(&mut self, text: &str) -> String {
+        if self.names.is_empty() {
+            return text.to_owned();
+        }
+        if self.names_automaton.is_none() {
+            let automaton = AhoCorasickBuilder::new()
+                .ascii_case_insensitive(true)
+                .match_kind(MatchKind::LeftmostLongest)
+                .build(self.names.iter().map(|(name, _)| name))
+                .expect("unable to build automaton of names");
+            self.names_automaton = Some(automaton);
+        }
+        let automaton = self.names_automaton.as_ref().unwrap();
+
+        let is_name_char = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+        let mut result = String::with_capacity(text.len());
+        let mut end = 0;
+        for m in automaton.find_iter(text) {
+            if text[..m.start()].ends_with(is_name_char)
+                || text[m.end()..].starts_with(is_name_char)
+            {
+                continue;
+            }
+            result.push_str(&text[end..m.start()]);
+            result.push_str(&self.names[m.pattern().as_usize()].1);
+            end = m.end();
+        }
+        result.push_str(&text[end..]);
+        result
+    }
+
+    /// returns the pseudonym of the normalized value `key`, which is created
+    /// by `generate` if it is not known yet
+    fn pseudonym(
+        &mut self,
+        kind: PseudonymKind,
+        key: &str,
+        original: &str,
+        generate: impl FnOnce(&Self) -> String,
+    ) -> String {
+        if let Some((_, pseudonym)) = self.mapping.get(&(kind, key.to_owned())) {
+            return pseudonym.clone();
+        }
+        let pseudonym = generate(self);
+        if matches!(
+            kind,
+            PseudonymKind::User | PseudonymKind::Domain | PseudonymKind::Host
+        ) && key.chars().count() >= MIN_NAME_LENGTH
+        {
+            self.names.push((key.to_owned(), pseudonym.clone()));
+            self.names_automaton = None;
+        }
+        self.mapping.insert(
+            (kind, key.to_owned()),
+            (original.to_owned(), pseudonym.clone()),
+        );
+        pseudonym
+    }
+
+    /// calculates the HMAC of a value
+    fn hash(&self, kind: PseudonymKind, value: &str) -> [u8; 32] {
+        let mut mac = self.mac.clone();
+        mac.update(kind.to_string().as_bytes());
+        mac.update(&[0]);
+        mac.update(value.as_bytes());
+        mac.finalize().into_bytes().into()
+    }
+
+    /// permutes the lowest `bits` bits of `value` with a Feistel network,
+    /// whose round function is the HMAC, and keeps all other bits. Every
+    /// prefix gets its own permutation. Because this is a bijection, two
+    /// different values never get the same pseudonym, independent of the
+    /// order in which they are seen
+    fn permute(&self, kind: PseudonymKind, value: u128, bits: u32) -> u128 {
+        let mask = |bits: u32| u128::MAX.checked_shr(128 - bits).unwrap_or(0);
+        let prefix = value & !mask(bits);
+
+        // the halves have different sizes if `bits` is odd, and are swapped
+        // in every round
+        let mut left_bits = bits / 2;
+        let mut right_bits = bits - left_bits;
+        let mut left = (value >> right_bits) & mask(left_bits);
+        let mut right = value & mask(right_bits);
+        for round in 0..FEISTEL_ROUNDS {
+            let mut mac = self.mac.clone();
+            mac.update(kind.to_string().as_bytes());
+            mac.update(&[0, bits as u8, round]);
+            mac.update(&prefix.to_be_bytes());
+            mac.update(&right.to_be_bytes());
+            let hash = mac.finalize().into_bytes();
+            let f = u128::from_be_bytes(hash[..16].try_into().unwrap()) & mask(left_bits);
+            (left, right) = (right, left ^ f);
+            (left_bits, right_bits) = (right_bits, left_bits);
+        }
+        prefix | (left << right_bits) | right
+    }
+}
+
+fn is_well_known(names: &[&str], value: &str) -> bool {
+    let value = value.to_lowercase();
+    names.contains(&value.as_str())
+}
+
+fn is_public_ipv4(ip: &Ipv4Addr) -> bool {
+    let [a, b, _, _] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_documentation()
+        || a == 0
+        || a >= 224
+        || (a == 100 && (64..128).contains(&b))
+        || (a == 198 && (18..20).contains(&b)))
+}
@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use crate::event_id::EventId;
+use crate::{event_id::EventId, TimeZoneSpec, TimestampFormat, TimestampFormatter};
 
 #[derive(PartialEq, Eq)]
 pub struct Range {
@@ -60,14 +60,22 @@ impl Range {
     pub fn events(&self) -> std::slice::Iter<'_, EventId> {
         self.events.iter()
     }
+
+    pub fn format(&self, formatter: &TimestampFormatter) -> String {
+        format!("{} - {} ({} - {})",
+            formatter.format(self.begin().timestamp()), 
+            formatter.format(self.end().timestamp()), 
+            self.begin().event_record_id(), 
+            self.end().event_record_id())
+    }
 }
 
 impl Display for Range {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} - {} ({} - {})",
-            self.begin().timestamp().format("%FT%T"), 
-            self.end().timestamp().format("%FT%T"), 
-            self.begin().event_record_id(), 
-            self.end().event_record_id())
+        let formatter = TimestampFormatter::new(
+            TimeZoneSpec::default(),
+            TimestampFormat::Custom("%FT%T".to_owned()),
+        );
+        self.format(&formatter).fmt(f)
     }
 }
\ No newline at end of file
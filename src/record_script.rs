@@ -0,0 +1,231 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use chrono::SecondsFormat;
+use eventdata::{
+    parameter_message, resolve_parameter_messages, Channel, Computer, EventId, Keywords,
+    LogonType, NtStatus, ProviderName,
+};
+use evtx::SerializedEvtxRecord;
+use rhai::{module_resolvers::DummyModuleResolver, CallFnOptions, Dynamic, Engine, Scope, AST};
+use serde_json::{json, Value};
+
+use crate::{flatten_custom_data, EvtxSource};
+
+/// the maximum number of operations of one single call of the script, so
+/// that endless loops are aborted
+const MAX_OPERATIONS: u64 = 100_000_000;
+
+/// the maximum size of strings, arrays and maps of the script
+const MAX_STRING_SIZE: usize = 16 * 1024 * 1024;
+const MAX_ARRAY_SIZE: usize = 1_000_000;
+const MAX_MAP_SIZE: usize = 1_000_000;
+
+/// a user supplied Rhai script which is run for every record. The script
+/// can define the functions
+///
+///  - `filter(record)`, which returns `true` if the record should be
+///    processed further
+///  - `map(record)`, which returns the output for the record
+///  - `finish()`, which returns the output after all records have been
+///    processed, e.g. some aggregated values
+///
+/// Variables which are declared at the top level of the script keep their
+/// values between the calls, so they can be used to aggregate data.
+///
+/// Scripts have no access to files or the network, and they are aborted
+/// if a call takes too many operations or creates too large values
+pub struct RecordScript {
+    path: PathBuf,
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+    has_filter: bool,
+    has_map: bool,
+    has_finish: bool,
+}
+
+impl RecordScript {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let mut engine = Engine::new();
+        engine.set_module_resolver(DummyModuleResolver::new());
+        engine.disable_symbol("eval");
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_expr_depths(64, 32);
+        engine.set_max_string_size(MAX_STRING_SIZE);
+        engine.set_max_array_size(MAX_ARRAY_SIZE);
+        engine.set_max_map_size(MAX_MAP_SIZE);
+        register_helpers(&mut engine);
+
+        let ast = engine
+            .compile_file(path.to_owned())
+            .map_err(|why| anyhow!("unable to load script {}: {why}", path.display()))?;
+
+        let has_function = |name: &str, params: usize| {
+            ast.iter_functions()
+                .any(|f| f.name == name && f.params.len() == params)
+        };
+        let has_filter = has_function("filter", 1);
+        let has_map = has_function("map", 1);
+        let has_finish = has_function("finish", 0);
+        if !(has_filter || has_map || has_finish) {
+            log::warn!(
+                "{} defines none of the functions filter(record), map(record) or finish()",
+                path.display()
+            );
+        }
+
+        // top level statements are run once, so that the script can
+        // initialize its variables
+        let mut scope = Scope::new();
+        engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|why| anyhow!("error in {}: {why}", path.display()))?;
+
+        Ok(Self {
+            path: path.to_owned(),
+            engine,
+            ast,
+            scope,
+            has_filter,
+            has_map,
+            has_finish,
+        })
+    }
+
+    /// returns `true` if the script produces the output for records
+    pub fn has_map(&self) -> bool {
+        self.has_map
+    }
+
+    /// returns `true` if the record should be processed, which is the case
+    /// for all records if the script has no `filter` function
+    pub fn filter(
+        &mut self,
+        source: &EvtxSource,
+        record: &SerializedEvtxRecord<Value>,
+    ) -> anyhow::Result<bool> {
+        if !self.has_filter {
+            return Ok(true);
+        }
+        let record = script_record(source, record)?;
+        let result = self.call("filter", (record,))?;
+        result
+            .as_bool()
+            .map_err(|t| anyhow!("filter() in {} returned {t} instead of bool", self.path.display()))
+    }
+
+    /// returns the output of `map` for a record, or `None` if `map` did not
+    /// return a value
+    pub fn map(
+        &mut self,
+        source: &EvtxSource,
+        record: &SerializedEvtxRecord<Value>,
+    ) -> anyhow::Result<Option<String>> {
+        if !self.has_map {
+            return Ok(None);
+        }
+        let record = script_record(source, record)?;
+        let result = self.call("map", (record,))?;
+        output_of(result)
+    }
+
+    /// returns the output of `finish`, or `None` if there is no `finish`
+    /// function or it did not return a value
+    pub fn finish(&mut self) -> anyhow::Result<Option<String>> {
+        if !self.has_finish {
+            return Ok(None);
+        }
+        let result = self.call("finish", ())?;
+        output_of(result)
+    }
+
+    fn call(&mut self, function: &str, args: impl rhai::FuncArgs) -> anyhow::Result<Dynamic> {
+        let options = CallFnOptions::new().eval_ast(false);
+        self.engine
+            .call_fn_with_options(options, &mut self.scope, &self.ast, function, args)
+            .map_err(|why| anyhow!("error in {function}() of {}: {why}", self.path.display()))
+    }
+}
+
+/// the record as it is passed to the script. Besides the complete data of
+/// the record in `data`, the most important fields are directly available
+fn script_record(
+    source: &EvtxSource,
+    record: &SerializedEvtxRecord<Value>,
+) -> anyhow::Result<Dynamic> {
+    let audit = Keywords::try_from(record).ok().and_then(|keywords| {
+        if keywords.is_audit_success() {
+            Some("success")
+        } else if keywords.is_audit_failure() {
+            Some("failure")
+        } else {
+            None
+        }
+    });
+
+    let value = json!({
+        "event_record_id": record.event_record_id,
+        "timestamp": record.timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        "epoch": record.timestamp.timestamp(),
+        "event_id": EventId::try_from(record).ok().map(|id| id.value()),
+        "provider": ProviderName::try_from(record).ok().map(|p| p.to_string()),
+        "channel": Channel::try_from(record).ok().map(|c| c.to_string()),
+        "computer": Computer::try_from(record).ok().map(|c| c.to_string()),
+        "level": record.data["Event"]["System"]["Level"],
+        "audit": audit,
+        "evtx_file": source.to_string(),
+        "fields": flatten_custom_data(record),
+        "data": record.data["Event"],
+    });
+    rhai::serde::to_dynamic(value).map_err(|why| anyhow!("unable to convert record: {why}"))
+}
+
+/// strings are displayed as they are, other values are displayed as JSON
+fn output_of(value: Dynamic) -> anyhow::Result<Option<String>> {
+    if value.is_unit() {
+        Ok(None)
+    } else if value.is_string() {
+        Ok(Some(value.into_string().unwrap_or_default()))
+    } else {
+        let value: Value = rhai::serde::from_dynamic(&value)
+            .map_err(|why| anyhow!("unable to convert script result: {why}"))?;
+        Ok(Some(value.to_string()))
+    }
+}
+
+/// makes the decoders of the `eventdata` crate available to scripts
+fn register_helpers(engine: &mut Engine) {
+    engine.register_fn("logon_type_name", |logon_type: i64| {
+        u64::try_from(logon_type)
+            .ok()
+            .and_then(|t| LogonType::try_from(t).ok())
+            .map(|t| t.name().to_owned())
+            .unwrap_or_default()
+    });
+    engine.register_fn("nt_status_description", |status: i64| {
+        u32::try_from(status)
+            .ok()
+            .and_then(|s| NtStatus::from(s).description())
+            .unwrap_or_default()
+            .to_owned()
+    });
+    engine.register_fn("nt_status_description", |status: &str| {
+        status
+            .parse::<NtStatus>()
+            .ok()
+            .and_then(|s| s.description())
+            .unwrap_or_default()
+            .to_owned()
+    });
+    engine.register_fn("parameter_message", |id: i64| {
+        u32::try_from(id)
+            .ok()
+            .and_then(parameter_message)
+            .unwrap_or_default()
+            .to_owned()
+    });
+    engine.register_fn("resolve_parameters", |text: &str| {
+        resolve_parameter_messages(text).into_owned()
+    });
+}
@@ -0,0 +1,51 @@
+use std::str::FromStr;
+
+use anyhow::bail;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+use crate::TimeZoneSpec;
+
+/// this struct is practically a parser for
+/// RFC3339-compliant strings and their abbreviated forms.
+///
+/// Abbreviated forms don't contain a time zone offset, so they must be
+/// converted using a time zone later (see [`Rfc3339Datetime::to_utc`])
+#[derive(Clone)]
+pub enum Rfc3339Datetime {
+    WithOffset(DateTime<FixedOffset>),
+    Local(NaiveDateTime),
+}
+
+impl FromStr for Rfc3339Datetime {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(timestamp) = DateTime::parse_from_rfc3339(s) {
+            return Ok(Self::WithOffset(timestamp));
+        }
+
+        if let Ok(timestamp) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S") {
+            return Ok(Self::Local(timestamp));
+        }
+
+        if let Ok(timestamp) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
+            return Ok(Self::Local(timestamp));
+        }
+
+        if let Ok(timestamp) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap();
+            return Ok(Self::Local(NaiveDateTime::new(timestamp, time)));
+        }
+
+        bail!("invalid timestamp: '{s}'");
+    }
+}
+
+impl Rfc3339Datetime {
+    pub fn to_utc(&self, timezone: &TimeZoneSpec) -> anyhow::Result<DateTime<Utc>> {
+        match self {
+            Self::WithOffset(timestamp) => Ok(timestamp.with_timezone(&Utc)),
+            Self::Local(timestamp) => timezone.to_utc(timestamp),
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use std::net::IpAddr;
+
+use eventdata::{Channel, Computer, EventId, EventLevel, Keywords, ProviderName};
+use evtx::SerializedEvtxRecord;
+use serde_json::Value;
+
+use crate::EvtxSource;
+
+const DEVICE_VENDOR: &str = "Microsoft";
+const DEVICE_PRODUCT: &str = "Windows";
+
+/// the fields of a record which are mapped to the standard keys of CEF and
+/// LEEF
+struct SiemFields<'r> {
+    event_id: String,
+    provider: String,
+    severity: u8,
+    outcome: Option<&'static str>,
+    computer: Option<String>,
+    channel: Option<String>,
+    target_user: Option<&'r str>,
+    target_domain: Option<&'r str>,
+    subject_user: Option<&'r str>,
+    subject_domain: Option<&'r str>,
+    source_ip: Option<IpAddr>,
+    source_port: Option<u16>,
+    source_host: Option<&'r str>,
+    process: Option<&'r str>,
+}
+
+impl<'r> SiemFields<'r> {
+    fn new(record: &'r SerializedEvtxRecord<Value>) -> Self {
+        let event_data = &record.data["Event"]["EventData"];
+        let field = |name: &str| {
+            event_data
+                .get(name)
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|v| !v.is_empty() && *v != "-")
+        };
+
+        let outcome = Keywords::try_from(record).ok().and_then(|keywords| {
+            if keywords.is_audit_success() {
+                Some("success")
+            } else if keywords.is_audit_failure() {
+                Some("failure")
+            } else {
+                None
+            }
+        });
+
+        Self {
+            event_id: EventId::try_from(record)
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+            provider: ProviderName::try_from(record)
+                .map(|p| p.to_string())
+                .unwrap_or_default(),
+            severity: EventLevel::of_record(record).cef_severity(),
+            outcome,
+            computer: Computer::try_from(record).ok().map(|c| c.to_string()),
+            channel: Channel::try_from(record).ok().map(|c| c.to_string()),
+            target_user: field("TargetUserName"),
+            target_domain: field("TargetDomainName"),
+            subject_user: field("SubjectUserName"),
+            subject_domain: field("SubjectDomainName"),
+            source_ip: field("IpAddress")
+                .or(field("SourceIp"))
+                .and_then(|ip| ip.parse().ok()),
+            source_port: field("IpPort")
+                .or(field("SourcePort"))
+                .and_then(|p| p.parse().ok())
+                .filter(|p| *p != 0),
+            source_host: field("WorkstationName").or(field("Workstation")),
+            process: field("NewProcessName").or(field("Image")),
+        }
+    }
+}
+
+/// formats a record using the ArcSight Common Event Format (CEF). `message`
+/// is the human readable message of the record, if it is known
+pub fn cef_line(
+    record: &SerializedEvtxRecord<Value>,
+    source: Option<&EvtxSource>,
+    message: Option<&str>,
+) -> String {
+    let fields = SiemFields::new(record);
+    let name = format!("{} {}", fields.provider, fields.event_id);
+
+    let mut extension = vec![
+        ("rt", record.timestamp.timestamp_millis().to_string()),
+        ("externalId", record.event_record_id.to_string()),
+        ("cs1Label", "Provider".to_owned()),
+        ("cs1", fields.provider.clone()),
+    ];
+    let mut add = |key, value: Option<String>| {
+        if let Some(value) = value {
+            extension.push((key, value));
+        }
+    };
+    add("dvchost", fields.computer);
+    add("cat", fields.channel);
+    add("outcome", fields.outcome.map(str::to_owned));
+    add("duser", fields.target_user.map(str::to_owned));
+    add("dntdom", fields.target_domain.map(str::to_owned));
+    add("suser", fields.subject_user.map(str::to_owned));
+    add("sntdom", fields.subject_domain.map(str::to_owned));
+    add("src", fields.source_ip.map(|ip| ip.to_string()));
+    add("spt", fields.source_port.map(|p| p.to_string()));
+    add("shost", fields.source_host.map(str::to_owned));
+    add("sproc", fields.process.map(str::to_owned));
+    add("fname", source.map(|s| s.to_string()));
+    add("msg", message.map(str::to_owned));
+
+    let extension = extension
+        .into_iter()
+        .map(|(key, value)| format!("{key}={}", escape_cef_extension(&value)))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!(
+        "CEF:0|{DEVICE_VENDOR}|{DEVICE_PRODUCT}||{}|{}|{}|{extension}",
+        escape_cef_header(&fields.event_id),
+        escape_cef_header(&name),
+        fields.severity
+    )
+}
+
+/// formats a record using the IBM Log Event Extended Format (LEEF) in
+/// version 1.0. `message` is the human readable message of the record, if
+/// it is known
+pub fn leef_line(
+    record: &SerializedEvtxRecord<Value>,
+    source: Option<&EvtxSource>,
+    message: Option<&str>,
+) -> String {
+    let fields = SiemFields::new(record);
+
+    let mut attributes = vec![
+        ("devTime", record.timestamp.timestamp_millis().to_string()),
+        ("sev", fields.severity.max(1).to_string()),
+        ("provider", fields.provider.clone()),
+        ("recordId", record.event_record_id.to_string()),
+    ];
+    let mut add = |key, value: Option<String>| {
+        if let Some(value) = value {
+            attributes.push((key, value));
+        }
+    };
+    add("identHostName", fields.computer);
+    add("cat", fields.channel);
+    add("outcome", fields.outcome.map(str::to_owned));
+    add("usrName", fields.target_user.or(fields.subject_user).map(str::to_owned));
+    add(
+        "domain",
+        match fields.target_user {
+            Some(_) => fields.target_domain,
+            None => fields.subject_domain,
+        }
+        .map(str::to_owned),
+    );
+    add("src", fields.source_ip.map(|ip| ip.to_string()));
+    add("srcPort", fields.source_port.map(|p| p.to_string()));
+    add("srcHostName", fields.source_host.map(str::to_owned));
+    add("process", fields.process.map(str::to_owned));
+    add("evtxFile", source.map(|s| s.to_string()));
+    add("msg", message.map(str::to_owned));
+
+    let attributes = attributes
+        .into_iter()
+        .map(|(key, value)| format!("{key}={}", escape_leef(&value)))
+        .collect::<Vec<_>>()
+        .join("\t");
+
+    format!(
+        "LEEF:1.0|{DEVICE_VENDOR}|{DEVICE_PRODUCT}||{}|{attributes}",
+        fields.event_id
+    )
+}
+
+fn escape_cef_header(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace(['\r', '\n'], " ")
+}
+
+fn escape_cef_extension(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace('\r', "\\r")
+        .replace('\n', "\\n")
+}
+
+/// LEEF 1.0 does not support escaping, so tabs and line breaks are replaced
+/// by spaces
+fn escape_leef(value: &str) -> String {
+    value.replace(['\t', '\r', '\n'], " ")
+}
@@ -0,0 +1,181 @@
+use std::path::Path;
+
+use chrono::SecondsFormat;
+use eventdata::{
+    Channel, Computer, EventId, Keywords, Level, Opcode, ProcessId, ProviderName, Task, ThreadId,
+    UserId,
+};
+use evtx::SerializedEvtxRecord;
+use rusqlite::{params, Connection};
+use serde_json::Value;
+
+use crate::{flatten_custom_data, EvtxSource};
+
+/// the tables and indices which are created in a new database
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS records (
+    id INTEGER PRIMARY KEY,
+    evtx_file TEXT NOT NULL,
+    event_record_id INTEGER NOT NULL,
+    timestamp TEXT NOT NULL,
+    event_id INTEGER,
+    provider TEXT,
+    channel TEXT NOT NULL DEFAULT '',
+    computer TEXT NOT NULL DEFAULT '',
+    level INTEGER,
+    task INTEGER,
+    opcode INTEGER,
+    keywords TEXT,
+    process_id INTEGER,
+    thread_id INTEGER,
+    user_id TEXT,
+    activity_id TEXT,
+    message TEXT,
+    data TEXT,
+    UNIQUE (computer, channel, event_record_id, timestamp)
+);
+CREATE TABLE IF NOT EXISTS event_data (
+    record_id INTEGER NOT NULL REFERENCES records(id),
+    name TEXT NOT NULL,
+    value
+);
+CREATE INDEX IF NOT EXISTS records_timestamp ON records(timestamp);
+CREATE INDEX IF NOT EXISTS records_event_id ON records(event_id);
+CREATE INDEX IF NOT EXISTS records_provider ON records(provider);
+CREATE INDEX IF NOT EXISTS records_computer ON records(computer);
+CREATE INDEX IF NOT EXISTS event_data_record_id ON event_data(record_id);
+CREATE INDEX IF NOT EXISTS event_data_name_value ON event_data(name, value);
+";
+
+/// the number of records which are inserted in one single transaction
+const TRANSACTION_SIZE: usize = 10_000;
+
+/// writes records into a SQLite database.
+///
+/// The table `records` contains one row per record, with one column per
+/// system field, the human readable message (if it is known) and the
+/// contents of `EventData` or `UserData` as JSON in `data`. The table
+/// `event_data` contains the same fields as name/value pairs, named like
+/// the fields of [`flatten_custom_data`].
+///
+/// Existing databases are extended; records which are already stored (i.e.
+/// which have the same computer, channel, event record id and timestamp)
+/// are skipped, so that files of multiple hosts can be collected in one
+/// database. The timestamp is part of the key because event record ids
+/// start again at 1 after a log has been cleared
+pub struct SqliteWriter {
+    connection: Connection,
+    uncommitted: usize,
+    inserted: usize,
+    skipped: usize,
+}
+
+impl SqliteWriter {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self {
+            connection,
+            uncommitted: 0,
+            inserted: 0,
+            skipped: 0,
+        })
+    }
+
+    pub fn write(
+        &mut self,
+        source: &EvtxSource,
+        record: &SerializedEvtxRecord<Value>,
+        message: Option<String>,
+    ) -> anyhow::Result<()> {
+        if self.uncommitted == 0 {
+            self.connection.execute_batch("BEGIN")?;
+        }
+
+        let event = &record.data["Event"];
+        let data = match event.get("EventData").or_else(|| event.get("UserData")) {
+            Some(data) if !data.is_null() => Some(data.to_string()),
+            _ => None,
+        };
+
+        let changes = self.connection.prepare_cached(
+            "INSERT OR IGNORE INTO records (
+                evtx_file, event_record_id, timestamp, event_id, provider,
+                channel, computer, level, task, opcode, keywords, process_id,
+                thread_id, user_id, activity_id, message, data
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
+        )?.execute(params![
+            source.to_string(),
+            record.event_record_id,
+            record.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+            EventId::try_from(record).ok().map(|id| id.value()),
+            ProviderName::try_from(record).ok().map(|p| p.to_string()),
+            Channel::try_from(record).map(|c| c.to_string()).unwrap_or_default(),
+            Computer::try_from(record).map(|c| c.to_string()).unwrap_or_default(),
+            Level::try_from(record).ok().map(|l| l.value()),
+            Task::try_from(record).ok().map(|t| t.0),
+            Opcode::try_from(record).ok().map(|o| o.0),
+            Keywords::try_from(record).ok().map(|k| k.to_string()),
+            ProcessId::try_from(record).ok().map(|p| p.0),
+            ThreadId::try_from(record).ok().map(|t| t.0),
+            UserId::try_from(record).ok().map(|u| u.to_string()),
+            event["System"]["Correlation"]["#attributes"]["ActivityID"].as_str(),
+            message,
+            data,
+        ])?;
+
+        if changes == 0 {
+            self.skipped += 1;
+        } else {
+            let id = self.connection.last_insert_rowid();
+            let mut statement = self.connection.prepare_cached(
+                "INSERT INTO event_data (record_id, name, value) VALUES (?1, ?2, ?3)",
+            )?;
+            for (name, value) in flatten_custom_data(record) {
+                statement.execute(params![id, name, sql_value(value)])?;
+            }
+            self.inserted += 1;
+        }
+
+        self.uncommitted += 1;
+        if self.uncommitted >= TRANSACTION_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// commits all records which have been written
+    pub fn flush(&mut self) -> anyhow::Result<()> {
+        if self.uncommitted > 0 {
+            self.connection.execute_batch("COMMIT")?;
+            self.uncommitted = 0;
+        }
+        Ok(())
+    }
+
+    /// the number of records which have been added to the database
+    pub fn inserted(&self) -> usize {
+        self.inserted
+    }
+
+    /// the number of records which have been skipped, because they already
+    /// were stored in the database
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+}
+
+/// stores numbers as numbers, so that they can be compared numerically
+fn sql_value(value: Value) -> rusqlite::types::Value {
+    use rusqlite::types::Value as SqlValue;
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(b.into()),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => n.as_f64().map(SqlValue::Real).unwrap_or(SqlValue::Null),
+        },
+        Value::String(s) => SqlValue::Text(s),
+        v => SqlValue::Text(v.to_string()),
+    }
+}
@@ -0,0 +1,225 @@
+use std::{
+    fmt::Display,
+    io::{stdout, Stdout, Write},
+    net::{TcpStream, ToSocketAddrs, UdpSocket},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail};
+use chrono::SecondsFormat;
+use eventdata::{Computer, EventId, EventLevel, ProcessId, ProviderName};
+use evtx::SerializedEvtxRecord;
+use serde_json::Value;
+
+/// the facility of all messages, which is `user-level messages`
+const FACILITY: u8 = 1;
+
+/// where syslog messages are sent to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SyslogTarget {
+    Stdout,
+    Udp(String),
+    Tcp(String),
+}
+
+/// parses `-` (for stdout), `udp://<host>:<port>` or `tcp://<host>:<port>`
+impl FromStr for SyslogTarget {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "-" {
+            Ok(Self::Stdout)
+        } else if let Some(address) = s.strip_prefix("udp://") {
+            Ok(Self::Udp(address.to_owned()))
+        } else if let Some(address) = s.strip_prefix("tcp://") {
+            Ok(Self::Tcp(address.to_owned()))
+        } else {
+            bail!("invalid syslog target '{s}', use '-', 'udp://<host>:<port>' or 'tcp://<host>:<port>'")
+        }
+    }
+}
+
+impl Display for SyslogTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Stdout => "-".fmt(f),
+            Self::Udp(address) => write!(f, "udp://{address}"),
+            Self::Tcp(address) => write!(f, "tcp://{address}"),
+        }
+    }
+}
+
+enum Transport {
+    Stdout(Stdout),
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+}
+
+/// sends records as RFC 5424 syslog messages. Messages are sent to TCP
+/// servers using octet counting (see RFC 6587), and are written to stdout
+/// one message per line
+pub struct SyslogWriter {
+    transport: Transport,
+}
+
+impl SyslogWriter {
+    pub fn connect(target: &SyslogTarget) -> anyhow::Result<Self> {
+        let transport = match target {
+            SyslogTarget::Stdout => Transport::Stdout(stdout()),
+            SyslogTarget::Udp(address) => {
+                // the local socket must have the same address family as the target
+                let address = address
+                    .to_socket_addrs()
+                    .ok()
+                    .and_then(|mut addresses| addresses.next())
+                    .ok_or_else(|| anyhow!("unable to resolve {target}"))?;
+                let local = if address.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+                let socket = UdpSocket::bind(local)?;
+                socket
+                    .connect(address)
+                    .map_err(|why| anyhow!("unable to connect to {target}: {why}"))?;
+                Transport::Udp(socket)
+            }
+            SyslogTarget::Tcp(address) => Transport::Tcp(
+                TcpStream::connect(address)
+                    .map_err(|why| anyhow!("unable to connect to {target}: {why}"))?,
+            ),
+        };
+        Ok(Self { transport })
+    }
+
+    /// sends `payload`, which describes `record`
+    pub fn send(&mut self, record: &SerializedEvtxRecord<Value>, payload: &str) -> anyhow::Result<()> {
+        let message = syslog_message(record, payload);
+        match &mut self.transport {
+            Transport::Stdout(stdout) => writeln!(stdout, "{message}")?,
+            Transport::Udp(socket) => {
+                socket.send(message.as_bytes())?;
+            }
+            Transport::Tcp(stream) => write!(stream, "{} {message}", message.len())?,
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> anyhow::Result<()> {
+        match &mut self.transport {
+            Transport::Stdout(stdout) => stdout.flush()?,
+            Transport::Udp(_) => (),
+            Transport::Tcp(stream) => stream.flush()?,
+        }
+        Ok(())
+    }
+}
+
+/// creates a RFC 5424 syslog message for `record`. The hostname is the
+/// computer name of the record, the app name is its provider, and the
+/// message id is its event id
+pub fn syslog_message(record: &SerializedEvtxRecord<Value>, payload: &str) -> String {
+    let priority = FACILITY * 8 + EventLevel::of_record(record).syslog_severity();
+    let timestamp = record.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true);
+    let hostname = header_field(Computer::try_from(record).ok().map(|c| c.to_string()), 255);
+    let app_name = header_field(ProviderName::try_from(record).ok().map(|p| p.to_string()), 48);
+    let proc_id = header_field(ProcessId::try_from(record).ok().map(|p| p.0.to_string()), 128);
+    let msg_id = header_field(EventId::try_from(record).ok().map(|id| id.to_string()), 32);
+    let payload = payload.replace(['\r', '\n'], " ");
+    format!("<{priority}>1 {timestamp} {hostname} {app_name} {proc_id} {msg_id} - {payload}")
+}
+
+/// header fields must consist of printable ASCII characters and must not be
+/// empty. A missing value is written as `-`
+fn header_field(value: Option<String>, max_length: usize) -> String {
+    let value: String = value
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max_length)
+        .collect();
+    if value.is_empty() {
+        "-".to_owned()
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Read, net::TcpListener, thread};
+
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    use super::*;
+
+    /// a failed logon, whose provider name is too long for the header and
+    /// which has no process id
+    fn record() -> SerializedEvtxRecord<Value> {
+        SerializedEvtxRecord {
+            event_record_id: 1,
+            timestamp: Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap(),
+            data: json!({"Event": {"System": {
+                "Computer": "PC1.corp.local",
+                "Provider": {"#attributes": {"Name": format!("My Provider {}", "x".repeat(60))}},
+                "EventID": 4625,
+                "Keywords": "0x8010000000000000",
+            }}}),
+        }
+    }
+
+    #[test]
+    fn message_header() {
+        let message = syslog_message(&record(), "line 1\nline 2");
+        let app_name = format!("MyProvider{}", "x".repeat(38));
+        assert_eq!(
+            message,
+            format!("<12>1 2024-01-02T03:04:05.000000Z PC1.corp.local {app_name} - 4625 - line 1 line 2")
+        );
+    }
+
+    #[test]
+    fn header_fields() {
+        assert_eq!(header_field(None, 10), "-");
+        assert_eq!(header_field(Some(" \t".to_owned()), 10), "-");
+        assert_eq!(header_field(Some("Müller PC".to_owned()), 10), "MllerPC");
+        assert_eq!(header_field(Some("abcdef".to_owned()), 4), "abcd");
+    }
+
+    #[test]
+    fn tcp_uses_octet_counting() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let target = format!("tcp://{}", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut received = Vec::new();
+            stream.read_to_end(&mut received).unwrap();
+            String::from_utf8(received).unwrap()
+        });
+
+        let payload = "Anmeldung für Müller fehlgeschlagen";
+        let mut writer = SyslogWriter::connect(&target.parse().unwrap()).unwrap();
+        writer.send(&record(), payload).unwrap();
+        writer.send(&record(), payload).unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+
+        let message = syslog_message(&record(), payload);
+        assert_ne!(message.len(), message.chars().count());
+        let frame = format!("{} {message}", message.len());
+        assert_eq!(server.join().unwrap(), format!("{frame}{frame}"));
+    }
+
+    #[test]
+    fn udp_sends_one_message_per_datagram() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let target = format!("udp://{}", socket.local_addr().unwrap());
+
+        let mut writer = SyslogWriter::connect(&target.parse().unwrap()).unwrap();
+        writer.send(&record(), "Müller").unwrap();
+
+        let mut buffer = [0; 1024];
+        let length = socket.recv(&mut buffer).unwrap();
+        assert_eq!(
+            std::str::from_utf8(&buffer[..length]).unwrap(),
+            syslog_message(&record(), "Müller")
+        );
+    }
+}
@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use clap::Args;
+
+use crate::{Rfc3339Datetime, TimeZoneSpec, TimestampFormat, TimestampFormatter};
+
+/// command line options which control how timestamps are parsed and displayed
+#[derive(Args, Clone)]
+pub struct TimeArgs {
+    /// time zone used to display timestamps and to interpret timestamps without
+    /// an explicit offset, such as in '--from' and '--to'. Use either an IANA
+    /// name (e.g. 'Europe/Berlin') or an offset (e.g. '+02:00')
+    #[clap(
+        long("timezone"),
+        global = true,
+        allow_hyphen_values = true,
+        default_value = "UTC"
+    )]
+    pub timezone: TimeZoneSpec,
+
+    /// format of displayed timestamps: 'rfc3339', 'epoch' or a strftime format
+    /// string (e.g. '%d.%m.%Y %H:%M:%S'). Every tool has its own default format
+    #[clap(long("time-format"), global = true)]
+    pub time_format: Option<TimestampFormat>,
+}
+
+impl TimeArgs {
+    /// creates a formatter which uses `default_format` if the user did not
+    /// specify any timestamp format
+    pub fn formatter(&self, default_format: TimestampFormat) -> TimestampFormatter {
+        TimestampFormatter::new(
+            self.timezone.clone(),
+            self.time_format.clone().unwrap_or(default_format),
+        )
+    }
+
+    pub fn to_utc(&self, ts: &Rfc3339Datetime) -> anyhow::Result<DateTime<Utc>> {
+        ts.to_utc(&self.timezone)
+    }
+}
@@ -0,0 +1,228 @@
+use std::{collections::BTreeMap, io::Write};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::ValueEnum;
+use eventdata::{Channel, Computer, EventId, ProviderName};
+use evtx::SerializedEvtxRecord;
+use serde_json::{Map, Value};
+
+use crate::{flatten_custom_data, EvtxSource};
+
+/// the columns which are required by Timesketch, followed by `data_type`
+const COLUMNS: &[&str] = &["message", "datetime", "timestamp", "timestamp_desc", "data_type"];
+
+pub const EVTX_RECORD_DATA_TYPE: &str = "windows:evtx:record";
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimesketchFormat {
+    /// one JSON object per line
+    Jsonl,
+
+    /// comma separated values, with one column per field
+    Csv,
+}
+
+/// one single event of a Timesketch timeline
+#[derive(Debug, Clone)]
+pub struct TimesketchEvent {
+    datetime: DateTime<Utc>,
+    message: String,
+    timestamp_desc: String,
+    data_type: String,
+    fields: BTreeMap<String, Value>,
+}
+
+impl TimesketchEvent {
+    pub fn new(
+        datetime: DateTime<Utc>,
+        message: String,
+        timestamp_desc: &str,
+        data_type: &str,
+    ) -> Self {
+        Self {
+            datetime,
+            message,
+            timestamp_desc: timestamp_desc.to_owned(),
+            data_type: data_type.to_owned(),
+            fields: BTreeMap::new(),
+        }
+    }
+
+    /// creates an event from an evtx record. If no `message` is given, a
+    /// message is generated from the record's fields
+    pub fn from_record(
+        record: &SerializedEvtxRecord<Value>,
+        source: Option<&EvtxSource>,
+        message: Option<String>,
+    ) -> Self {
+        let fields = flatten_custom_data(record);
+        let event_id = EventId::try_from(record).ok().map(|id| id.value());
+        let provider = ProviderName::try_from(record).ok().map(|p| p.to_string());
+
+        let message = message.unwrap_or_else(|| {
+            let data = fields
+                .iter()
+                .map(|(k, v)| format!("{k}={}", value_to_string(v)))
+                .collect::<Vec<_>>()
+                .join(" ");
+            format!(
+                "[{} / {}] {data}",
+                event_id.map(|id| id.to_string()).unwrap_or_default(),
+                provider.as_deref().unwrap_or_default()
+            )
+        });
+
+        let mut event = Self::new(
+            record.timestamp,
+            message,
+            "Event Recorded",
+            EVTX_RECORD_DATA_TYPE,
+        );
+        event.fields = fields;
+        event.add_field("event_record_id", record.event_record_id);
+        event.add_field("event_id", event_id);
+        event.add_field("provider", provider);
+        event.add_field(
+            "channel",
+            Channel::try_from(record).ok().map(|c| c.to_string()),
+        );
+        event.add_field(
+            "computer",
+            Computer::try_from(record).ok().map(|c| c.to_string()),
+        );
+        if let Some(source) = source {
+            event.add_field("evtx_file", source.to_string());
+        }
+        event
+    }
+
+    /// adds an additional field, which replaces fields of the same name.
+    /// Fields without a value are ignored
+    pub fn add_field<V: Into<Value>>(&mut self, name: &str, value: V) {
+        let value = value.into();
+        if !value.is_null() {
+            self.fields.insert(name.to_owned(), value);
+        }
+    }
+
+    pub fn with_field<V: Into<Value>>(mut self, name: &str, value: V) -> Self {
+        self.add_field(name, value);
+        self
+    }
+
+    pub fn datetime(&self) -> &DateTime<Utc> {
+        &self.datetime
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mut object = Map::new();
+        for (name, value) in self.fields.iter() {
+            object.insert(name.clone(), value.clone());
+        }
+        object.insert("message".into(), self.message.clone().into());
+        object.insert("datetime".into(), self.datetime_string().into());
+        object.insert("timestamp".into(), self.datetime.timestamp_micros().into());
+        object.insert("timestamp_desc".into(), self.timestamp_desc.clone().into());
+        object.insert("data_type".into(), self.data_type.clone().into());
+        Value::Object(object)
+    }
+
+    fn datetime_string(&self) -> String {
+        self.datetime.to_rfc3339_opts(SecondsFormat::Micros, false)
+    }
+
+    fn column(&self, name: &str) -> String {
+        match name {
+            "message" => self.message.clone(),
+            "datetime" => self.datetime_string(),
+            "timestamp" => self.datetime.timestamp_micros().to_string(),
+            "timestamp_desc" => self.timestamp_desc.clone(),
+            "data_type" => self.data_type.clone(),
+            name => self.fields.get(name).map(value_to_string).unwrap_or_default(),
+        }
+    }
+}
+
+/// writes events in a format which can be imported by Timesketch.
+///
+/// CSV files need a header which contains all fields, so events are
+/// buffered until [`TimesketchWriter::flush`] is called. Events which are
+/// written afterwards use the same header; fields which are not part of the
+/// header are omitted
+pub struct TimesketchWriter<W: Write> {
+    format: TimesketchFormat,
+
+    /// the output, until it is handed over to `csv_writer`
+    writer: Option<W>,
+    csv_writer: Option<csv::Writer<W>>,
+    header: Vec<String>,
+    pending: Vec<TimesketchEvent>,
+}
+
+impl<W: Write> TimesketchWriter<W> {
+    pub fn new(writer: W, format: TimesketchFormat) -> Self {
+        Self {
+            format,
+            writer: Some(writer),
+            csv_writer: None,
+            header: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn write(&mut self, event: TimesketchEvent) -> anyhow::Result<()> {
+        match (self.writer.as_mut(), self.csv_writer.as_mut()) {
+            (_, Some(csv_writer)) => {
+                csv_writer.write_record(self.header.iter().map(|c| event.column(c)))?;
+            }
+            (Some(writer), None) if self.format == TimesketchFormat::Jsonl => {
+                serde_json::to_writer(&mut *writer, &event.to_json())?;
+                writeln!(writer)?;
+            }
+            _ => self.pending.push(event),
+        }
+        Ok(())
+    }
+
+    /// writes all buffered events
+    pub fn flush(&mut self) -> anyhow::Result<()> {
+        if self.format == TimesketchFormat::Csv && self.csv_writer.is_none() {
+            let mut header: Vec<String> = COLUMNS.iter().map(|c| c.to_string()).collect();
+            let mut fields: Vec<&String> = self
+                .pending
+                .iter()
+                .flat_map(|e| e.fields.keys())
+                .filter(|f| !header.contains(f))
+                .collect();
+            fields.sort();
+            fields.dedup();
+            header.extend(fields.into_iter().cloned());
+
+            let writer = self.writer.take().expect("missing output");
+            let mut csv_writer = csv::Writer::from_writer(writer);
+            csv_writer.write_record(&header)?;
+            self.header = header;
+            self.csv_writer = Some(csv_writer);
+
+            for event in std::mem::take(&mut self.pending) {
+                self.write(event)?;
+            }
+        }
+
+        if let Some(csv_writer) = self.csv_writer.as_mut() {
+            csv_writer.flush()?;
+        }
+        if let Some(writer) = self.writer.as_mut() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    }
+}
@@ -0,0 +1,88 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::bail;
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, SecondsFormat, Utc,
+};
+
+use crate::TimeZoneSpec;
+
+/// the way timestamps are displayed
+#[derive(Clone, Debug)]
+pub enum TimestampFormat {
+    /// RFC 3339, with the given precision of seconds
+    Rfc3339(SecondsFormat),
+
+    /// seconds since 1970-01-01T00:00:00Z
+    Epoch,
+
+    /// user defined format, using `strftime` syntax
+    Custom(String),
+}
+
+impl FromStr for TimestampFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &s.to_lowercase()[..] {
+            "rfc3339" => Ok(Self::Rfc3339(SecondsFormat::AutoSi)),
+            "epoch" => Ok(Self::Epoch),
+            _ => {
+                if !s.contains('%') {
+                    bail!("invalid timestamp format '{s}', use 'rfc3339', 'epoch' or a strftime format string");
+                }
+                if StrftimeItems::new(s).any(|i| matches!(i, Item::Error)) {
+                    bail!("invalid strftime format string: '{s}'");
+                }
+                Ok(Self::Custom(s.to_owned()))
+            }
+        }
+    }
+}
+
+impl Display for TimestampFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rfc3339(_) => "rfc3339".fmt(f),
+            Self::Epoch => "epoch".fmt(f),
+            Self::Custom(fmt) => fmt.fmt(f),
+        }
+    }
+}
+
+/// converts timestamps into strings, using a specific time zone and format
+#[derive(Clone, Debug)]
+pub struct TimestampFormatter {
+    timezone: TimeZoneSpec,
+    format: TimestampFormat,
+}
+
+impl Default for TimestampFormatter {
+    fn default() -> Self {
+        Self {
+            timezone: TimeZoneSpec::default(),
+            format: TimestampFormat::Rfc3339(SecondsFormat::AutoSi),
+        }
+    }
+}
+
+impl TimestampFormatter {
+    pub fn new(timezone: TimeZoneSpec, format: TimestampFormat) -> Self {
+        Self { timezone, format }
+    }
+
+    pub fn timezone(&self) -> &TimeZoneSpec {
+        &self.timezone
+    }
+
+    pub fn format(&self, ts: &DateTime<Utc>) -> String {
+        match &self.format {
+            TimestampFormat::Epoch => ts.timestamp().to_string(),
+            TimestampFormat::Rfc3339(precision) => {
+                self.timezone.from_utc(ts).to_rfc3339_opts(*precision, true)
+            }
+            TimestampFormat::Custom(fmt) => self.timezone.from_utc(ts).format(fmt).to_string(),
+        }
+    }
+}
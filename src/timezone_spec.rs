@@ -0,0 +1,103 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, bail};
+use chrono::{DateTime, FixedOffset, LocalResult, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// a time zone as specified by the user, either by using an IANA name (such
+/// as `Europe/Berlin`) or a fixed offset (such as `+02:00`)
+#[derive(Clone, Debug)]
+pub enum TimeZoneSpec {
+    Named(Tz),
+    Fixed(FixedOffset),
+}
+
+impl Default for TimeZoneSpec {
+    fn default() -> Self {
+        Self::Named(Tz::UTC)
+    }
+}
+
+impl TimeZoneSpec {
+    /// interprets a naive date and time as local time in this time zone
+    pub fn to_utc(&self, naive: &NaiveDateTime) -> anyhow::Result<DateTime<Utc>> {
+        let local = match self {
+            Self::Named(tz) => tz
+                .from_local_datetime(naive)
+                .map(|ts| ts.with_timezone(&Utc)),
+            Self::Fixed(offset) => offset
+                .from_local_datetime(naive)
+                .map(|ts| ts.with_timezone(&Utc)),
+        };
+
+        match local {
+            LocalResult::Single(ts) => Ok(ts),
+            LocalResult::Ambiguous(earliest, _) => {
+                log::warn!("'{naive}' is ambiguous in time zone {self}, using the earlier point in time");
+                Ok(earliest)
+            }
+            LocalResult::None => Err(anyhow!("'{naive}' does not exist in time zone {self}")),
+        }
+    }
+
+    /// converts a timestamp into this time zone. The result uses a fixed
+    /// offset, which is the offset valid for this specific timestamp.
+    pub fn from_utc(&self, ts: &DateTime<Utc>) -> DateTime<FixedOffset> {
+        let offset = match self {
+            Self::Named(tz) => ts.with_timezone(tz).offset().fix(),
+            Self::Fixed(offset) => *offset,
+        };
+        ts.with_timezone(&offset)
+    }
+
+    fn parse_offset(s: &str) -> anyhow::Result<FixedOffset> {
+        let (sign, value) = match s.chars().next() {
+            Some('+') => (1, &s[1..]),
+            Some('-') => (-1, &s[1..]),
+            _ => bail!("an offset must start with '+' or '-'"),
+        };
+        if !value.chars().all(|c| c.is_ascii_digit() || c == ':') {
+            bail!("an offset must consist of digits, such as '+02:00' or '-0530'");
+        }
+
+        let (hours, minutes) = match value.split_once(':') {
+            Some((h, m)) => (h, m),
+            None if value.len() > 2 => value.split_at(value.len() - 2),
+            None => (value, "0"),
+        };
+
+        let hours: i32 = hours.parse()?;
+        let minutes: i32 = minutes.parse()?;
+        if hours > 23 || minutes > 59 {
+            bail!("offset out of range");
+        }
+
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .ok_or_else(|| anyhow!("offset out of range"))
+    }
+}
+
+impl FromStr for TimeZoneSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with('+') || s.starts_with('-') {
+            Self::parse_offset(s)
+                .map(Self::Fixed)
+                .map_err(|why| anyhow!("invalid time zone offset '{s}': {why}"))
+        } else {
+            Tz::from_str(s)
+                .map(Self::Named)
+                .map_err(|_| anyhow!("unknown time zone: '{s}'"))
+        }
+    }
+}
+
+impl Display for TimeZoneSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Named(tz) => tz.name().fmt(f),
+            Self::Fixed(offset) => offset.fmt(f),
+        }
+    }
+}